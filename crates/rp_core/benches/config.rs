@@ -0,0 +1,173 @@
+#![feature(test)]
+
+extern crate test;
+
+use rp_core::{
+    config::{Changeable, Config, Node},
+    schema::Schema,
+};
+use std::{io::Cursor, sync::Arc};
+use test::Bencher;
+
+// enough firewall rules to make anything quadratic stand out
+const RULES: usize = 10_000;
+
+fn schema() -> anyhow::Result<Arc<Schema>> {
+    let schema = r#"---
+templates:
+  "rule":
+    range:
+      lower:
+        inclusive: 1
+      upper:
+        inclusive: 1000000
+      integer: true
+  "address":
+    address:
+      family: ipv4
+nodes:
+  "firewall":
+    subnodes:
+      "rules":
+        ordered: true
+        source:
+          id: rule
+          template: rule
+        node:
+          properties:
+            "action":
+              values:
+                - literal: accept
+                - literal: drop
+            "source":
+              must:
+                - property: [action]
+              values:
+                - template: address"#;
+
+    let mut schema = Schema::from_yaml_file(Cursor::new(schema))?;
+    schema.validate()?;
+    schema.build_regex_cache()?;
+
+    // the binary schema has the node locators multi nodes need
+    let mut binary = Vec::new();
+    schema.to_binary_file(&mut binary)?;
+    Ok(Arc::new(Schema::from_binary(&binary)?))
+}
+
+fn rules(config: &Config) -> Arc<rp_core::config::ConfigNode> {
+    config
+        .get_node_with_name("firewall")
+        .and_then(|firewall| firewall.get_node_with_name("rules").ok().flatten())
+        .expect("no rules node")
+}
+
+fn add_rules(config: &Config) -> anyhow::Result<()> {
+    let rules = rules(config);
+    for index in 1..=RULES {
+        let rule = rules
+            .get_node_with_name(&index.to_string())?
+            .expect("rule not created");
+        rule.get_property("action")
+            .expect("no action")
+            .set("accept")?;
+        rule.get_property("source")
+            .expect("no source")
+            .set(&format!("10.0.{}.{}", index / 256, index % 256))?;
+    }
+
+    Ok(())
+}
+
+fn populated(schema: &Arc<Schema>) -> anyhow::Result<Config> {
+    let config = Config::from_schema(Arc::downgrade(schema))?;
+    add_rules(&config)?;
+    config.apply_changes()?;
+    Ok(config)
+}
+
+#[bench]
+fn construct(b: &mut Bencher) -> anyhow::Result<()> {
+    let schema = schema()?;
+
+    b.iter(|| {
+        let config = Config::from_schema(Arc::downgrade(&schema)).expect("building failed");
+        add_rules(&config).expect("adding rules failed");
+        config
+    });
+    Ok(())
+}
+
+#[bench]
+fn edit(b: &mut Bencher) -> anyhow::Result<()> {
+    let schema = schema()?;
+    let config = populated(&schema)?;
+    let rules = rules(&config);
+
+    let mut index = 0;
+    b.iter(|| {
+        index = index % RULES + 1;
+        let rule = rules
+            .get_node_with_name(&index.to_string())
+            .ok()
+            .flatten()
+            .expect("rule not found");
+        rule.get_property("action")
+            .expect("no action")
+            .set("drop")
+            .expect("editing failed");
+    });
+    Ok(())
+}
+
+#[bench]
+fn apply(b: &mut Bencher) -> anyhow::Result<()> {
+    let schema = schema()?;
+    let config = populated(&schema)?;
+    let rules = rules(&config);
+
+    let mut drop = false;
+    b.iter(|| {
+        drop = !drop;
+        let rule = rules
+            .get_node_with_name("1")
+            .ok()
+            .flatten()
+            .expect("rule not found");
+        rule.get_property("action")
+            .expect("no action")
+            .set(if drop { "drop" } else { "accept" })
+            .expect("editing failed");
+        config.apply_changes().expect("applying failed")
+    });
+    Ok(())
+}
+
+#[bench]
+fn save(b: &mut Bencher) -> anyhow::Result<()> {
+    let schema = schema()?;
+    let config = populated(&schema)?;
+
+    b.iter(|| {
+        let mut save = Vec::new();
+        config.save_config(&mut save).expect("saving failed");
+        save
+    });
+    Ok(())
+}
+
+#[bench]
+fn load(b: &mut Bencher) -> anyhow::Result<()> {
+    let schema = schema()?;
+    let mut save = Vec::new();
+    populated(&schema)?.save_config(&mut save)?;
+
+    b.iter(|| {
+        let config = Config::from_schema(Arc::downgrade(&schema)).expect("building failed");
+        config
+            .load_config(Cursor::new(&save))
+            .expect("loading failed");
+        config
+    });
+    Ok(())
+}
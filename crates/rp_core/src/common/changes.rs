@@ -0,0 +1,201 @@
+use crate::{config::NodeId, error::ApplyError};
+use std::{
+    cell::RefCell,
+    collections::HashMap,
+    sync::{Condvar, Mutex, MutexGuard, PoisonError},
+};
+
+thread_local! {
+    // the change locks this thread holds, and how. edits go through other edits
+    // (inserting a node creates it) and loading a config applies it, so a thread
+    // doesn't wait on a lock it already holds
+    static HELD: RefCell<HashMap<usize, Held>> = RefCell::new(HashMap::new());
+}
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+enum Held {
+    Edit,
+    Apply,
+}
+
+// shared by every node and property of a config. any number of edits may be made
+// at once, but changes are validated and applied (or discarded) with no edits in
+// between. the nodes that were edited are kept track of so that only they and
+// what depends on them are gone through then, not the whole config
+#[derive(Debug, Default)]
+pub struct Changes {
+    lock: Mutex<LockState>,
+    released: Condvar,
+    changed: Mutex<HashMap<NodeId, Changed>>,
+}
+
+#[derive(Debug, Default)]
+struct LockState {
+    editing: usize,
+    applying: bool,
+    // threads waiting to apply, which new edits wait behind so they get to
+    waiting: usize,
+    // a thread waiting to apply in the middle of its own edit
+    upgrading: bool,
+    // threads waiting for the lock to be released, which otherwise nobody needs
+    // to be woken for
+    asleep: usize,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum Changed {
+    // the node's properties, or which nodes a multi node has
+    Own,
+    // the node hasn't been applied yet, so neither has anything in it
+    Whole,
+}
+
+pub struct ChangeGuard<'a> {
+    lock: &'a Changes,
+    // how the lock was taken, and how the thread held it before, if it wasn't
+    // already held that way
+    taken: Option<(Held, Option<Held>)>,
+}
+
+impl Changes {
+    // waits for the changes being applied, if any
+    pub fn edit(&self) -> ChangeGuard<'_> {
+        if self.held().is_some() {
+            return self.guard(None);
+        }
+
+        let mut state = self.state();
+        while state.applying || state.upgrading || state.waiting > 0 {
+            state = self.wait(state);
+        }
+        state.editing += 1;
+
+        self.guard(Some((Held::Edit, None)))
+    }
+
+    // waits for every edit being made, other than the thread's own if it's in the
+    // middle of one. two threads can't both apply from within their edits, as
+    // each would wait for the other's edit to end
+    pub fn apply(&self) -> Result<ChangeGuard<'_>, ApplyError> {
+        let held = self.held();
+        if held == Some(Held::Apply) {
+            return Ok(self.guard(None));
+        }
+
+        let mut state = self.state();
+        if held == Some(Held::Edit) {
+            if state.upgrading {
+                return Err(ApplyError::AlreadyApplying);
+            }
+
+            state.upgrading = true;
+            while state.editing > 1 {
+                state = self.wait(state);
+            }
+            state.upgrading = false;
+            state.editing -= 1;
+        } else {
+            state.waiting += 1;
+            while state.applying || state.upgrading || state.editing > 0 {
+                state = self.wait(state);
+            }
+            state.waiting -= 1;
+        }
+        state.applying = true;
+
+        Ok(self.guard(Some((Held::Apply, held))))
+    }
+
+    // the node was edited
+    pub fn mark(&self, node: NodeId, changed: Changed) {
+        let mut nodes = self.changed.lock().unwrap_or_else(PoisonError::into_inner);
+        let existing = nodes.entry(node).or_insert(changed);
+        if *existing != Changed::Whole {
+            *existing = changed;
+        }
+    }
+
+    // the nodes edited since the changes were last applied or discarded. they're
+    // kept until then, so changes that fail to validate are checked again
+    pub fn changed(&self) -> HashMap<NodeId, Changed> {
+        self.changed
+            .lock()
+            .unwrap_or_else(PoisonError::into_inner)
+            .iter()
+            .map(|(node, changed)| (*node, *changed))
+            .collect()
+    }
+
+    pub fn clear(&self) {
+        self.changed
+            .lock()
+            .unwrap_or_else(PoisonError::into_inner)
+            .clear();
+    }
+
+    fn guard(&self, taken: Option<(Held, Option<Held>)>) -> ChangeGuard<'_> {
+        if let Some((held, _)) = taken {
+            HELD.with(|locks| locks.borrow_mut().insert(self.address(), held));
+        }
+
+        ChangeGuard { lock: self, taken }
+    }
+
+    fn held(&self) -> Option<Held> {
+        HELD.with(|locks| locks.borrow().get(&self.address()).copied())
+    }
+
+    // the lock guards nothing but its own state, which is never left half changed,
+    // so whatever panicked while holding it didn't break anything
+    fn state(&self) -> MutexGuard<'_, LockState> {
+        self.lock.lock().unwrap_or_else(PoisonError::into_inner)
+    }
+
+    fn wait<'a>(&self, mut state: MutexGuard<'a, LockState>) -> MutexGuard<'a, LockState> {
+        state.asleep += 1;
+        let mut state = self
+            .released
+            .wait(state)
+            .unwrap_or_else(PoisonError::into_inner);
+        state.asleep -= 1;
+        state
+    }
+
+    fn address(&self) -> usize {
+        self as *const Changes as usize
+    }
+}
+
+impl Drop for ChangeGuard<'_> {
+    fn drop(&mut self) {
+        let (taken, before) = match self.taken {
+            Some(taken) => taken,
+            None => return,
+        };
+
+        let mut state = self.lock.state();
+        match taken {
+            Held::Edit => state.editing -= 1,
+            Held::Apply => {
+                state.applying = false;
+                // back to the edit the changes were applied in
+                if before == Some(Held::Edit) {
+                    state.editing += 1;
+                }
+            }
+        }
+        let asleep = state.asleep > 0;
+        drop(state);
+
+        HELD.with(|locks| {
+            let mut locks = locks.borrow_mut();
+            match before {
+                Some(before) => locks.insert(self.lock.address(), before),
+                None => locks.remove(&self.lock.address()),
+            }
+        });
+        if asleep {
+            self.lock.released.notify_all();
+        }
+    }
+}
@@ -1,22 +1,41 @@
-use crate::error::FormatError;
+use super::Changes;
+use crate::{config::Arena, error::FormatError};
 use lazy_static::lazy_static;
 use regex_automata::Regex;
-use std::{collections::HashMap, rc::Rc};
+use std::{collections::HashMap, sync::Arc};
 
 #[derive(Debug)]
 pub struct Context {
     values: HashMap<String, String>,
-    parent: Option<Rc<Context>>,
+    parent: Option<Arc<Context>>,
+    // contexts built on one another belong to the same config
+    changes: Arc<Changes>,
+    arena: Arc<Arena>,
 }
 
 impl Context {
-    pub fn new(parent: Option<Rc<Context>>) -> Context {
+    pub fn new(parent: Option<Arc<Context>>) -> Context {
+        let (changes, arena) = match &parent {
+            Some(parent) => (Arc::clone(&parent.changes), Arc::clone(&parent.arena)),
+            None => (Arc::new(Changes::default()), Arc::new(Arena::default())),
+        };
+
         Context {
             values: HashMap::new(),
-            parent: parent.map(|p| Rc::clone(&p)),
+            parent: parent.map(|p| Arc::clone(&p)),
+            changes,
+            arena,
         }
     }
 
+    pub fn changes(&self) -> &Arc<Changes> {
+        &self.changes
+    }
+
+    pub(crate) fn arena(&self) -> &Arc<Arena> {
+        &self.arena
+    }
+
     pub fn get_value(&self, id: &str) -> Option<String> {
         match &self.values.get(id) {
             Some(value) => Some((*value).to_string()),
@@ -109,7 +128,7 @@ mod tests {
     fn parents() -> anyhow::Result<()> {
         let mut parent = Context::new(None);
         parent.set_value(String::from("id"), String::from("value"));
-        let child = Context::new(Some(Rc::new(parent)));
+        let child = Context::new(Some(Arc::new(parent)));
 
         let formatted = child.format(String::from("{id}"))?;
         assert_eq!(formatted, "value");
@@ -1,8 +1,11 @@
+mod changes;
 mod commands;
 mod context;
 pub mod helpers;
 mod shell_mode;
+pub mod source_registry;
 
+pub use changes::{ChangeGuard, Changed, Changes};
 pub use commands::{CommandFromArgs, CommandMetadata};
 pub use context::Context;
 pub use shell_mode::ShellMode;
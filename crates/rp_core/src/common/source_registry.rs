@@ -0,0 +1,57 @@
+use crate::{common::Context, error::SourceCommandError};
+use anyhow::anyhow;
+use lazy_static::lazy_static;
+use rp_log::*;
+use std::{collections::HashMap, sync::RwLock};
+
+pub type SourceResolver = fn(&Context) -> anyhow::Result<Vec<String>>;
+
+lazy_static! {
+    static ref FUNCTIONS: RwLock<HashMap<String, SourceResolver>> = RwLock::new(HashMap::new());
+    static ref PROPERTIES: RwLock<HashMap<String, SourceResolver>> = RwLock::new(HashMap::new());
+}
+
+pub fn register_function(name: &str, resolver: SourceResolver) -> anyhow::Result<()> {
+    FUNCTIONS
+        .write()
+        .map_err(|_| anyhow!("source function registry lock poisoned"))?
+        .insert(name.to_owned(), resolver);
+    trace!("Registered source function '{}'", name);
+    Ok(())
+}
+
+// properties are registered with their unformatted name (e.g. "{interface}.mtu")
+// and it's up to the resolver to pull the IDs it needs from the given context
+pub fn register_property(property: &str, resolver: SourceResolver) -> anyhow::Result<()> {
+    PROPERTIES
+        .write()
+        .map_err(|_| anyhow!("source property registry lock poisoned"))?
+        .insert(property.to_owned(), resolver);
+    trace!("Registered source property '{}'", property);
+    Ok(())
+}
+
+pub fn resolve_function(name: &str, context: &Context) -> anyhow::Result<Vec<String>> {
+    let resolver = *FUNCTIONS
+        .read()
+        .map_err(|_| anyhow!("source function registry lock poisoned"))?
+        .get(name)
+        .ok_or_else(|| SourceCommandError::UnknownFunction(name.to_owned()))?;
+
+    trace!("Resolving source function '{}'", name);
+    resolver(context)
+}
+
+pub fn resolve_property(property: &str, context: &Context) -> anyhow::Result<Vec<String>> {
+    let resolver = *PROPERTIES
+        .read()
+        .map_err(|_| anyhow!("source property registry lock poisoned"))?
+        .get(property)
+        .ok_or_else(|| SourceCommandError::UnknownProperty(property.to_owned()))?;
+
+    // formatting the property makes sure every ID it refers to is in the context
+    // before the resolver gets to it
+    let formatted = context.format(property.to_owned())?;
+    trace!("Resolving source property '{}' ({})", property, formatted);
+    resolver(context)
+}
@@ -0,0 +1,140 @@
+use crate::schema::{Condition, Schema, SchemaNode, Value, PARENT, WILDCARD};
+use std::collections::HashMap;
+
+// the nodes whose validity depends on other nodes, found from the schema. a
+// change to a node only needs the node itself and these validated again, not
+// the whole config. the paths have multi node children as wildcards
+#[derive(Debug, Default)]
+pub struct Dependents {
+    // nodes with conditions on a node's properties
+    conditions: HashMap<Vec<String>, Vec<ConditionDependent>>,
+    // nodes with properties referring to the nodes in a multi node
+    references: HashMap<Vec<String>, Vec<Vec<String>>>,
+}
+
+#[derive(Debug)]
+struct ConditionDependent {
+    // how much of its path the node with the condition shares with the node with
+    // the property, which is all the condition goes up to
+    shared: usize,
+    // the rest of the path of the node with the condition
+    rest: Vec<String>,
+}
+
+impl Dependents {
+    pub fn from_schema(schema: &Schema) -> Dependents {
+        let mut dependents = Dependents::default();
+        for (name, node) in &schema.nodes {
+            dependents.add_node(node, &[name.to_owned()]);
+        }
+        dependents
+    }
+
+    fn add_node(&mut self, node: &SchemaNode, path: &[String]) {
+        match node {
+            SchemaNode::SingleSchemaNode(single) => {
+                for property in single.properties.values() {
+                    for condition in property.conditions() {
+                        self.add_condition(condition, path, path);
+                    }
+
+                    for allowed in &property.values {
+                        if let Value::Reference(reference) = &allowed.value {
+                            self.references
+                                .entry(reference.clone())
+                                .or_default()
+                                .push(path.to_vec());
+                        }
+                    }
+                }
+
+                for (name, subnode) in &single.subnodes {
+                    self.add_node(subnode, &[path, &[name.to_owned()]].concat());
+                }
+            }
+            SchemaNode::MultiSchemaNode(multi) => {
+                // the multi node's conditions are written in the node it's in
+                for condition in &multi.when {
+                    self.add_condition(condition, &path[..path.len() - 1], path);
+                }
+
+                self.add_node(&multi.node, &[path, &[WILDCARD.to_owned()]].concat());
+            }
+        }
+    }
+
+    // the condition is written at base and checked by the node at the path
+    fn add_condition(&mut self, condition: &Condition, base: &[String], path: &[String]) {
+        let mut target = base.to_vec();
+        let mut shared = base.len();
+
+        for name in &condition.property {
+            if name == PARENT {
+                // schema validation ensures conditions don't go above the root
+                if target.pop().is_none() {
+                    return;
+                }
+                shared = shared.min(target.len());
+            } else {
+                target.push(name.to_owned());
+            }
+        }
+
+        // the condition is about a property, so the rest is the node it's in
+        target.pop();
+        self.conditions
+            .entry(target)
+            .or_default()
+            .push(ConditionDependent {
+                shared,
+                rest: path[shared..].to_vec(),
+            });
+    }
+
+    // the nodes the changes to the node at the path may make valid or invalid,
+    // with wildcards for every node in a multi node
+    pub fn of(&self, schema: &Schema, path: &[String]) -> Vec<Vec<String>> {
+        let mut dependents = Vec::new();
+
+        if let Some(conditions) = wildcard_path(schema, path)
+            .as_ref()
+            .and_then(|wildcards| self.conditions.get(wildcards))
+        {
+            for dependent in conditions {
+                if let Some(shared) = path.get(..dependent.shared) {
+                    dependents.push([shared, &dependent.rest].concat());
+                }
+            }
+        }
+
+        // references go only through single nodes, so they're found as they are
+        if let Some(references) = self.references.get(path) {
+            dependents.extend(references.iter().cloned());
+        }
+
+        dependents
+    }
+}
+
+// the path with the nodes in multi nodes as wildcards, or none if the schema
+// doesn't have it
+fn wildcard_path(schema: &Schema, path: &[String]) -> Option<Vec<String>> {
+    let (first, rest) = path.split_first()?;
+    let mut current = &**schema.nodes.get(first)?;
+    let mut wildcards = vec![first.to_owned()];
+
+    for name in rest {
+        current = match current {
+            SchemaNode::SingleSchemaNode(single) => {
+                wildcards.push(name.to_owned());
+                &**single.subnodes.get(name)?
+            }
+            SchemaNode::MultiSchemaNode(multi) => {
+                wildcards.push(WILDCARD.to_owned());
+                &multi.node
+            }
+        };
+    }
+
+    Some(wildcards)
+}
@@ -1,14 +1,23 @@
 mod changeable;
+mod dependents;
 mod node;
 mod node_name;
+mod position;
 mod property;
 mod save_load;
 
-use crate::{common::Context, schema::Schema};
+use crate::{
+    common::{Changed, Changes, Context},
+    error,
+    schema::{Schema, WILDCARD},
+};
 use anyhow::anyhow;
 pub use changeable::Changeable;
-pub use node::{ConfigNode, FromSchemaNode, Node};
+use dependents::Dependents;
+pub(crate) use node::Arena;
+pub use node::{ConfigNode, FromSchemaNode, Node, NodeId};
 pub use node_name::NodeName;
+pub use position::Position;
 pub use property::Property;
 use rp_log::*;
 pub use save_load::{
@@ -16,16 +25,25 @@ pub use save_load::{
     save::{save, Save, SaveBuilder},
 };
 use std::{
-    cell::RefCell,
-    collections::HashMap,
+    collections::{BTreeMap, HashMap, HashSet},
     io::{Read, Write},
-    rc::{Rc, Weak},
+    sync::{
+        atomic::{AtomicBool, Ordering},
+        Arc, Weak,
+    },
 };
 
 #[derive(Debug)]
 pub struct Config {
-    pub nodes: HashMap<String, Rc<ConfigNode>>,
-    unsaved: RefCell<bool>,
+    pub nodes: HashMap<String, Arc<ConfigNode>>,
+    unsaved: AtomicBool,
+    // whether the config as applied has been validated as a whole. until it has,
+    // validating just the changes isn't enough
+    validated: AtomicBool,
+    changes: Arc<Changes>,
+    arena: Arc<Arena>,
+    dependents: Dependents,
+    schema: Weak<Schema>,
 }
 
 impl Config {
@@ -33,27 +51,33 @@ impl Config {
         let mut nodes = HashMap::new();
         let mut context = Context::new(None);
         context.set_value(String::from("mock"), String::from("mock"));
-        let context_rc = Rc::new(context);
+        let context_rc = Arc::new(context);
 
-        if let Some(s) = schema.upgrade() {
+        let dependents = if let Some(s) = schema.upgrade() {
             for (name, node) in &s.nodes {
-                nodes.insert(
-                    name.to_owned(),
-                    Rc::new(ConfigNode::from_schema_node(
-                        Rc::clone(&context_rc),
-                        &name,
-                        Weak::clone(&schema),
-                        &node,
-                    )?),
-                );
+                let (_, node) = node::build(
+                    Arc::clone(&context_rc),
+                    None,
+                    &[name.to_owned()],
+                    Weak::clone(&schema),
+                    &node,
+                )?;
+                nodes.insert(name.to_owned(), node);
             }
+
+            Dependents::from_schema(&s)
         } else {
             return Err(anyhow!("Schema weak reference upgrading failed"));
-        }
+        };
 
         Ok(Config {
             nodes,
-            unsaved: RefCell::new(false),
+            unsaved: AtomicBool::new(false),
+            validated: AtomicBool::new(false),
+            changes: Arc::clone(context_rc.changes()),
+            arena: Arc::clone(context_rc.arena()),
+            dependents,
+            schema,
         })
     }
 
@@ -67,46 +91,236 @@ impl Config {
         names
     }
 
-    pub fn get_node_with_name(&self, name: &str) -> Option<Rc<ConfigNode>> {
-        self.nodes.get(name).map(|n| Rc::clone(n))
+    pub fn get_node_with_name(&self, name: &str) -> Option<Arc<ConfigNode>> {
+        self.nodes.get(name).map(|n| Arc::clone(n))
+    }
+
+    // the names a reference to the multi node at the path may take, as they'll be
+    // once the changes are applied
+    pub fn get_referenced_names(&self, path: &[String]) -> anyhow::Result<Vec<String>> {
+        if path.is_empty() {
+            return Err(anyhow!("Empty reference path"));
+        }
+
+        // schema validation ensures the path goes only through single nodes
+        let node = self
+            .find_node(path)?
+            .ok_or_else(|| anyhow!("Referenced node '{}' not in config", path.join(" ")))?;
+
+        Ok(node
+            .get_available_node_names()
+            .into_iter()
+            .filter_map(|name| match name {
+                NodeName::Literal(name) => Some(name),
+                NodeName::Multiple(_) => None,
+            })
+            .collect())
+    }
+
+    // the values a property at the path will have once the changes are applied.
+    // unlike traversing normally, nodes that don't exist aren't created and the
+    // property just has no values
+    pub fn get_pending_values(&self, path: &[String]) -> anyhow::Result<Vec<String>> {
+        let (key, path) = match path.split_last() {
+            Some(split) => split,
+            None => return Ok(Vec::new()),
+        };
+        let node = match self.find_node(path)? {
+            Some(node) => node,
+            None => return Ok(Vec::new()),
+        };
+
+        Ok(node
+            .get_property(key)
+            .map(|property| property.pending_values())
+            .unwrap_or_default())
+    }
+
+    // the node at the path as it'll be once the changes are applied, without
+    // creating any nodes along the way
+    pub fn find_node(&self, path: &[String]) -> anyhow::Result<Option<Arc<ConfigNode>>> {
+        Ok(self.arena.find(path))
+    }
+
+    // checks the config as it'll be once the changes are applied, reporting every
+    // problem at once
+    pub fn validate(&self) -> anyhow::Result<()> {
+        let mut violations = Vec::new();
+        for (name, node) in &self.nodes {
+            node.validate(self, &[name.to_owned()], &mut violations)?;
+        }
+
+        violations_to_result(violations)
+    }
+
+    // like validating, but only the changed nodes and the nodes that depend on
+    // them, which is enough once the config as applied has been validated
+    fn validate_changes(&self, changed: &BTreeMap<Vec<String>, Changed>) -> anyhow::Result<()> {
+        let schema = self
+            .schema
+            .upgrade()
+            .ok_or_else(|| anyhow!("Schema weak reference upgrading failed"))?;
+
+        let mut nodes = changed.clone();
+        for path in changed.keys() {
+            for dependent in self.dependents.of(&schema, path) {
+                for path in self.expand(&dependent)? {
+                    nodes.entry(path).or_insert(Changed::Own);
+                }
+            }
+        }
+
+        let mut violations = Vec::new();
+        for_changed(&nodes, |path, changed| {
+            if let Some(node) = self.find_node(path)? {
+                match changed {
+                    Changed::Own => node.validate_own(self, path, &mut violations)?,
+                    Changed::Whole => node.validate(self, path, &mut violations)?,
+                }
+            }
+            Ok(())
+        })?;
+
+        violations_to_result(violations)
+    }
+
+    // the nodes at the path, which has wildcards for every node in a multi node
+    fn expand(&self, path: &[String]) -> anyhow::Result<Vec<Vec<String>>> {
+        let mut paths = vec![Vec::new()];
+
+        for name in path {
+            let mut next = Vec::new();
+            for path in paths {
+                let names = if name == WILDCARD {
+                    match self.find_node(&path)? {
+                        Some(node) => node
+                            .get_available_node_names()
+                            .into_iter()
+                            .filter_map(|name| match name {
+                                NodeName::Literal(name) => Some(name),
+                                NodeName::Multiple(_) => None,
+                            })
+                            .collect(),
+                        None => Vec::new(),
+                    }
+                } else {
+                    vec![name.to_owned()]
+                };
+
+                for name in names {
+                    next.push([&path[..], &[name]].concat());
+                }
+            }
+            paths = next;
+        }
+
+        Ok(paths)
+    }
+
+    pub fn refresh_sources(&self) -> anyhow::Result<bool> {
+        let mut refreshed = false;
+
+        for node in self.nodes.values() {
+            refreshed = node.refresh_sources()? || refreshed;
+        }
+
+        Ok(refreshed)
     }
 
     pub fn save_config<T>(&self, dest: T) -> anyhow::Result<()>
     where
         T: Write,
     {
-        *self.unsaved.try_borrow_mut()? = false;
-        save(self, dest)
+        let schema = self
+            .schema
+            .upgrade()
+            .ok_or_else(|| anyhow!("Schema weak reference upgrading failed"))?;
+
+        self.unsaved.store(false, Ordering::SeqCst);
+        save(self, schema.version(), dest)
     }
 
     pub fn has_unsaved_changes(&self) -> anyhow::Result<bool> {
-        Ok(*self.unsaved.try_borrow()?)
+        Ok(self.unsaved.load(Ordering::SeqCst))
     }
 
-    pub fn load_config<T>(&self, src: T) -> anyhow::Result<()>
+    // a saved config that breaks the schema's constraints, e.g. one made before a
+    // mandatory property was added, is kept so that it can be fixed. the problems
+    // are returned instead, and applying anything checks the whole config again
+    // until they're gone
+    pub fn load_config<T>(&self, src: T) -> anyhow::Result<Option<error::ValidationError>>
     where
         T: Read,
     {
-        if let Err(e) = load(self, src) {
-            trace!("Caught error while loading config; discarding changes",);
-            self.discard_changes();
-            Err(e)
-        } else {
-            if self.is_clean() {
-                warn!(
-                    "Loading new configuration didn't actually load anything (configuration clean)"
-                );
-            } else {
-                self.apply_changes()?;
-                *self.unsaved.try_borrow_mut()? = false;
-            }
+        let schema = self
+            .schema
+            .upgrade()
+            .ok_or_else(|| anyhow!("Schema weak reference upgrading failed"))?;
 
-            Ok(())
+        // nothing else is edited while the loaded config is checked and applied
+        let _apply = self.changes.apply()?;
+
+        let violations = match load(self, &schema, src).and_then(|_| self.validate()) {
+            Ok(()) => None,
+            Err(e) => match e.downcast::<error::ValidationError>() {
+                Ok(violations) => Some(violations),
+                Err(e) => {
+                    trace!("Caught error while loading config; discarding changes",);
+                    self.discard_changes();
+                    return Err(e);
+                }
+            },
+        };
+
+        if self.is_clean() {
+            warn!("Loading new configuration didn't actually load anything (configuration clean)");
         }
+
+        self.commit()?;
+        self.validated.store(violations.is_none(), Ordering::SeqCst);
+        self.unsaved.store(false, Ordering::SeqCst);
+
+        Ok(violations)
     }
 }
 
 impl Config {
+    // the nodes edited since the changes were last applied or discarded, by path
+    // so that a node comes right before the nodes in it. nodes dropped since are
+    // left out
+    fn changed(&self) -> BTreeMap<Vec<String>, Changed> {
+        let mut nodes = BTreeMap::new();
+
+        for (id, changed) in self.changes.changed() {
+            if let Some(path) = self.arena.path(id) {
+                // a new node can take the place of an edited one being removed
+                let existing = nodes.entry(path).or_insert(changed);
+                if changed == Changed::Whole {
+                    *existing = changed;
+                }
+            }
+        }
+
+        nodes
+    }
+
+    // applies the changed nodes, parents first
+    fn commit(&self) -> anyhow::Result<bool> {
+        let mut edits = false;
+        for_changed(&self.changed(), |path, changed| {
+            if let Some(node) = self.find_node(path)? {
+                edits = match changed {
+                    Changed::Own => node.apply_own_changes()?,
+                    Changed::Whole => node.apply_changes()?,
+                } || edits;
+            }
+            Ok(())
+        })?;
+
+        self.changes.clear();
+        Ok(edits)
+    }
+
     pub fn pretty_print(&self) {
         for (name, node) in &self.nodes {
             println!("{} {{", name);
@@ -122,23 +336,52 @@ impl Changeable for Config {
     }
 
     fn apply_changes(&self) -> anyhow::Result<bool> {
-        let mut edits = false;
-
-        for node in self.nodes.values() {
-            edits = node.apply_changes()? || edits;
+        // edits made between validating and applying would go in unchecked
+        let _apply = self.changes.apply()?;
+        if self.validated.load(Ordering::SeqCst) {
+            self.validate_changes(&self.changed())?;
+        } else {
+            self.validate()?;
         }
 
+        let edits = self.commit()?;
+        self.validated.store(true, Ordering::SeqCst);
         if edits {
-            *self.unsaved.try_borrow_mut()? = true;
+            self.unsaved.store(true, Ordering::SeqCst);
         }
 
         Ok(edits)
     }
 
     fn discard_changes(&self) {
-        for node in self.nodes.values() {
-            node.discard_changes();
+        let _apply = match self.changes.apply() {
+            Ok(apply) => apply,
+            Err(e) => {
+                error!("Discarding changes failed: {}", e);
+                return;
+            }
+        };
+        let discarded = for_changed(&self.changed(), |path, changed| {
+            if let Some(node) = self.find_node(path)? {
+                match changed {
+                    Changed::Own => node.discard_own_changes(),
+                    Changed::Whole => node.discard_changes(),
+                }
+            }
+            Ok(())
+        });
+
+        if let Err(e) = discarded {
+            error!("Discarding changes failed: {}", e);
         }
+        self.changes.clear();
+    }
+}
+
+impl Drop for Config {
+    // the nodes refer to the arena they're in, so they'd keep each other around
+    fn drop(&mut self) {
+        self.arena.clear();
     }
 }
 
@@ -165,3 +408,40 @@ impl Load for Config {
         Ok(())
     }
 }
+
+// goes through the changed nodes, parents first. the nodes in a node that changed
+// as a whole are left to it
+fn for_changed<F>(changed: &BTreeMap<Vec<String>, Changed>, mut f: F) -> anyhow::Result<()>
+where
+    F: FnMut(&[String], Changed) -> anyhow::Result<()>,
+{
+    let mut whole: Option<&[String]> = None;
+
+    for (path, changed) in changed {
+        if whole.map_or(false, |whole| path.starts_with(whole)) {
+            continue;
+        }
+        if *changed == Changed::Whole {
+            whole = Some(path);
+        }
+
+        f(path, *changed)?;
+    }
+
+    Ok(())
+}
+
+// a violation can be found through more than one changed node
+fn violations_to_result(violations: Vec<error::Violation>) -> anyhow::Result<()> {
+    let mut seen = HashSet::new();
+    let violations: Vec<error::Violation> = violations
+        .into_iter()
+        .filter(|violation| seen.insert(violation.to_string()))
+        .collect();
+
+    if violations.is_empty() {
+        Ok(())
+    } else {
+        Err(error::ValidationError { violations }.into())
+    }
+}
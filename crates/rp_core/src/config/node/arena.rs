@@ -0,0 +1,376 @@
+use super::ConfigNode;
+use std::{
+    collections::HashMap,
+    fmt, mem,
+    sync::{Arc, PoisonError, RwLock, RwLockReadGuard, RwLockWriteGuard},
+};
+
+// where a node is kept in the arena. the generation tells a node apart from the
+// nodes that were in its slot before it, so an ID kept around after its node
+// was dropped doesn't find another one
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub struct NodeId {
+    slot: usize,
+    generation: usize,
+}
+
+// every node of a config, which with firewall rules and VLANs can be in the tens
+// of thousands. the nodes refer to the nodes in them by ID, and are found by
+// path without going down the tree. the arena is shared by the nodes through
+// their context, so the nodes are dropped by clearing it
+#[derive(Default)]
+pub struct Arena {
+    slots: RwLock<Slots>,
+}
+
+#[derive(Default)]
+struct Slots {
+    slots: Vec<Slot>,
+    // slots of dropped nodes, reused for new ones
+    free: Vec<usize>,
+    // the nodes by path. a node being removed keeps its path until the removal
+    // is applied, so a new node put in its place shadows it until then
+    index: HashMap<Arc<[String]>, NodeId>,
+    shadowed: HashMap<Arc<[String]>, Vec<NodeId>>,
+    // how many nodes are being removed, since until then the nodes found by path
+    // have to be checked for being in one
+    removed: usize,
+}
+
+#[derive(Default)]
+struct Slot {
+    generation: usize,
+    entry: Option<Entry>,
+}
+
+struct Entry {
+    // none while the nodes in the node are being built
+    node: Option<Arc<ConfigNode>>,
+    path: Arc<[String]>,
+    parent: Option<NodeId>,
+    removed: bool,
+}
+
+impl Arena {
+    // a place for the node at the path, which gets its ID before it's built so that
+    // the nodes in it can be built with their parent
+    pub fn reserve(&self, parent: Option<NodeId>, path: &[String]) -> NodeId {
+        let path: Arc<[String]> = path.into();
+        let mut slots = self.write();
+
+        let slot = match slots.free.pop() {
+            Some(slot) => slot,
+            None => {
+                slots.slots.push(Slot::default());
+                slots.slots.len() - 1
+            }
+        };
+        let id = NodeId {
+            slot,
+            generation: slots.slots[slot].generation,
+        };
+        slots.slots[slot].entry = Some(Entry {
+            node: None,
+            path: Arc::clone(&path),
+            parent,
+            removed: false,
+        });
+
+        if let Some(shadowed) = slots.index.insert(Arc::clone(&path), id) {
+            slots.shadowed.entry(path).or_default().push(shadowed);
+        }
+
+        id
+    }
+
+    pub fn fill(&self, id: NodeId, node: ConfigNode) -> Option<Arc<ConfigNode>> {
+        let mut slots = self.write();
+        let entry = slots.entry_mut(id)?;
+        let node = Arc::new(node);
+        entry.node = Some(Arc::clone(&node));
+
+        Some(node)
+    }
+
+    pub fn get(&self, id: NodeId) -> Option<Arc<ConfigNode>> {
+        self.read().entry(id).and_then(|entry| entry.node.clone())
+    }
+
+    pub fn path(&self, id: NodeId) -> Option<Vec<String>> {
+        self.read().entry(id).map(|entry| entry.path.to_vec())
+    }
+
+    // the node at the path as it'll be once the changes are applied, so not one
+    // that's being removed or that's in one
+    pub fn find(&self, path: &[String]) -> Option<Arc<ConfigNode>> {
+        let slots = self.read();
+        let indexed = slots.index.get(path)?;
+        let shadowed = slots.shadowed.get(path).into_iter().flatten().rev();
+
+        Some(indexed)
+            .into_iter()
+            .chain(shadowed)
+            .find(|id| slots.is_pending(**id))
+            .and_then(|id| slots.entry(*id))
+            .and_then(|entry| entry.node.clone())
+    }
+
+    // whether the node is being removed. the nodes in it go along with it
+    pub fn set_removed(&self, id: NodeId, removed: bool) {
+        let mut slots = self.write();
+        let changed = match slots.entry_mut(id) {
+            Some(entry) if entry.removed != removed => {
+                entry.removed = removed;
+                true
+            }
+            _ => false,
+        };
+
+        if changed && removed {
+            slots.removed += 1;
+        } else if changed {
+            slots.removed -= 1;
+        }
+    }
+
+    // drops the nodes along with the nodes in them. the nodes aren't gone through
+    // with the arena locked, as they lock the nodes in them
+    pub fn free<I>(&self, ids: I)
+    where
+        I: IntoIterator<Item = NodeId>,
+    {
+        let mut ids: Vec<NodeId> = ids.into_iter().collect();
+
+        while !ids.is_empty() {
+            let nodes: Vec<Arc<ConfigNode>> = {
+                let mut slots = self.write();
+                ids.iter().filter_map(|id| slots.take(*id)).collect()
+            };
+
+            ids = nodes.iter().flat_map(|node| node.child_ids()).collect();
+        }
+    }
+
+    // the nodes refer to the arena through their context, so they're only dropped
+    // once it lets go of them
+    pub fn clear(&self) {
+        let slots = mem::take(&mut *self.write());
+        drop(slots);
+    }
+
+    pub fn len(&self) -> usize {
+        let slots = self.read();
+        slots.slots.len() - slots.free.len()
+    }
+
+    // the lock guards nothing but the arena's own bookkeeping, which is never left
+    // half changed
+    fn read(&self) -> RwLockReadGuard<'_, Slots> {
+        self.slots.read().unwrap_or_else(PoisonError::into_inner)
+    }
+
+    fn write(&self) -> RwLockWriteGuard<'_, Slots> {
+        self.slots.write().unwrap_or_else(PoisonError::into_inner)
+    }
+}
+
+impl fmt::Debug for Arena {
+    // the nodes have the arena in their context, so it doesn't print them
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("Arena").field("nodes", &self.len()).finish()
+    }
+}
+
+impl Slots {
+    fn entry(&self, id: NodeId) -> Option<&Entry> {
+        self.slots
+            .get(id.slot)
+            .filter(|slot| slot.generation == id.generation)
+            .and_then(|slot| slot.entry.as_ref())
+    }
+
+    fn entry_mut(&mut self, id: NodeId) -> Option<&mut Entry> {
+        self.slots
+            .get_mut(id.slot)
+            .filter(|slot| slot.generation == id.generation)
+            .and_then(|slot| slot.entry.as_mut())
+    }
+
+    // neither the node nor any node it's in is being removed
+    fn is_pending(&self, id: NodeId) -> bool {
+        if self.removed == 0 {
+            return self.entry(id).is_some();
+        }
+
+        let mut next = Some(id);
+        while let Some(id) = next {
+            match self.entry(id) {
+                Some(entry) if !entry.removed => next = entry.parent,
+                _ => return false,
+            }
+        }
+
+        true
+    }
+
+    fn take(&mut self, id: NodeId) -> Option<Arc<ConfigNode>> {
+        let slot = self
+            .slots
+            .get_mut(id.slot)
+            .filter(|slot| slot.generation == id.generation)?;
+        let entry = slot.entry.take()?;
+        slot.generation += 1;
+        self.free.push(id.slot);
+
+        if entry.removed {
+            self.removed -= 1;
+        }
+        self.unindex(&entry.path, id);
+
+        entry.node
+    }
+
+    fn unindex(&mut self, path: &Arc<[String]>, id: NodeId) {
+        let shadowed = self.shadowed.get_mut(path);
+
+        if self.index.get(path) == Some(&id) {
+            match shadowed.and_then(|shadowed| shadowed.pop()) {
+                Some(next) => {
+                    self.index.insert(Arc::clone(path), next);
+                }
+                None => {
+                    self.index.remove(path);
+                }
+            }
+        } else if let Some(shadowed) = shadowed {
+            shadowed.retain(|other| *other != id);
+        }
+
+        if self
+            .shadowed
+            .get(path)
+            .map_or(false, |shadowed| shadowed.is_empty())
+        {
+            self.shadowed.remove(path);
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{super::build, *};
+    use crate::{
+        common::Context,
+        config::Node,
+        schema::{SchemaNode, SingleSchemaNode},
+    };
+    use std::sync::Weak;
+
+    // a node with a subnode in it
+    fn schema_node(depth: usize) -> SchemaNode {
+        let mut subnodes = HashMap::new();
+        if depth > 0 {
+            subnodes.insert(String::from("sub"), Box::new(schema_node(depth - 1)));
+        }
+
+        SchemaNode::SingleSchemaNode(SingleSchemaNode {
+            description: None,
+            subnodes,
+            properties: HashMap::new(),
+            locator: Arc::default(),
+        })
+    }
+
+    fn path(names: &[&str]) -> Vec<String> {
+        names.iter().map(|name| String::from(*name)).collect()
+    }
+
+    fn built(context: &Arc<Context>, name: &str) -> (NodeId, Arc<ConfigNode>) {
+        build(
+            Arc::clone(context),
+            None,
+            &path(&[name]),
+            Weak::new(),
+            &schema_node(1),
+        )
+        .expect("building node failed")
+    }
+
+    #[test]
+    fn find_by_path() {
+        let context = Arc::new(Context::new(None));
+        let arena = context.arena();
+        let (id, _) = built(&context, "node");
+        let (_, other) = built(&context, "other");
+        assert_eq!(arena.len(), 4);
+
+        assert!(Arc::ptr_eq(
+            &arena.find(&path(&["other"])).expect("node not found"),
+            &other
+        ));
+        assert!(arena.find(&path(&["node", "sub"])).is_some());
+        assert!(arena.find(&path(&["node", "none"])).is_none());
+
+        // nodes in a node being removed are as good as gone
+        arena.set_removed(id, true);
+        assert!(arena.find(&path(&["node", "sub"])).is_none());
+        assert!(arena.find(&path(&["other", "sub"])).is_some());
+        arena.set_removed(id, false);
+        assert!(arena.find(&path(&["node", "sub"])).is_some());
+    }
+
+    #[test]
+    fn new_node_shadows_removed() {
+        let context = Arc::new(Context::new(None));
+        let arena = context.arena();
+        let (removed, _) = built(&context, "node");
+        arena.set_removed(removed, true);
+        let (id, node) = built(&context, "node");
+
+        assert!(Arc::ptr_eq(
+            &arena.find(&path(&["node"])).expect("new node not found"),
+            &node
+        ));
+        let sub = arena
+            .find(&path(&["node", "sub"]))
+            .expect("new subnode not found");
+        assert!(Arc::ptr_eq(
+            &node
+                .get_node_with_name("sub")
+                .ok()
+                .flatten()
+                .expect("no subnode"),
+            &sub
+        ));
+
+        // the removed node is found again once the new one is gone
+        arena.free(Some(id));
+        arena.set_removed(removed, false);
+        assert!(Arc::ptr_eq(
+            &arena
+                .find(&path(&["node"]))
+                .expect("removed node not found"),
+            &arena.get(removed).expect("removed node dropped")
+        ));
+        assert_eq!(arena.len(), 2);
+    }
+
+    #[test]
+    fn free_drops_nodes_in_node() {
+        let context = Arc::new(Context::new(None));
+        let arena = context.arena();
+        let (id, _) = built(&context, "node");
+        arena.free(Some(id));
+
+        assert_eq!(arena.len(), 0);
+        assert!(arena.find(&path(&["node", "sub"])).is_none());
+
+        // an ID of a dropped node doesn't find the node in its slot now
+        let (_, node) = built(&context, "node");
+        assert!(arena.get(id).is_none());
+        assert!(Arc::ptr_eq(
+            &arena.find(&path(&["node"])).expect("node not found"),
+            &node
+        ));
+    }
+}
@@ -0,0 +1,444 @@
+use super::{
+    super::position::{reposition, restore_order},
+    Arena, ConfigNode, NodeId, Position,
+};
+use indexmap::IndexMap;
+use std::{
+    collections::{hash_map::Entry, HashMap, HashSet},
+    mem,
+    sync::Arc,
+};
+
+#[derive(Debug, PartialEq, Clone)]
+pub(super) enum NodeChange {
+    Unchanged,
+    New,
+    Removed,
+    Moved,
+}
+
+// the nodes of a multi node, which can be in the thousands (firewall rules, VLANs).
+// the changed nodes and the count are kept track of, so that applying and
+// discarding changes and checking the count don't go through every node. the
+// nodes themselves are in the arena, which is kept up to date with which of
+// them are being removed and which are dropped
+#[derive(Debug)]
+pub(super) struct Children {
+    // in the order they're added, which matters only for ordered multi nodes
+    nodes: IndexMap<String, (NodeId, NodeChange)>,
+    // the nodes that aren't unchanged
+    changed: HashSet<String>,
+    // the nodes that aren't being removed
+    pending: usize,
+    // the nodes added since the changes were last applied, even if they're being
+    // removed again, which are dropped if the changes are discarded
+    added: HashSet<String>,
+    // removed nodes that new ones took the place of, to go back to if the changes
+    // are discarded
+    replaced: HashMap<String, NodeId>,
+    // the order of the nodes before they were first moved, to go back to if the
+    // changes are discarded
+    committed_order: Option<Vec<String>>,
+    arena: Arc<Arena>,
+}
+
+impl Children {
+    pub fn new(arena: Arc<Arena>) -> Children {
+        Children {
+            nodes: IndexMap::new(),
+            changed: HashSet::new(),
+            pending: 0,
+            added: HashSet::new(),
+            replaced: HashMap::new(),
+            committed_order: None,
+            arena,
+        }
+    }
+
+    pub fn get(&self, name: &str) -> Option<(Arc<ConfigNode>, &NodeChange)> {
+        let (id, change) = self.nodes.get(name)?;
+        Some((self.arena.get(*id)?, change))
+    }
+
+    // removed nodes are still around until the removal is applied
+    pub fn get_pending(&self, name: &str) -> Option<Arc<ConfigNode>> {
+        self.nodes
+            .get(name)
+            .filter(|(_, change)| *change != NodeChange::Removed)
+            .and_then(|(id, _)| self.arena.get(*id))
+    }
+
+    pub fn pending_count(&self) -> usize {
+        self.pending
+    }
+
+    pub fn iter(&self) -> impl Iterator<Item = (&String, Arc<ConfigNode>, &NodeChange)> {
+        self.nodes
+            .iter()
+            .filter_map(move |(name, (id, change))| Some((name, self.arena.get(*id)?, change)))
+    }
+
+    // every node, including the ones new nodes took the place of
+    pub fn ids(&self) -> Vec<NodeId> {
+        self.nodes
+            .values()
+            .map(|(id, _)| *id)
+            .chain(self.replaced.values().copied())
+            .collect()
+    }
+
+    // a node that's already there is kept, and the new one dropped, unless it's
+    // being removed, in which case the new node takes its place
+    pub fn insert(
+        &mut self,
+        name: &str,
+        id: NodeId,
+        change: NodeChange,
+    ) -> Option<Arc<ConfigNode>> {
+        match self.nodes.get_mut(name) {
+            Some(entry) if entry.1 == NodeChange::Removed && change != NodeChange::Removed => {
+                let (removed, _) = mem::replace(entry, (id, change));
+                match self.replaced.entry(name.to_owned()) {
+                    Entry::Vacant(entry) => {
+                        entry.insert(removed);
+                    }
+                    // the node that was there first is the one to go back to
+                    Entry::Occupied(_) => self.arena.free(Some(removed)),
+                }
+                self.added.insert(name.to_owned());
+                self.pending += 1;
+            }
+            Some(_) => self.arena.free(Some(id)),
+            None => {
+                if change != NodeChange::Removed {
+                    self.pending += 1;
+                } else {
+                    self.arena.set_removed(id, true);
+                }
+                if change != NodeChange::Unchanged {
+                    self.changed.insert(name.to_owned());
+                }
+                if change == NodeChange::New {
+                    self.added.insert(name.to_owned());
+                }
+                self.nodes.insert(name.to_owned(), (id, change));
+            }
+        }
+
+        self.arena.get(self.nodes[name].0)
+    }
+
+    // the node stays until the removal is applied. a new node that took the place
+    // of a removed one gives it back
+    pub fn remove(&mut self, name: &str) -> bool {
+        let (id, change) = match self.nodes.get(name) {
+            Some((id, change)) => (*id, change.clone()),
+            None => return false,
+        };
+
+        match change {
+            NodeChange::Removed => return true,
+            NodeChange::New => match self.replaced.remove(name) {
+                Some(removed) => {
+                    self.nodes[name] = (removed, NodeChange::Removed);
+                    self.added.remove(name);
+                    self.arena.free(Some(id));
+                }
+                None => {
+                    self.nodes[name].1 = NodeChange::Removed;
+                    self.arena.set_removed(id, true);
+                }
+            },
+            NodeChange::Unchanged | NodeChange::Moved => {
+                self.nodes[name].1 = NodeChange::Removed;
+                self.changed.insert(name.to_owned());
+                self.arena.set_removed(id, true);
+            }
+        }
+
+        self.pending -= 1;
+        true
+    }
+
+    // both the node and the anchor have to be there
+    pub fn reposition(&mut self, name: &str, position: &Position) {
+        if !self.nodes.contains_key(name) || !self.nodes.contains_key(position.anchor()) {
+            return;
+        }
+
+        if self.committed_order.is_none() {
+            self.committed_order = Some(self.nodes.keys().cloned().collect());
+        }
+        reposition(&mut self.nodes, name, position);
+
+        if let Some((_, change)) = self.nodes.get_mut(name) {
+            if *change == NodeChange::Unchanged {
+                *change = NodeChange::Moved;
+                self.changed.insert(name.to_owned());
+            }
+        }
+    }
+
+    // drops the nodes for good, returning whether there were any
+    pub fn drop_where<F>(&mut self, mut f: F) -> bool
+    where
+        F: FnMut(&String, &NodeChange) -> bool,
+    {
+        let mut dropped = HashSet::new();
+        let mut ids = Vec::new();
+        let pending = &mut self.pending;
+
+        self.nodes.retain(|name, (id, change)| {
+            if !f(name, change) {
+                return true;
+            }
+
+            if *change != NodeChange::Removed {
+                *pending -= 1;
+            }
+            dropped.insert(name.to_owned());
+            ids.push(*id);
+            false
+        });
+
+        if dropped.is_empty() {
+            return false;
+        }
+
+        self.arena.free(ids);
+        self.changed.retain(|name| !dropped.contains(name));
+        if let Some(order) = self.committed_order.as_mut() {
+            order.retain(|name| !dropped.contains(name));
+        }
+
+        true
+    }
+
+    // removed nodes are dropped and the rest are as they are now. returns whether
+    // any nodes changed
+    pub fn commit(&mut self) -> bool {
+        let edits = !self.changed.is_empty();
+        if self.has_changed(NodeChange::Removed) {
+            self.drop_where(|_, change| *change == NodeChange::Removed);
+        }
+
+        for name in mem::take(&mut self.changed) {
+            if let Some((_, change)) = self.nodes.get_mut(&name) {
+                *change = NodeChange::Unchanged;
+            }
+        }
+        self.added.clear();
+        self.arena.free(self.replaced.drain().map(|(_, id)| id));
+        self.committed_order = None;
+
+        edits
+    }
+
+    // new nodes are dropped and the rest go back to how they were
+    pub fn discard(&mut self) {
+        for (name, removed) in mem::take(&mut self.replaced) {
+            if let Some(entry) = self.nodes.get_mut(&name) {
+                if entry.1 != NodeChange::Removed {
+                    self.pending -= 1;
+                }
+                let (new, _) = mem::replace(entry, (removed, NodeChange::Removed));
+                self.added.remove(&name);
+                self.arena.free(Some(new));
+            }
+        }
+
+        let added = mem::take(&mut self.added);
+        if !added.is_empty() {
+            self.drop_where(|name, _| added.contains(name));
+        }
+
+        for name in mem::take(&mut self.changed) {
+            if let Some((id, change)) = self.nodes.get_mut(&name) {
+                if *change == NodeChange::Removed {
+                    self.pending += 1;
+                    self.arena.set_removed(*id, false);
+                }
+                *change = NodeChange::Unchanged;
+            }
+        }
+
+        if let Some(order) = self.committed_order.take() {
+            restore_order(&mut self.nodes, &order);
+        }
+    }
+
+    // puts the nodes in the order they're in the names, e.g. when loaded from a
+    // save. nodes that aren't in the names go last
+    pub fn restore_order(&mut self, names: &[String]) {
+        restore_order(&mut self.nodes, names);
+    }
+
+    fn has_changed(&self, change: NodeChange) -> bool {
+        self.changed
+            .iter()
+            .any(|name| self.nodes.get(name).map_or(false, |(_, c)| *c == change))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{
+        common::Context,
+        config::FromSchemaNode,
+        schema::{SchemaNode, SingleSchemaNode},
+    };
+    use std::{collections::HashMap, sync::Weak};
+
+    // a node built into the children's arena
+    fn node(children: &Children, name: &str) -> NodeId {
+        let schema_node = SchemaNode::SingleSchemaNode(SingleSchemaNode {
+            description: None,
+            subnodes: HashMap::new(),
+            properties: HashMap::new(),
+            locator: Arc::default(),
+        });
+        let path = [String::from("node"), name.to_owned()];
+        let id = children.arena.reserve(None, &path);
+        let node = ConfigNode::from_schema_node(
+            Arc::new(Context::new(None)),
+            &path,
+            id,
+            Weak::new(),
+            &schema_node,
+        )
+        .expect("building node failed");
+        children.arena.fill(id, node);
+        id
+    }
+
+    fn names(children: &Children) -> Vec<&str> {
+        children.iter().map(|(name, _, _)| name.as_str()).collect()
+    }
+
+    fn children(names: &[&str]) -> Children {
+        let mut children = Children::new(Arc::default());
+        for name in names {
+            children.insert(name, node(&children, name), NodeChange::Unchanged);
+        }
+        children
+    }
+
+    fn path(name: &str) -> Vec<String> {
+        vec![String::from("node"), name.to_owned()]
+    }
+
+    #[test]
+    fn insert_keeps_existing() {
+        let mut children = children(&["a"]);
+        let (existing, _) = children.get("a").expect("node not inserted");
+        let kept = children
+            .insert("a", node(&children, "a"), NodeChange::New)
+            .expect("node not in arena");
+
+        assert!(Arc::ptr_eq(&existing, &kept));
+        assert_eq!(children.pending_count(), 1);
+        assert_eq!(children.arena.len(), 1);
+    }
+
+    #[test]
+    fn commit_drops_removed() {
+        let mut children = children(&["a", "b", "c"]);
+        children.remove("b");
+        children.insert("d", node(&children, "d"), NodeChange::New);
+        assert_eq!(children.pending_count(), 3);
+        assert!(children.get_pending("b").is_none());
+        assert!(children.arena.find(&path("b")).is_none());
+
+        children.commit();
+        assert_eq!(names(&children), ["a", "c", "d"]);
+        assert!(children
+            .iter()
+            .all(|(_, _, change)| *change == NodeChange::Unchanged));
+        assert!(children.changed.is_empty());
+        assert_eq!(children.arena.len(), 3);
+    }
+
+    #[test]
+    fn discard_restores() {
+        let mut children = children(&["a", "b", "c"]);
+        children.remove("a");
+        children.insert("d", node(&children, "d"), NodeChange::New);
+        children.reposition("c", &Position::Before(String::from("a")));
+        assert_eq!(names(&children), ["c", "a", "b", "d"]);
+
+        children.discard();
+        assert_eq!(names(&children), ["a", "b", "c"]);
+        assert_eq!(children.pending_count(), 3);
+        assert!(children.changed.is_empty());
+        assert!(children.arena.find(&path("a")).is_some());
+        assert!(children.arena.find(&path("d")).is_none());
+        assert_eq!(children.arena.len(), 3);
+    }
+
+    #[test]
+    fn new_node_replaces_removed() {
+        let mut children = children(&["a", "b"]);
+        let (removed, _) = children.get("a").expect("node not inserted");
+        children.remove("a");
+        children.insert("a", node(&children, "a"), NodeChange::New);
+        assert_eq!(children.pending_count(), 2);
+        let replacing = children.get_pending("a").expect("node not replaced");
+        assert!(!Arc::ptr_eq(&removed, &replacing));
+        assert!(Arc::ptr_eq(
+            &replacing,
+            &children.arena.find(&path("a")).expect("new node not found")
+        ));
+
+        // removing the new node removes the one it replaced
+        children.remove("a");
+        assert_eq!(children.pending_count(), 1);
+        assert!(children.arena.find(&path("a")).is_none());
+        children.insert("a", node(&children, "a"), NodeChange::New);
+
+        children.discard();
+        assert_eq!(names(&children), ["a", "b"]);
+        assert_eq!(children.pending_count(), 2);
+        assert!(Arc::ptr_eq(
+            &removed,
+            &children.get_pending("a").expect("replaced node not back")
+        ));
+        assert!(Arc::ptr_eq(
+            &removed,
+            &children
+                .arena
+                .find(&path("a"))
+                .expect("replaced node not found")
+        ));
+        assert_eq!(children.arena.len(), 2);
+    }
+
+    #[test]
+    fn commit_drops_replaced() {
+        let mut children = children(&["a"]);
+        children.remove("a");
+        children.insert("a", node(&children, "a"), NodeChange::New);
+        assert_eq!(children.arena.len(), 2);
+
+        children.commit();
+        assert_eq!(children.pending_count(), 1);
+        assert_eq!(children.arena.len(), 1);
+        assert!(Arc::ptr_eq(
+            &children.get_pending("a").expect("new node not kept"),
+            &children.arena.find(&path("a")).expect("new node not found")
+        ));
+    }
+
+    #[test]
+    fn discard_drops_removed_new_node() {
+        let mut children = children(&["a"]);
+        children.insert("b", node(&children, "b"), NodeChange::New);
+        children.remove("b");
+        assert_eq!(children.pending_count(), 1);
+
+        children.discard();
+        assert_eq!(names(&children), ["a"]);
+        assert_eq!(children.pending_count(), 1);
+        assert_eq!(children.arena.len(), 1);
+    }
+}
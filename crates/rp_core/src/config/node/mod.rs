@@ -1,30 +1,67 @@
+mod arena;
+mod children;
 mod multi_config_node;
 mod single_config_node;
 
-use super::{Changeable, Load, LoadSource, NodeName, Property, Save, SaveBuilder};
+use super::{
+    Changeable, Config, Load, LoadSource, NodeName, Position, Property, Save, SaveBuilder,
+};
 use crate::{
     common::Context,
+    error::Violation,
     schema::{Schema, SchemaNode},
 };
+use anyhow::anyhow;
+pub(crate) use arena::Arena;
+pub use arena::NodeId;
 use enum_dispatch::enum_dispatch;
 use multi_config_node::MultiConfigNode;
 use single_config_node::SingleConfigNode;
 use std::{
     collections::HashMap,
-    rc::{Rc, Weak},
+    sync::{Arc, Weak},
 };
 
 #[enum_dispatch]
 pub trait Node: Changeable + Save + Load {
     fn name(&self) -> String;
+    fn description(&self) -> Option<String>;
 
     fn get_available_node_names(&self) -> Vec<NodeName>;
     fn get_available_property_names(&self) -> Vec<String>;
-    fn get_node_with_name(&self, name: &str) -> anyhow::Result<Option<Rc<ConfigNode>>>;
+    fn get_node_with_name(&self, name: &str) -> anyhow::Result<Option<Arc<ConfigNode>>>;
+    // unlike getting a node with the name, never creates one. nodes that are being
+    // removed aren't found
+    fn find_node(&self, name: &str) -> anyhow::Result<Option<Arc<ConfigNode>>>;
     fn get_property(&self, property: &str) -> Option<&Property>;
     fn get_property_values(&self, of_property: Option<String>) -> HashMap<String, Vec<String>>;
 
     fn remove_subnode(&self, subnode: &str) -> anyhow::Result<()>;
+    // only nodes in ordered multi nodes can be put in a specific place
+    fn insert_subnode(&self, subnode: &str, position: &Position)
+        -> anyhow::Result<Arc<ConfigNode>>;
+    fn move_subnode(&self, subnode: &str, position: &Position) -> anyhow::Result<()>;
+    fn refresh_sources(&self) -> anyhow::Result<bool>;
+    // collects everything wrong with the node as it'll be once its changes are
+    // applied. The path includes the node's own name
+    fn validate(
+        &self,
+        config: &Config,
+        path: &[String],
+        violations: &mut Vec<Violation>,
+    ) -> anyhow::Result<()>;
+    // like validating, but not the nodes in it: only the node's properties, or for
+    // a multi node which nodes it has
+    fn validate_own(
+        &self,
+        config: &Config,
+        path: &[String],
+        violations: &mut Vec<Violation>,
+    ) -> anyhow::Result<()>;
+    // like applying and discarding changes, but only those made to the node
+    // itself and not to the nodes in it
+    fn apply_own_changes(&self) -> anyhow::Result<bool>;
+    fn discard_own_changes(&self);
 
     fn pretty_print(&self, indent: usize);
 }
@@ -33,14 +70,43 @@ pub trait FromSchemaNode<TBuiltFrom>
 where
     Self: std::marker::Sized,
 {
+    // the path includes the node's own name. the node's place in the arena is
+    // already reserved under the ID
     fn from_schema_node(
-        context: Rc<Context>,
-        name: &str,
+        context: Arc<Context>,
+        path: &[String],
+        id: NodeId,
         schema: Weak<Schema>,
         schema_node: &TBuiltFrom,
     ) -> anyhow::Result<ConfigNode>;
 }
 
+// builds the node into the config's arena, along with the nodes in it
+pub(crate) fn build(
+    context: Arc<Context>,
+    parent: Option<NodeId>,
+    path: &[String],
+    schema: Weak<Schema>,
+    schema_node: &SchemaNode,
+) -> anyhow::Result<(NodeId, Arc<ConfigNode>)> {
+    let arena = Arc::clone(context.arena());
+    let id = arena.reserve(parent, path);
+
+    match ConfigNode::from_schema_node(context, path, id, schema, schema_node) {
+        Ok(node) => match arena.fill(id, node) {
+            Some(node) => Ok((id, node)),
+            None => Err(anyhow!(
+                "Node '{}' dropped while it was built",
+                path.join(" ")
+            )),
+        },
+        Err(e) => {
+            arena.free(Some(id));
+            Err(e)
+        }
+    }
+}
+
 #[enum_dispatch(Node)]
 #[derive(Debug)]
 pub enum ConfigNode {
@@ -50,22 +116,34 @@ pub enum ConfigNode {
 
 impl FromSchemaNode<SchemaNode> for ConfigNode {
     fn from_schema_node(
-        context: Rc<Context>,
-        name: &str,
+        context: Arc<Context>,
+        path: &[String],
+        id: NodeId,
         schema: Weak<Schema>,
         schema_node: &SchemaNode,
     ) -> anyhow::Result<ConfigNode> {
         match schema_node {
             SchemaNode::SingleSchemaNode(node) => Ok(SingleConfigNode::from_schema_node(
-                context, name, schema, node,
+                context, path, id, schema, node,
             )?),
             SchemaNode::MultiSchemaNode(node) => Ok(MultiConfigNode::from_schema_node(
-                context, name, schema, node,
+                context, path, id, schema, node,
             )?),
         }
     }
 }
 
+impl ConfigNode {
+    // the nodes in the node, which go along with it when it's dropped from the
+    // arena
+    fn child_ids(&self) -> Vec<NodeId> {
+        match self {
+            Self::SingleConfigNode(node) => node.child_ids(),
+            Self::MultiConfigNode(node) => node.child_ids(),
+        }
+    }
+}
+
 impl Changeable for ConfigNode {
     fn is_clean(&self) -> bool {
         match self {
@@ -1,34 +1,41 @@
 use super::{
-    Changeable, ConfigNode, FromSchemaNode, Load, LoadSource, Node, NodeName, Property, Save,
-    SaveBuilder,
+    build,
+    children::{Children, NodeChange},
+    Changeable, Config, ConfigNode, FromSchemaNode, Load, LoadSource, Node, NodeId, NodeName,
+    Position, Property, Save, SaveBuilder,
 };
 use crate::{
-    common::Context,
-    error,
-    schema::{MultiSchemaNode, MultiSchemaNodeSource, NodeLocator, Schema, SchemaNodeTrait},
+    common::{Changed, Context},
+    error::{self, ElementCountError, LockError, OrderError, Violation},
+    schema::{
+        Condition, Matches, MultiSchemaNode, NodeLocator, Schema, SchemaNodeTrait, SourceCommand,
+    },
 };
 use anyhow::anyhow;
 use colored::Colorize;
+use rp_log::*;
 use std::{
-    cell::RefCell,
-    collections::HashMap,
-    rc::{Rc, Weak},
+    collections::{HashMap, HashSet},
+    sync::{Arc, RwLock, Weak},
 };
 
-#[derive(Debug, PartialEq, Clone)]
-enum NodeChange {
-    Unchanged,
-    New,
-    Removed,
-}
-
 #[derive(Debug)]
 pub struct MultiConfigNode {
-    nodes: RefCell<HashMap<String, (Rc<ConfigNode>, NodeChange)>>,
+    nodes: RwLock<Children>,
+    sourced_nodes: RwLock<HashSet<String>>,
     name: String,
+    path: Vec<String>,
+    node_id: NodeId,
+    description: Option<String>,
+    id: String,
+    source_command: Option<SourceCommand>,
     new_node_creation_allowed: NewNodeCreationAllowed,
-    node_locator: Rc<NodeLocator>,
-    context: Rc<Context>,
+    when: Vec<Condition>,
+    min: Option<usize>,
+    max: Option<usize>,
+    ordered: bool,
+    node_locator: Arc<NodeLocator>,
+    context: Arc<Context>,
     schema: Weak<Schema>,
 }
 
@@ -39,12 +46,117 @@ enum NewNodeCreationAllowed {
 }
 
 impl MultiConfigNode {
-    // TODO: where is it checked that this given name is actually valid for the
-    // MultiConfigNode?
-    fn build_new_node(&self, name: &str) -> anyhow::Result<Rc<ConfigNode>> {
-        Ok(Rc::new(ConfigNode::from_schema_node(
-            Rc::clone(&self.context),
-            name,
+    // every user-requested node goes through here, be it from editing, loading or
+    // elsewhere, so this is where the node's name is checked against the template
+    fn build_new_node(&self, name: &str) -> anyhow::Result<NodeId> {
+        match &self.new_node_creation_allowed {
+            NewNodeCreationAllowed::Yes { template } => {
+                let schema = self.schema.upgrade().ok_or_else(|| {
+                    anyhow!(
+                        "schema weak pointer dropped while creating new node into MultiConfigNode"
+                    )
+                })?;
+                let schema_template = schema.templates.get(template).ok_or_else(|| {
+                    anyhow!("Template '{}' not found in schema templates", template)
+                })?;
+
+                if !schema_template.matches(name)? {
+                    return Err(error::NodeCreationError::NameMismatch {
+                        name: name.to_owned(),
+                        template: template.to_owned(),
+                    }
+                    .into());
+                }
+            }
+            NewNodeCreationAllowed::No => {
+                return Err(error::NodeCreationError::NotAllowed {
+                    name: name.to_owned(),
+                    node: self.name.to_owned(),
+                }
+                .into())
+            }
+        }
+
+        self.build_node(name)
+    }
+
+    // both the node and the anchor have to be there to order them
+    fn check_order_elements(
+        &self,
+        nodes: &Children,
+        node: Option<&str>,
+        position: &Position,
+    ) -> anyhow::Result<()> {
+        if !self.ordered {
+            return Err(OrderError::NotOrdered(self.name.to_owned()).into());
+        }
+
+        for element in node.into_iter().chain(Some(position.anchor())) {
+            if nodes.get_pending(element).is_none() {
+                return Err(OrderError::NoSuchElement {
+                    item: self.name.to_owned(),
+                    element: element.to_owned(),
+                }
+                .into());
+            }
+        }
+
+        Ok(())
+    }
+
+    // the count is checked under the same lock the node is added under, so nodes
+    // created at the same time can't both take the last place. a node that's
+    // being removed is replaced with a new one
+    fn create_node(&self, nodes: &mut Children, name: &str) -> anyhow::Result<Arc<ConfigNode>> {
+        if let Some(node) = nodes.get_pending(name) {
+            return Ok(node);
+        }
+
+        if let Err(e @ ElementCountError::TooMany { .. }) =
+            self.check_count(&self.name, nodes.pending_count() + 1)
+        {
+            return Err(e.into());
+        }
+
+        let new_node = self.build_new_node(name)?;
+        self.added(new_node);
+        inserted(nodes.insert(name, new_node, NodeChange::New), name)
+    }
+
+    // which nodes there are changed
+    fn edited(&self) {
+        self.context.changes().mark(self.node_id, Changed::Own);
+    }
+
+    // a node that isn't applied yet, along with everything in it
+    fn added(&self, node: NodeId) {
+        self.edited();
+        self.context.changes().mark(node, Changed::Whole);
+    }
+
+    fn child_path(&self, name: &str) -> Vec<String> {
+        [&self.path[..], &[name.to_owned()]].concat()
+    }
+
+    fn check_count(&self, item: &str, count: usize) -> Result<(), ElementCountError> {
+        match (self.min, self.max) {
+            (Some(min), _) if count < min => Err(ElementCountError::TooFew {
+                item: item.to_owned(),
+                min,
+            }),
+            (_, Some(max)) if count > max => Err(ElementCountError::TooMany {
+                item: item.to_owned(),
+                max,
+            }),
+            _ => Ok(()),
+        }
+    }
+
+    fn build_node(&self, name: &str) -> anyhow::Result<NodeId> {
+        let (id, _) = build(
+            child_context(&self.context, &self.id, name),
+            Some(self.node_id),
+            &self.child_path(name),
             Weak::clone(&self.schema),
             self.schema
                 .upgrade()
@@ -53,11 +165,17 @@ impl MultiConfigNode {
                         "schema weak pointer dropped while creating new node into MultiConfigNode"
                     )
                 })?
-                .find_node(Rc::clone(&self.node_locator))
+                .find_node(Arc::clone(&self.node_locator))
                 .ok_or_else(|| {
                     anyhow!("corresponding schema node for MultiConfigNode not found")
                 })?,
-        )?))
+        )?;
+
+        Ok(id)
+    }
+
+    pub(super) fn child_ids(&self) -> Vec<NodeId> {
+        self.nodes.read().expect("lock poisoned").ids()
     }
 }
 
@@ -66,18 +184,25 @@ impl Node for MultiConfigNode {
         self.name.to_owned()
     }
 
+    fn description(&self) -> Option<String> {
+        self.description.clone()
+    }
+
     fn get_available_node_names(&self) -> Vec<NodeName> {
         let schema = self.schema.upgrade().expect("schema dropped");
         let mut names = vec![];
 
         if let NewNodeCreationAllowed::Yes { template } = &self.new_node_creation_allowed {
-            names.push(NodeName::Multiple(Rc::downgrade(
+            names.push(NodeName::Multiple(Arc::downgrade(
                 schema.templates.get(template).expect("template not found"),
             )));
         }
 
-        for name in self.nodes.borrow().keys() {
-            names.push(NodeName::Literal(name.to_owned()));
+        // removed nodes are still around until the removal is applied
+        for (name, _, change) in self.nodes.read().expect("lock poisoned").iter() {
+            if *change != NodeChange::Removed {
+                names.push(NodeName::Literal(name.to_owned()));
+            }
         }
 
         names
@@ -87,17 +212,22 @@ impl Node for MultiConfigNode {
         vec![]
     }
 
-    fn get_node_with_name(&self, name: &str) -> anyhow::Result<Option<Rc<ConfigNode>>> {
-        let mut nodes = self.nodes.try_borrow_mut()?;
-        match nodes.get(name) {
-            Some((node, _)) => Ok(Some(Rc::clone(&node))),
-            _ => {
-                let new_node = self.build_new_node(name)?;
-                nodes.insert(name.to_owned(), (Rc::clone(&new_node), NodeChange::New));
-
-                Ok(Some(new_node))
-            }
+    fn get_node_with_name(&self, name: &str) -> anyhow::Result<Option<Arc<ConfigNode>>> {
+        // existing nodes are looked up while validating, which other threads may be
+        // doing at the same time
+        if let Some(node) = self.nodes.read().map_err(|_| LockError)?.get_pending(name) {
+            return Ok(Some(node));
         }
+
+        let _edit = self.context.changes().edit();
+        let mut nodes = self.nodes.write().map_err(|_| LockError)?;
+
+        // another thread may have created the node in the meantime
+        Ok(Some(self.create_node(&mut nodes, name)?))
+    }
+
+    fn find_node(&self, name: &str) -> anyhow::Result<Option<Arc<ConfigNode>>> {
+        Ok(self.nodes.read().map_err(|_| LockError)?.get_pending(name))
     }
 
     fn get_property(&self, _property: &str) -> Option<&Property> {
@@ -109,11 +239,12 @@ impl Node for MultiConfigNode {
     }
 
     fn pretty_print(&self, indent: usize) {
-        for (name, (node, change)) in &*self.nodes.borrow() {
+        for (name, node, change) in self.nodes.read().expect("lock poisoned").iter() {
             let (name, left_brace, right_brace) = match change {
                 NodeChange::Unchanged => (name.normal(), "{".normal(), "}".normal()),
                 NodeChange::New => (["+", name].concat().green(), "{".green(), "}".green()),
                 NodeChange::Removed => (["-", name].concat().red(), "{".red(), "}".red()),
+                NodeChange::Moved => (["~", name].concat().yellow(), "{".yellow(), "}".yellow()),
             };
 
             println!("{:indent$}{} {}", "", name, left_brace, indent = indent * 4);
@@ -127,12 +258,24 @@ impl Node for MultiConfigNode {
         // either
         match self.new_node_creation_allowed {
             NewNodeCreationAllowed::Yes { .. } => {
-                let mut nodes = self.nodes.try_borrow_mut()?;
-                let (_node, change) = nodes.get_mut(node).ok_or(error::NodeRemovalError {
-                    node: String::from(node),
-                })?;
-                // TODO: handle case when removing non-unchanged node
-                *change = NodeChange::Removed;
+                let _edit = self.context.changes().edit();
+                let mut nodes = self.nodes.write().map_err(|_| LockError)?;
+                if nodes.get_pending(node).is_none() {
+                    return Err(error::NodeRemovalError {
+                        node: String::from(node),
+                    }
+                    .into());
+                }
+
+                let remaining = nodes.pending_count().saturating_sub(1);
+                if let Err(e @ ElementCountError::TooFew { .. }) =
+                    self.check_count(&self.name, remaining)
+                {
+                    return Err(e.into());
+                }
+
+                nodes.remove(node);
+                self.edited();
                 Ok(())
             }
             NewNodeCreationAllowed::No => Err(error::NodeRemovalError {
@@ -141,70 +284,212 @@ impl Node for MultiConfigNode {
             .into()),
         }
     }
+
+    fn insert_subnode(
+        &self,
+        subnode: &str,
+        position: &Position,
+    ) -> anyhow::Result<Arc<ConfigNode>> {
+        let _edit = self.context.changes().edit();
+        let mut nodes = self.nodes.write().map_err(|_| LockError)?;
+
+        self.check_order_elements(&nodes, None, position)?;
+        let node = self.create_node(&mut nodes, subnode)?;
+        nodes.reposition(subnode, position);
+        self.edited();
+
+        Ok(node)
+    }
+
+    fn move_subnode(&self, subnode: &str, position: &Position) -> anyhow::Result<()> {
+        let _edit = self.context.changes().edit();
+        let mut nodes = self.nodes.write().map_err(|_| LockError)?;
+
+        self.check_order_elements(&nodes, Some(subnode), position)?;
+        nodes.reposition(subnode, position);
+        self.edited();
+
+        Ok(())
+    }
+
+    fn refresh_sources(&self) -> anyhow::Result<bool> {
+        let mut refreshed = false;
+
+        if let Some(command) = &self.source_command {
+            let results = command.resolve(&self.context)?;
+            let _edit = self.context.changes().edit();
+            let mut nodes = self.nodes.write().map_err(|_| LockError)?;
+            let mut sourced_nodes = self.sourced_nodes.write().map_err(|_| LockError)?;
+
+            // sourced nodes that aren't in the results anymore don't exist in the system
+            // either. one with changes that aren't applied yet is removed like any
+            // other node instead, so the changes are shown going away along with it
+            let disappeared: Vec<String> = sourced_nodes
+                .iter()
+                .filter(|name| !results.contains(name))
+                .cloned()
+                .collect();
+            let mut changed = HashSet::new();
+
+            for name in &disappeared {
+                match nodes.get(name) {
+                    Some((_, NodeChange::Removed)) => {
+                        changed.insert(name.to_owned());
+                    }
+                    Some((node, change))
+                        if *change != NodeChange::Unchanged || !node.is_clean() =>
+                    {
+                        warn!(
+                            "Source node '{}' in '{}' disappeared with changes not yet applied, removing it",
+                            name, self.name
+                        );
+                        nodes.remove(name);
+                        changed.insert(name.to_owned());
+                        refreshed = true;
+                    }
+                    _ => debug!("Source node '{}' in '{}' disappeared", name, self.name),
+                }
+            }
+
+            refreshed = nodes
+                .drop_where(|name, _| disappeared.contains(name) && !changed.contains(name))
+                || refreshed;
+            if refreshed {
+                self.edited();
+            }
+
+            // the removed nodes stay sourced, so that they're dropped by the next
+            // refresh if their removal is discarded
+            sourced_nodes.retain(|name| results.contains(name) || changed.contains(name));
+
+            for result in results {
+                if nodes.get(&result).is_none() {
+                    debug!("New source node '{}' in '{}'", result, self.name);
+                    let new_node = self.build_node(&result)?;
+                    nodes.insert(&result, new_node, NodeChange::Unchanged);
+                    self.added(new_node);
+                    refreshed = true;
+                }
+                sourced_nodes.insert(result);
+            }
+        }
+
+        for (_, node, _) in self.nodes.read().map_err(|_| LockError)?.iter() {
+            refreshed = node.refresh_sources()? || refreshed;
+        }
+
+        Ok(refreshed)
+    }
+
+    fn validate(
+        &self,
+        config: &Config,
+        path: &[String],
+        violations: &mut Vec<Violation>,
+    ) -> anyhow::Result<()> {
+        self.validate_own(config, path, violations)?;
+
+        // the nodes aren't kept locked while they're validated, since their
+        // conditions look nodes up from the top of the config, this one included
+        let nodes: Vec<(String, Arc<ConfigNode>)> = self
+            .nodes
+            .read()
+            .map_err(|_| LockError)?
+            .iter()
+            .filter(|(_, _, change)| **change != NodeChange::Removed)
+            .map(|(name, node, _)| (name.to_owned(), node))
+            .collect();
+
+        for (name, node) in nodes {
+            node.validate(config, &[path, &[name]].concat(), violations)?;
+        }
+
+        Ok(())
+    }
+
+    fn validate_own(
+        &self,
+        config: &Config,
+        path: &[String],
+        violations: &mut Vec<Violation>,
+    ) -> anyhow::Result<()> {
+        // locked in the same order as when refreshing the sources
+        let (added, count) = {
+            let nodes = self.nodes.read().map_err(|_| LockError)?;
+            let sourced_nodes = self.sourced_nodes.read().map_err(|_| LockError)?;
+            let added = nodes.iter().any(|(name, _, change)| {
+                *change != NodeChange::Removed && !sourced_nodes.contains(name)
+            });
+            (added, nodes.pending_count())
+        };
+
+        if let Err(e) = self.check_count(&path.join(" "), count) {
+            violations.push(e.into());
+        }
+
+        // sourced nodes exist regardless, so only added ones count
+        if added {
+            let parent = &path[..path.len() - 1];
+            for condition in &self.when {
+                if !condition.holds(
+                    &config.get_pending_values(&condition.resolve(parent).unwrap_or_default())?,
+                ) {
+                    violations.push(Violation::NotApplicable {
+                        item: path.join(" "),
+                        condition: condition.describe(parent),
+                    });
+                }
+            }
+        }
+
+        Ok(())
+    }
+
+    fn apply_own_changes(&self) -> anyhow::Result<bool> {
+        Ok(self.nodes.write().map_err(|_| LockError)?.commit())
+    }
+
+    fn discard_own_changes(&self) {
+        self.nodes.write().expect("lock poisoned").discard();
+    }
 }
 
 impl Changeable for MultiConfigNode {
     fn is_clean(&self) -> bool {
         self.nodes
-            .borrow()
-            .values()
-            .all(|(node, change)| node.is_clean() && *change == NodeChange::Unchanged)
+            .read()
+            .expect("lock poisoned")
+            .iter()
+            .all(|(_, node, change)| node.is_clean() && *change == NodeChange::Unchanged)
     }
 
     fn apply_changes(&self) -> anyhow::Result<bool> {
+        let mut nodes = self.nodes.write().map_err(|_| LockError)?;
         let mut edits = false;
-        let new_nodes: HashMap<String, (Rc<ConfigNode>, NodeChange)> = match self
-            .nodes
-            .try_borrow()?
-            .iter()
-            .filter_map(|(name, (node, change))| match change {
-                NodeChange::Unchanged | NodeChange::New => match node.apply_changes() {
-                    Ok(changes) => {
-                        edits = edits || changes;
-                        // TODO: is it bad to always clone existing nodes?
-                        Some(Ok((name.clone(), (Rc::clone(node), NodeChange::Unchanged))))
-                    }
-                    Err(e) => Some(Err(e)),
-                },
-                NodeChange::Removed => {
-                    edits = true;
-                    None
-                }
-            })
-            .collect()
-        {
-            Ok(n) => n,
-            Err(e) => return Err(e),
-        };
 
-        self.nodes.replace(new_nodes);
+        for (_, node, change) in nodes.iter() {
+            if *change != NodeChange::Removed {
+                edits = node.apply_changes()? || edits;
+            }
+        }
 
-        Ok(edits)
+        Ok(nodes.commit() || edits)
     }
 
     fn discard_changes(&self) {
-        let new_nodes: HashMap<String, (Rc<ConfigNode>, NodeChange)> = self
-            .nodes
-            .borrow()
-            .iter()
-            .filter_map(|(name, (node, change))| {
-                node.discard_changes();
-                match change {
-                    NodeChange::New => None,
-                    NodeChange::Removed | NodeChange::Unchanged => {
-                        Some((name.clone(), (Rc::clone(node), NodeChange::Unchanged)))
-                    }
-                }
-            })
-            .collect();
+        let mut nodes = self.nodes.write().expect("lock poisoned");
+
+        for (_, node, _) in nodes.iter() {
+            node.discard_changes();
+        }
 
-        self.nodes.replace(new_nodes);
+        nodes.discard();
     }
 }
 
 impl Save for MultiConfigNode {
     fn save(&self, builder: &mut SaveBuilder) -> anyhow::Result<()> {
-        for (name, (node, _)) in self.nodes.try_borrow()?.iter() {
+        for (name, node, _) in self.nodes.read().map_err(|_| LockError)?.iter() {
             builder.begin_node(name.clone())?;
             node.save(builder)?;
             builder.end_node()?;
@@ -216,64 +501,82 @@ impl Save for MultiConfigNode {
 
 impl Load for MultiConfigNode {
     fn load(&self, source: &mut LoadSource) -> anyhow::Result<()> {
-        let mut nodes = self.nodes.try_borrow_mut()?;
+        let mut nodes = self.nodes.write().map_err(|_| LockError)?;
         let names: Vec<String> = source.get_node_names();
-        for node_name in names {
-            let node = if let Some((existing, _)) = nodes.get(&node_name) {
-                Rc::clone(existing)
-            } else {
-                let new_node = self.build_new_node(&node_name)?;
-                nodes.insert(
-                    node_name.to_owned(),
-                    (Rc::clone(&new_node), NodeChange::New),
+        for node_name in &names {
+            let node = if let Some(existing) = nodes.get_pending(node_name) {
+                existing
+            } else if let NewNodeCreationAllowed::No = self.new_node_creation_allowed {
+                // only the system provides these nodes, so a saved one that isn't
+                // there has gone away since the save, like an unplugged NIC
+                warn!(
+                    "Saved node '{}' in '{}' doesn't exist anymore, not loading it",
+                    node_name, self.name
                 );
-                new_node
+                continue;
+            } else {
+                let new_node = self.build_new_node(node_name)?;
+                self.added(new_node);
+                inserted(
+                    nodes.insert(node_name, new_node, NodeChange::New),
+                    node_name,
+                )?
             };
 
-            source.begin_node(&node_name)?;
+            source.begin_node(node_name)?;
             node.load(source)?;
             source.end_node()?;
         }
 
+        // nodes that were already there, like sourced ones, go where the save has
+        // them
+        if self.ordered {
+            nodes.restore_order(&names);
+        }
+
         Ok(())
     }
 }
 
 impl FromSchemaNode<MultiSchemaNode> for MultiConfigNode {
     fn from_schema_node(
-        context: Rc<Context>,
-        name: &str,
+        context: Arc<Context>,
+        path: &[String],
+        id: NodeId,
         schema: Weak<Schema>,
         schema_node: &MultiSchemaNode,
     ) -> anyhow::Result<ConfigNode> {
-        let mut nodes = HashMap::new();
-
-        // let new_node_creation_allowed = match &schema_node.source {
-        //     MultiSchemaNodeSource::Query(query) => {
-        //         for result in &query.run(&context)? {
-        //             let mut result_context = Context::new(Some(Rc::clone(&context)));
-        //             result_context.set_value(query.id.to_owned(), result.to_owned());
-        //             nodes.insert(
-        //                 result.to_owned(),
-        //                 (
-        //                     Rc::new(ConfigNode::from_schema_node(
-        //                         Rc::new(result_context),
-        //                         &result.to_owned(),
-        //                         Weak::clone(&schema),
-        //                         &schema_node.node,
-        //                     )?),
-        //                     NodeChange::Unchanged,
-        //                 ),
-        //             );
-        //         }
-        //         NewNodeCreationAllowed::No
-        //     }
-        //     MultiSchemaNodeSource::Template(template) => NewNodeCreationAllowed::Yes
-        // {         template: template.to_owned(),
-        //     },
-        // };
-
-        let new_node_creation_allowed = if let Some(template) = &schema_node.source.template {
+        let name = path
+            .last()
+            .ok_or_else(|| anyhow!("MultiConfigNode built with an empty path"))?;
+        let source = &schema_node.source;
+        let mut nodes = Children::new(Arc::clone(context.arena()));
+        let mut sourced_nodes = HashSet::new();
+
+        if let Some(command) = &source.command {
+            for result in command.resolve(&context)? {
+                let built = build(
+                    child_context(&context, &source.id, &result),
+                    Some(id),
+                    &[path, &[result.to_owned()]].concat(),
+                    Weak::clone(&schema),
+                    &schema_node.node,
+                );
+                // the nodes built so far go with the node that failed to build
+                let (child, _) = match built {
+                    Ok(built) => built,
+                    Err(e) => {
+                        context.arena().free(nodes.ids());
+                        return Err(e);
+                    }
+                };
+
+                nodes.insert(&result, child, NodeChange::Unchanged);
+                sourced_nodes.insert(result);
+            }
+        }
+
+        let new_node_creation_allowed = if let Some(template) = &source.template {
             NewNodeCreationAllowed::Yes {
                 template: template.clone(),
             }
@@ -282,13 +585,37 @@ impl FromSchemaNode<MultiSchemaNode> for MultiConfigNode {
         };
 
         Ok(MultiConfigNode {
-            nodes: RefCell::new(nodes),
+            nodes: RwLock::new(nodes),
+            sourced_nodes: RwLock::new(sourced_nodes),
             name: name.to_owned(),
+            path: path.to_vec(),
+            node_id: id,
+            description: schema_node.description.clone(),
+            id: source.id.to_owned(),
+            source_command: source.command.clone(),
             new_node_creation_allowed,
-            context: Rc::clone(&context),
+            when: schema_node.when.clone(),
+            min: schema_node.min,
+            max: schema_node.max,
+            ordered: schema_node.ordered,
+            context: Arc::clone(&context),
             node_locator: schema_node.node.get_locator(),
             schema,
         }
         .into())
     }
 }
+
+// the node that was just inserted, which is in the arena unless it was dropped
+// from it in the meantime
+fn inserted(node: Option<Arc<ConfigNode>>, name: &str) -> anyhow::Result<Arc<ConfigNode>> {
+    node.ok_or_else(|| anyhow!("Node '{}' dropped while it was created", name))
+}
+
+// each child gets its own name bound to the source ID so that its nodes and
+// properties can refer to it (e.g. "{interface}.mtu")
+fn child_context(parent: &Arc<Context>, id: &str, name: &str) -> Arc<Context> {
+    let mut context = Context::new(Some(Arc::clone(parent)));
+    context.set_value(id.to_owned(), name.to_owned());
+    Arc::new(context)
+}
@@ -1,23 +1,40 @@
 use super::{
-    Changeable, ConfigNode, FromSchemaNode, Load, LoadSource, Node, NodeName, Property, Save,
-    SaveBuilder,
+    build, Arena, Changeable, Config, ConfigNode, FromSchemaNode, Load, LoadSource, Node, NodeId,
+    NodeName, Position, Property, Save, SaveBuilder,
 };
 use crate::{
     common::{helpers, Context},
-    error,
+    error::{self, Violation},
     schema::{Schema, SingleSchemaNode},
 };
+use anyhow::anyhow;
 use rp_log::*;
 use std::{
     collections::HashMap,
-    rc::{Rc, Weak},
+    sync::{Arc, Weak},
 };
 
 #[derive(Debug)]
 pub struct SingleConfigNode {
     name: String,
-    subnodes: HashMap<String, Rc<ConfigNode>>,
+    description: Option<String>,
+    subnodes: HashMap<String, NodeId>,
     properties: HashMap<String, Property>,
+    arena: Arc<Arena>,
+}
+
+impl SingleConfigNode {
+    // the subnodes are there for as long as the node is, unless the node was
+    // dropped from the arena along with them
+    fn subnodes(&self) -> impl Iterator<Item = (&String, Arc<ConfigNode>)> {
+        self.subnodes
+            .iter()
+            .filter_map(move |(name, id)| Some((name, self.arena.get(*id)?)))
+    }
+
+    pub(super) fn child_ids(&self) -> Vec<NodeId> {
+        self.subnodes.values().copied().collect()
+    }
 }
 
 impl Node for SingleConfigNode {
@@ -25,6 +42,10 @@ impl Node for SingleConfigNode {
         self.name.to_owned()
     }
 
+    fn description(&self) -> Option<String> {
+        self.description.clone()
+    }
+
     fn get_available_node_names(&self) -> Vec<NodeName> {
         let mut names = Vec::new();
 
@@ -39,8 +60,12 @@ impl Node for SingleConfigNode {
         self.properties.keys().map(|key| key.to_owned()).collect()
     }
 
-    fn get_node_with_name(&self, name: &str) -> anyhow::Result<Option<Rc<ConfigNode>>> {
-        Ok(self.subnodes.get(name).map(|n| Rc::clone(n)))
+    fn get_node_with_name(&self, name: &str) -> anyhow::Result<Option<Arc<ConfigNode>>> {
+        Ok(self.subnodes.get(name).and_then(|id| self.arena.get(*id)))
+    }
+
+    fn find_node(&self, name: &str) -> anyhow::Result<Option<Arc<ConfigNode>>> {
+        self.get_node_with_name(name)
     }
 
     fn get_property(&self, property: &str) -> Option<&Property> {
@@ -68,7 +93,7 @@ impl Node for SingleConfigNode {
     }
 
     fn pretty_print(&self, indent: usize) {
-        for (name, node) in &self.subnodes {
+        for (name, node) in self.subnodes() {
             println!("{:indent$}{} {{", "", name, indent = indent * 4);
             node.pretty_print(indent + 1);
             println!("{:indent$}}}", "", indent = indent * 4);
@@ -85,22 +110,84 @@ impl Node for SingleConfigNode {
         }
         .into())
     }
-}
 
-impl Changeable for SingleConfigNode {
-    fn is_clean(&self) -> bool {
-        self.properties.values().all(|prop| prop.is_clean())
-            && self.subnodes.values().all(|node| node.is_clean())
+    fn insert_subnode(
+        &self,
+        _subnode: &str,
+        _position: &Position,
+    ) -> anyhow::Result<Arc<ConfigNode>> {
+        Err(error::OrderError::NotOrdered(self.name.to_owned()).into())
     }
 
-    fn apply_changes(&self) -> anyhow::Result<bool> {
+    fn move_subnode(&self, _subnode: &str, _position: &Position) -> anyhow::Result<()> {
+        Err(error::OrderError::NotOrdered(self.name.to_owned()).into())
+    }
+
+    fn refresh_sources(&self) -> anyhow::Result<bool> {
+        let mut refreshed = false;
+
+        for (_, node) in self.subnodes() {
+            refreshed = node.refresh_sources()? || refreshed;
+        }
+
+        Ok(refreshed)
+    }
+
+    fn validate(
+        &self,
+        config: &Config,
+        path: &[String],
+        violations: &mut Vec<Violation>,
+    ) -> anyhow::Result<()> {
+        self.validate_own(config, path, violations)?;
+
+        for (name, node) in self.subnodes() {
+            node.validate(config, &[path, &[name.to_owned()]].concat(), violations)?;
+        }
+
+        Ok(())
+    }
+
+    fn validate_own(
+        &self,
+        config: &Config,
+        path: &[String],
+        violations: &mut Vec<Violation>,
+    ) -> anyhow::Result<()> {
+        for property in self.properties.values() {
+            property.validate(config, path, violations)?;
+        }
+
+        Ok(())
+    }
+
+    fn apply_own_changes(&self) -> anyhow::Result<bool> {
         let mut edits = false;
 
         for prop in self.properties.values() {
             edits = prop.apply_changes()? || edits;
         }
 
-        for node in self.subnodes.values() {
+        Ok(edits)
+    }
+
+    fn discard_own_changes(&self) {
+        for prop in self.properties.values() {
+            prop.discard_changes();
+        }
+    }
+}
+
+impl Changeable for SingleConfigNode {
+    fn is_clean(&self) -> bool {
+        self.properties.values().all(|prop| prop.is_clean())
+            && self.subnodes().all(|(_, node)| node.is_clean())
+    }
+
+    fn apply_changes(&self) -> anyhow::Result<bool> {
+        let mut edits = self.apply_own_changes()?;
+
+        for (_, node) in self.subnodes() {
             edits = node.apply_changes()? || edits;
         }
 
@@ -108,11 +195,9 @@ impl Changeable for SingleConfigNode {
     }
 
     fn discard_changes(&self) {
-        for prop in self.properties.values() {
-            prop.discard_changes();
-        }
+        self.discard_own_changes();
 
-        for node in self.subnodes.values() {
+        for (_, node) in self.subnodes() {
             node.discard_changes();
         }
     }
@@ -120,7 +205,7 @@ impl Changeable for SingleConfigNode {
 
 impl Save for SingleConfigNode {
     fn save(&self, builder: &mut SaveBuilder) -> anyhow::Result<()> {
-        for (name, node) in &self.subnodes {
+        for (name, node) in self.subnodes() {
             builder.begin_node(name.clone())?;
             node.save(builder)?;
             builder.end_node()?;
@@ -138,7 +223,7 @@ impl Save for SingleConfigNode {
 
 impl Load for SingleConfigNode {
     fn load(&self, source: &mut LoadSource) -> anyhow::Result<()> {
-        for (name, node) in &self.subnodes {
+        for (name, node) in self.subnodes() {
             source.begin_node(name)?;
             node.load(source)?;
             source.end_node()?;
@@ -147,17 +232,25 @@ impl Load for SingleConfigNode {
         for (name, property) in &self.properties {
             match source.get_property(name) {
                 Ok(values) => {
+                    // saved values may predate normalisation, compare them in their
+                    // canonical form so they don't show up as changes
+                    let values = values
+                        .iter()
+                        .map(|value| property.normalise(value))
+                        .collect::<anyhow::Result<Vec<String>>>()?;
+                    // the order of an ordered property's values is part of them
                     let existing = property.values();
-                    if helpers::equal_vecs(&existing, values) {
+                    let equal = if property.is_ordered() {
+                        existing == values
+                    } else {
+                        helpers::equal_vecs(&existing, &values)
+                    };
+                    if equal {
                         trace!("Loaded values equal to existing values in node '{}' property '{}', not loading ({:?})", name, self.name, values);
                     } else {
-                        if !existing.is_empty() {
-                            property.remove(None)?;
-                        }
-
-                        for value in values {
-                            property.set(value)?;
-                        }
+                        // the element count is checked when validating the loaded
+                        // config as a whole
+                        property.replace(&values)?;
                     }
                 }
                 Err(e) => {
@@ -181,30 +274,24 @@ impl Load for SingleConfigNode {
 
 impl FromSchemaNode<SingleSchemaNode> for SingleConfigNode {
     fn from_schema_node(
-        context: Rc<Context>,
-        name: &str,
+        context: Arc<Context>,
+        path: &[String],
+        id: NodeId,
         schema: Weak<Schema>,
         schema_node: &SingleSchemaNode,
     ) -> anyhow::Result<ConfigNode> {
+        let name = path
+            .last()
+            .ok_or_else(|| anyhow!("SingleConfigNode built with an empty path"))?;
         let name = context.format(name.to_owned())?;
+        let arena = Arc::clone(context.arena());
         let mut subnodes = HashMap::new();
         let mut properties = HashMap::new();
 
-        for (subname, subnode) in &schema_node.subnodes {
-            subnodes.insert(
-                subname.to_owned(),
-                Rc::new(ConfigNode::from_schema_node(
-                    Rc::clone(&context),
-                    &subname,
-                    Weak::clone(&schema),
-                    &subnode,
-                )?),
-            );
-        }
-
         for (key, property) in &schema_node.properties {
             let prop = Property::from_schema_property(
-                Rc::clone(&context),
+                Arc::clone(&context),
+                id,
                 &key,
                 property,
                 Weak::clone(&schema),
@@ -212,10 +299,32 @@ impl FromSchemaNode<SingleSchemaNode> for SingleConfigNode {
             properties.insert(key.to_owned(), prop);
         }
 
+        for (subname, subnode) in &schema_node.subnodes {
+            let built = build(
+                Arc::clone(&context),
+                Some(id),
+                &[path, &[subname.to_owned()]].concat(),
+                Weak::clone(&schema),
+                &subnode,
+            );
+            // the subnodes built so far go with the node that failed to build
+            match built {
+                Ok((subnode, _)) => {
+                    subnodes.insert(subname.to_owned(), subnode);
+                }
+                Err(e) => {
+                    arena.free(subnodes.values().copied());
+                    return Err(e);
+                }
+            }
+        }
+
         Ok(SingleConfigNode {
             name,
+            description: schema_node.description.clone(),
             subnodes,
             properties,
+            arena,
         }
         .into())
     }
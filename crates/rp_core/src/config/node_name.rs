@@ -1,6 +1,6 @@
 use crate::schema::{Matches, Template};
 use anyhow::anyhow;
-use std::rc::Weak;
+use std::sync::Weak;
 
 #[derive(Debug)]
 pub enum NodeName {
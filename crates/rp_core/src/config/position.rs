@@ -0,0 +1,64 @@
+use indexmap::IndexMap;
+use std::{collections::HashMap, mem};
+
+// where to put a value or node in an ordered property or multi node, relative to
+// an existing one
+#[derive(Debug, Clone, PartialEq)]
+pub enum Position {
+    Before(String),
+    After(String),
+}
+
+impl Position {
+    pub fn anchor(&self) -> &str {
+        match self {
+            Position::Before(anchor) | Position::After(anchor) => anchor,
+        }
+    }
+
+    // the same side of another anchor
+    pub fn relative_to(&self, anchor: String) -> Position {
+        match self {
+            Position::Before(_) => Position::Before(anchor),
+            Position::After(_) => Position::After(anchor),
+        }
+    }
+}
+
+// moves the key next to the anchor, keeping everything else in order. both have
+// to be in the map
+pub(super) fn reposition<V>(map: &mut IndexMap<String, V>, key: &str, position: &Position) {
+    if key == position.anchor() {
+        return;
+    }
+
+    let entry = match map.shift_remove_full(key) {
+        Some((_, key, value)) => (key, value),
+        None => return,
+    };
+    let mut entries: Vec<(String, V)> = mem::replace(map, IndexMap::new()).into_iter().collect();
+    let index = entries
+        .iter()
+        .position(|(key, _)| key == position.anchor())
+        .map(|index| match position {
+            Position::Before(_) => index,
+            Position::After(_) => index + 1,
+        })
+        .unwrap_or_else(|| entries.len());
+
+    entries.insert(index, entry);
+    *map = entries.into_iter().collect();
+}
+
+// puts the keys back in the order they were before being moved around. keys that
+// weren't there go last
+pub(super) fn restore_order<V>(map: &mut IndexMap<String, V>, order: &[String]) {
+    let positions: HashMap<&str, usize> = order
+        .iter()
+        .enumerate()
+        .map(|(index, key)| (key.as_str(), index))
+        .collect();
+    let position = |key: &String| positions.get(key.as_str()).copied().unwrap_or(usize::MAX);
+
+    map.sort_by(|a, _, b, _| position(a).cmp(&position(b)));
+}
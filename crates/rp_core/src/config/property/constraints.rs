@@ -1,31 +1,152 @@
 use crate::{
     error::ConstraintError,
-    schema::{Matches, Property, Schema, Value},
+    schema::{AllowedValue, Condition, Matches, Normalise, Property, Schema, Value},
 };
 use anyhow::anyhow;
 
+const SECRET_MASK: &str = "********";
+
 #[derive(Debug)]
 pub struct Constraints {
+    pub mandatory: bool,
     pub multiple: bool,
-    values: Vec<Value>,
+    pub ordered: bool,
+    pub secret: bool,
+    pub min: Option<usize>,
+    pub max: Option<usize>,
+    values: Vec<AllowedValue>,
     pub deletable: bool,
+    pub when: Vec<Condition>,
+    pub must: Vec<Condition>,
+    pub excludes: Vec<Condition>,
 }
 
 impl Constraints {
     pub fn from_schema_property(property: &Property) -> Constraints {
         Constraints {
+            mandatory: property.mandatory,
             multiple: property.multiple,
-            values: property.values.clone().into_iter().collect(),
+            ordered: property.ordered,
+            secret: property.secret,
+            min: property.min,
+            max: property.max,
+            values: allowed_in_order(property),
             deletable: property.deletable,
+            when: property.when.clone(),
+            must: property.must.clone(),
+            excludes: property.excludes.clone(),
         }
     }
 
-    pub fn matches(&self, value: &str, schema: &Schema) -> anyhow::Result<()> {
-        for v in &self.values {
+    pub fn allowed_values(&self) -> &[AllowedValue] {
+        &self.values
+    }
+
+    // the value as it may be shown to the user
+    pub fn shown<'a>(&self, value: &'a str) -> &'a str {
+        if self.secret {
+            SECRET_MASK
+        } else {
+            value
+        }
+    }
+
+    pub fn references(&self) -> Vec<&[String]> {
+        self.values
+            .iter()
+            .filter_map(|allowed| match &allowed.value {
+                Value::Reference(path) => Some(path.as_slice()),
+                _ => None,
+            })
+            .collect()
+    }
+
+    // checks the value against the allowed values and returns it in the canonical
+    // form of whichever one it matched
+    pub fn normalise(&self, value: &str, schema: &Schema) -> anyhow::Result<String> {
+        if let Some(normalised) = self.normalise_directly(value, schema)? {
+            return Ok(normalised);
+        }
+
+        // references can only be checked against the whole config, which is done
+        // when committing
+        if !self.references().is_empty() {
+            return Ok(value.to_owned());
+        }
+
+        Err(ConstraintError {
+            given: self.shown(value).to_owned(),
+            allowed_values: self
+                .values
+                .iter()
+                .map(|allowed| allowed.value.clone())
+                .collect(),
+        }
+        .into())
+    }
+
+    // the existing value the given one stands for. a password hashes differently
+    // every time, so it's checked against the stored hashes instead
+    pub fn find_existing<'a, I>(
+        &self,
+        value: &str,
+        existing: I,
+        schema: &Schema,
+    ) -> anyhow::Result<Option<&'a String>>
+    where
+        I: IntoIterator<Item = &'a String>,
+    {
+        let normalised = self
+            .normalise(value, schema)
+            .unwrap_or_else(|_| value.to_owned());
+        let templates: Vec<_> = self
+            .values
+            .iter()
+            .filter_map(|allowed| match &allowed.value {
+                Value::Template(template) => schema.templates.get(template),
+                _ => None,
+            })
+            .collect();
+
+        for existing in existing {
+            if *existing == normalised || existing == value {
+                return Ok(Some(existing));
+            }
+
+            for template in &templates {
+                if template.stored_as(value, existing)? {
+                    return Ok(Some(existing));
+                }
+            }
+        }
+
+        Ok(None)
+    }
+
+    // like normalise but without references
+    pub fn normalise_directly(
+        &self,
+        value: &str,
+        schema: &Schema,
+    ) -> anyhow::Result<Option<String>> {
+        Ok(self
+            .find_match(value, schema)?
+            .map(|(_, normalised)| normalised))
+    }
+
+    // the allowed value the value matches along with its normalised form,
+    // references aside
+    pub fn find_match(
+        &self,
+        value: &str,
+        schema: &Schema,
+    ) -> anyhow::Result<Option<(&AllowedValue, String)>> {
+        for allowed in &self.values {
+            let v = &allowed.value;
             match v {
                 Value::Literal(literal) => {
                     if value == literal {
-                        return Ok(());
+                        return Ok(Some((allowed, value.to_owned())));
                     }
                 }
                 Value::Template(template) => {
@@ -33,7 +154,7 @@ impl Constraints {
                         anyhow!("Value template '{}' not found in schema templates", v)
                     })?;
                     if schema_template.matches(value)? {
-                        return Ok(());
+                        return Ok(Some((allowed, schema_template.normalise(value)?)));
                     }
                 }
                 Value::Range(range) => {
@@ -41,15 +162,29 @@ impl Constraints {
                         continue;
                     }
 
-                    return Ok(());
+                    return Ok(Some((allowed, range.normalise(value)?)));
                 }
+                Value::Reference(_) => (),
             }
         }
 
-        Err(ConstraintError {
-            given: value.to_string(),
-            allowed_values: self.values.clone(),
-        }
-        .into())
+        Ok(None)
     }
 }
+
+// the schema keeps the allowed values in a set, but a value matching more than
+// one has to match the same one every time. literals are the most specific, so
+// they go first, and references last since they're checked only when committing
+fn allowed_in_order(property: &Property) -> Vec<AllowedValue> {
+    let mut values: Vec<AllowedValue> = property.values.iter().cloned().collect();
+    values.sort_by_key(|allowed| {
+        let kind = match &allowed.value {
+            Value::Literal(_) => 0,
+            Value::Range(_) => 1,
+            Value::Template(_) => 2,
+            Value::Reference(_) => 3,
+        };
+        (kind, allowed.value.to_string())
+    });
+    values
+}
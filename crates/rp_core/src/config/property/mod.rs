@@ -1,16 +1,21 @@
 mod constraints;
 
-use super::Changeable;
-use crate::{error::PropertyError, schema::Schema};
+use super::{
+    position::{reposition, restore_order},
+    Changeable, Config, NodeId, Position,
+};
+use crate::{
+    common::{Changed, Changes, Context},
+    error::{ElementCountError, LockError, OrderError, PropertyError, ReferenceError, Violation},
+    schema::{AllowedValue, Condition, Schema},
+};
 use anyhow::anyhow;
 use colored::Colorize;
 use constraints::Constraints;
-use crate::common::Context;
-use std::{
-    cell::RefCell,
-    collections::HashMap,
-    rc::{Rc, Weak},
-};
+use indexmap::IndexMap;
+use std::sync::{Arc, RwLock, Weak};
+
+type Values = IndexMap<String, PropertyChange>;
 
 #[derive(Debug, PartialEq, Clone)]
 enum PropertyChange {
@@ -18,32 +23,42 @@ enum PropertyChange {
     New,
     Removed,
     Edited { old_value: String },
+    Moved,
 }
 
 #[derive(Debug)]
 pub struct Property {
     pub key: String,
-    values: RefCell<HashMap<String, PropertyChange>>,
+    description: Option<String>,
+    // in the order they're set, which matters only for ordered properties
+    values: RwLock<Values>,
+    // the order of the values before they were first moved, to go back to if the
+    // changes are discarded
+    committed_order: RwLock<Option<Vec<String>>>,
     // TODO: this is pretty horrible just look it up from the schema or smth
     default_values: Vec<String>,
     constraints: Constraints,
+    changes: Arc<Changes>,
+    // the node the property is in
+    node: NodeId,
     schema: Weak<Schema>,
 }
 
 impl Property {
     pub fn from_schema_property(
-        context: Rc<Context>,
+        context: Arc<Context>,
+        node: NodeId,
         key: &str,
         property: &crate::schema::Property,
         schema: Weak<Schema>,
     ) -> anyhow::Result<Property> {
-        let mut values = HashMap::new();
+        let mut values = IndexMap::new();
         let constraints = Constraints::from_schema_property(property);
 
         if let Some(schema_rc) = schema.upgrade() {
             for default in &property.default {
                 for v in default.resolve(&context)? {
-                    constraints.matches(&v, schema_rc.as_ref())?;
+                    let v = constraints.normalise(&v, schema_rc.as_ref())?;
                     values.insert(v, PropertyChange::Unchanged);
                 }
             }
@@ -53,9 +68,13 @@ impl Property {
             } else {
                 Ok(Property {
                     key: key.to_owned(),
+                    description: property.description.clone(),
                     default_values: values.iter().map(|(value, _)| value.to_owned()).collect(),
-                    values: RefCell::new(values),
+                    values: RwLock::new(values),
+                    committed_order: RwLock::new(None),
                     constraints,
+                    changes: Arc::clone(context.changes()),
+                    node,
                     schema,
                 })
             }
@@ -64,49 +83,358 @@ impl Property {
         }
     }
 
+    pub fn description(&self) -> Option<&str> {
+        self.description.as_deref()
+    }
+
+    pub fn allowed_values(&self) -> &[AllowedValue] {
+        self.constraints.allowed_values()
+    }
+
+    pub fn is_secret(&self) -> bool {
+        self.constraints.secret
+    }
+
+    pub fn is_ordered(&self) -> bool {
+        self.constraints.ordered
+    }
+
     pub fn values(&self) -> Vec<String> {
         self.values
-            .borrow()
-            .iter()
-            .map(|(value, _)| value.to_owned())
+            .read()
+            .expect("lock poisoned")
+            .keys()
+            .cloned()
             .collect()
     }
 
-    pub fn set(&self, value: &str) -> anyhow::Result<()> {
+    // the values as they'll be once the changes are applied
+    pub fn pending_values(&self) -> Vec<String> {
+        pending(&self.values.read().expect("lock poisoned"))
+            .cloned()
+            .collect()
+    }
+
+    // names in every node this property may refer to
+    pub fn reference_candidates(&self, config: &Config) -> anyhow::Result<Vec<String>> {
+        let mut candidates = Vec::new();
+        for reference in self.constraints.references() {
+            candidates.extend(config.get_referenced_names(reference)?);
+        }
+        Ok(candidates)
+    }
+
+    // path is the path of the node this property is in
+    pub fn validate(
+        &self,
+        config: &Config,
+        path: &[String],
+        violations: &mut Vec<Violation>,
+    ) -> anyhow::Result<()> {
+        let property_path = [path, &[self.key.to_owned()]].concat().join(" ");
+        let values = self.pending_values();
+
+        if self.constraints.mandatory && values.is_empty() {
+            violations.push(Violation::MissingMandatory(property_path.to_owned()));
+        }
+
+        if let Err(e) = self.check_count(&property_path, values.len()) {
+            violations.push(e.into());
+        }
+
         let schema = self
             .schema
             .upgrade()
             .ok_or_else(|| anyhow!("schema weak pointer upgrade failed"))?;
-        self.constraints.matches(&value, schema.as_ref())?;
 
-        let mut values = self.values.try_borrow_mut()?;
+        self.validate_conditions(config, path, &values, schema.as_ref(), violations)?;
+
+        let references = self.constraints.references();
+        if references.is_empty() {
+            return Ok(());
+        }
+
+        let candidates = self.reference_candidates(config)?;
+
+        for value in values {
+            if self
+                .constraints
+                .normalise_directly(&value, schema.as_ref())?
+                .is_none()
+                && !candidates.contains(&value)
+            {
+                violations.push(
+                    ReferenceError {
+                        property: property_path.to_owned(),
+                        value: self.constraints.shown(&value).to_owned(),
+                        reference: references
+                            .iter()
+                            .map(|reference| reference.join(" "))
+                            .collect::<Vec<String>>()
+                            .join("' or '"),
+                    }
+                    .into(),
+                );
+            }
+        }
+
+        Ok(())
+    }
+
+    fn validate_conditions(
+        &self,
+        config: &Config,
+        path: &[String],
+        values: &[String],
+        schema: &Schema,
+        violations: &mut Vec<Violation>,
+    ) -> anyhow::Result<()> {
+        if values.is_empty() {
+            return Ok(());
+        }
+
+        let property_path = [path, &[self.key.to_owned()]].concat();
+
+        // a property left at its defaults counts as unset for its own conditions
+        if !self.has_default_values(values) {
+            for condition in &self.constraints.when {
+                if !condition_holds(config, path, condition, None)? {
+                    violations.push(Violation::NotApplicable {
+                        item: property_path.join(" "),
+                        condition: condition.describe(path),
+                    });
+                }
+            }
+        }
+
+        for condition in &self.constraints.must {
+            if !condition_holds(config, path, condition, None)? {
+                violations.push(Violation::Unmet {
+                    item: property_path.join(" "),
+                    condition: condition.describe(path),
+                });
+            }
+        }
+
+        for condition in &self.constraints.excludes {
+            if condition_holds(config, path, condition, None)? {
+                violations.push(Violation::Excluded {
+                    item: property_path.join(" "),
+                    condition: condition.describe(path),
+                });
+            }
+        }
+
+        for value in values {
+            let allowed = match self.constraints.find_match(value, schema)? {
+                Some((allowed, _)) => allowed,
+                None => continue,
+            };
+
+            for condition in &allowed.excludes {
+                if !condition_holds(config, path, condition, Some((&property_path, value)))? {
+                    continue;
+                }
+
+                let condition = if condition.resolve(path).as_ref() == Some(&property_path) {
+                    format!("'{}' has other values", property_path.join(" "))
+                } else {
+                    condition.describe(path)
+                };
+                violations.push(Violation::Excluded {
+                    item: format!(
+                        "{} {}",
+                        property_path.join(" "),
+                        self.constraints.shown(value)
+                    ),
+                    condition,
+                });
+            }
+        }
+
+        Ok(())
+    }
+
+    fn has_default_values(&self, values: &[String]) -> bool {
+        values.len() == self.default_values.len()
+            && values
+                .iter()
+                .all(|value| self.default_values.contains(value))
+    }
+
+    fn check_count(&self, item: &str, count: usize) -> Result<(), ElementCountError> {
+        match (self.constraints.min, self.constraints.max) {
+            (Some(min), _) if count < min => Err(ElementCountError::TooFew {
+                item: item.to_owned(),
+                min,
+            }),
+            (_, Some(max)) if count > max => Err(ElementCountError::TooMany {
+                item: item.to_owned(),
+                max,
+            }),
+            _ => Ok(()),
+        }
+    }
+
+    pub fn normalise(&self, value: &str) -> anyhow::Result<String> {
+        let schema = self
+            .schema
+            .upgrade()
+            .ok_or_else(|| anyhow!("schema weak pointer upgrade failed"))?;
+        self.constraints.normalise(value, schema.as_ref())
+    }
+
+    pub fn set(&self, value: &str) -> anyhow::Result<()> {
+        let _edit = self.changes.edit();
+        let value = self.normalise(value)?;
+        let mut values = self.values.write().map_err(|_| LockError)?;
+        self.edited();
+
+        self.set_counted(&mut values, value)
+    }
+
+    // the node is validated and applied with the property. an edit that fails
+    // still marks it, which only means it's checked again
+    fn edited(&self) {
+        self.changes.mark(self.node, Changed::Own);
+    }
+
+    // the count is checked under the same lock the value is set under, so values
+    // set at the same time can't both take the last place
+    fn set_counted(&self, values: &mut Values, value: String) -> anyhow::Result<()> {
+        if self.constraints.multiple && !pending(values).any(|existing| *existing == value) {
+            // a property below its minimum can still be added to
+            if let Err(e @ ElementCountError::TooMany { .. }) =
+                self.check_count(&self.key, pending(values).count() + 1)
+            {
+                return Err(e.into());
+            }
+        }
+
+        self.set_normalised(values, value)
+    }
+
+    // replaces the values without regard to how many there may be, that's left
+    // for validation
+    pub fn replace(&self, new_values: &[String]) -> anyhow::Result<()> {
+        let _edit = self.changes.edit();
+        let new_values = new_values
+            .iter()
+            .map(|value| self.normalise(value))
+            .collect::<anyhow::Result<Vec<String>>>()?;
+        let mut values = self.values.write().map_err(|_| LockError)?;
+        self.edited();
+
+        if !values.is_empty() {
+            self.remove_from(&mut values, None)?;
+        }
+
+        for value in &new_values {
+            self.set_normalised(&mut values, value.to_owned())?;
+        }
+
+        // values that were already there keep their place when set again, so an
+        // ordered property is put in the order it's given
+        if self.constraints.ordered {
+            let before: Vec<String> = values.keys().cloned().collect();
+            restore_order(&mut values, &new_values);
+
+            if values.keys().ne(before.iter()) {
+                self.committed_order
+                    .write()
+                    .map_err(|_| LockError)?
+                    .get_or_insert(before.clone());
+
+                for (index, (value, change)) in values.iter_mut().enumerate() {
+                    if *change == PropertyChange::Unchanged && before.get(index) != Some(value) {
+                        *change = PropertyChange::Moved;
+                    }
+                }
+            }
+        }
+
+        Ok(())
+    }
+
+    fn set_normalised(&self, values: &mut Values, value: String) -> anyhow::Result<()> {
+        // setting a value that's already there in another form isn't a change
+        match values.get(&value) {
+            None | Some(PropertyChange::Removed) => (),
+            Some(_) => return Ok(()),
+        }
 
         if !self.constraints.multiple {
             // multiple values aren't allowed so at this point there must be only one old
             // value
             if values.is_empty() {
-                values.insert(value.to_string(), PropertyChange::New);
+                values.insert(value, PropertyChange::New);
             } else {
                 let old_value = values
                     .keys()
                     .nth(0)
                     .cloned()
                     .ok_or_else(|| anyhow!("values empty after check"))?;
-                values.remove(&old_value);
-                values.insert(value.to_string(), PropertyChange::Edited { old_value });
+                values.shift_remove(&old_value);
+                values.insert(value, PropertyChange::Edited { old_value });
             }
         } else {
-            values.insert(value.to_string(), PropertyChange::New);
+            values.insert(value, PropertyChange::New);
         }
 
         Ok(())
     }
 
+    // the value as it's stored, or as given if it isn't there so that the error
+    // names it
+    fn stored_value<'a, I>(&self, value: &str, existing: I) -> anyhow::Result<String>
+    where
+        I: IntoIterator<Item = &'a String>,
+    {
+        let schema = self
+            .schema
+            .upgrade()
+            .ok_or_else(|| anyhow!("schema weak pointer upgrade failed"))?;
+
+        Ok(self
+            .constraints
+            .find_existing(value, existing, schema.as_ref())?
+            .cloned()
+            .unwrap_or_else(|| value.to_owned()))
+    }
+
     pub fn remove(&self, value: Option<&str>) -> anyhow::Result<()> {
+        let _edit = self.changes.edit();
+        let mut values = self.values.write().map_err(|_| LockError)?;
+        self.edited();
+        let value = match value {
+            Some(value) => Some(self.stored_value(value, values.keys())?),
+            None => None,
+        };
+
+        let count = pending(&values).count();
+        let remaining = match &value {
+            Some(value) => pending(&values)
+                .filter(|existing| *existing != value)
+                .count(),
+            None => 0,
+        };
+
+        // a property above its maximum can still be removed from
+        if remaining < count {
+            if let Err(e @ ElementCountError::TooFew { .. }) =
+                self.check_count(&self.key, remaining)
+            {
+                return Err(e.into());
+            }
+        }
+
+        self.remove_from(&mut values, value.as_deref())
+    }
+
+    // the value is in its stored form
+    fn remove_from(&self, values: &mut Values, value: Option<&str>) -> anyhow::Result<()> {
         let mut match_made = false;
-        let values: HashMap<String, PropertyChange> = self
-            .values
-            .try_borrow()?
+        let remaining: Values = values
             .iter()
             .filter_map(|(existing, change)| {
                 if let Some(value) = value {
@@ -122,7 +450,7 @@ impl Property {
                     PropertyChange::Edited { old_value } => {
                         Some((old_value.clone(), PropertyChange::Removed))
                     }
-                    PropertyChange::Unchanged | PropertyChange::Removed => {
+                    PropertyChange::Unchanged | PropertyChange::Removed | PropertyChange::Moved => {
                         Some((existing.clone(), PropertyChange::Removed))
                     }
                 }
@@ -131,14 +459,16 @@ impl Property {
 
         if let Some(value) = value {
             if !match_made {
-                return Err(PropertyError::NoSuchValue(value.to_string()).into());
+                return Err(
+                    PropertyError::NoSuchValue(self.constraints.shown(value).to_owned()).into(),
+                );
             }
         }
 
-        if values.is_empty() {
+        if remaining.is_empty() {
             Err(PropertyError::NoValueSet.into())
         } else {
-            self.values.replace(values);
+            *values = remaining;
 
             // TODO: test this
             // if values.is_empty() && !self.constraints.deletable {
@@ -150,46 +480,151 @@ impl Property {
     }
 }
 
+impl Property {
+    // adds the value next to an existing one. a value that's already there is
+    // moved instead
+    pub fn insert(&self, value: &str, position: &Position) -> anyhow::Result<()> {
+        let _edit = self.changes.edit();
+        let value = self.normalise(value)?;
+        let mut values = self.values.write().map_err(|_| LockError)?;
+        self.edited();
+
+        let position = self.check_order_elements(&values, None, position)?;
+        self.set_counted(&mut values, value.clone())?;
+        self.reposition(&mut values, &value, &position)
+    }
+
+    pub fn move_value(&self, value: &str, position: &Position) -> anyhow::Result<()> {
+        let _edit = self.changes.edit();
+        let value = self.normalise(value)?;
+        let mut values = self.values.write().map_err(|_| LockError)?;
+        self.edited();
+
+        let position = self.check_order_elements(&values, Some(&value), position)?;
+        self.reposition(&mut values, &value, &position)
+    }
+
+    fn reposition(
+        &self,
+        values: &mut Values,
+        value: &str,
+        position: &Position,
+    ) -> anyhow::Result<()> {
+        self.committed_order
+            .write()
+            .map_err(|_| LockError)?
+            .get_or_insert_with(|| values.keys().cloned().collect());
+        reposition(values, value, position);
+
+        if let Some(change) = values.get_mut(value) {
+            if *change == PropertyChange::Unchanged {
+                *change = PropertyChange::Moved;
+            }
+        }
+
+        Ok(())
+    }
+
+    // both the value and the anchor have to be there to order them, and the
+    // anchor may be given in any form
+    fn check_order_elements(
+        &self,
+        values: &Values,
+        value: Option<&str>,
+        position: &Position,
+    ) -> anyhow::Result<Position> {
+        if !self.constraints.ordered {
+            return Err(OrderError::NotOrdered(self.key.to_owned()).into());
+        }
+
+        let anchor = self
+            .normalise(position.anchor())
+            .unwrap_or_else(|_| position.anchor().to_owned());
+
+        for element in value.into_iter().chain(Some(anchor.as_str())) {
+            if !pending(values).any(|existing| existing == element) {
+                return Err(OrderError::NoSuchElement {
+                    item: self.key.to_owned(),
+                    element: self.constraints.shown(element).to_owned(),
+                }
+                .into());
+            }
+        }
+
+        Ok(position.relative_to(anchor))
+    }
+}
+
+// the values as they'll be once the changes are applied
+fn pending(values: &Values) -> impl Iterator<Item = &String> {
+    values
+        .iter()
+        .filter(|(_, change)| **change != PropertyChange::Removed)
+        .map(|(value, _)| value)
+}
+
+// whether the condition written in the node at path holds. a value excluding
+// its own property only cares about the property's other values
+fn condition_holds(
+    config: &Config,
+    path: &[String],
+    condition: &Condition,
+    excluding: Option<(&[String], &str)>,
+) -> anyhow::Result<bool> {
+    let target = match condition.resolve(path) {
+        Some(target) => target,
+        None => return Ok(condition.holds(&[])),
+    };
+    let mut values = config.get_pending_values(&target)?;
+
+    if let Some((property_path, value)) = excluding {
+        if target == property_path {
+            values.retain(|v| v != value);
+        }
+    }
+
+    Ok(condition.holds(&values))
+}
+
 impl Changeable for Property {
     fn is_clean(&self) -> bool {
         self.values
-            .borrow()
+            .read()
+            .expect("lock poisoned")
             .values()
             .all(|change| *change == PropertyChange::Unchanged)
     }
 
     fn apply_changes(&self) -> anyhow::Result<bool> {
+        let mut values = self.values.write().map_err(|_| LockError)?;
         let mut edits = false;
-        let new_values: HashMap<String, PropertyChange> = self
-            .values
-            .try_borrow()?
-            .iter()
-            .filter_map(|(value, change)| match change {
-                PropertyChange::New | PropertyChange::Edited { .. } => {
-                    edits = true;
-                    Some((value.clone(), PropertyChange::Unchanged))
-                }
-                PropertyChange::Removed => {
-                    edits = true;
-                    None
-                }
-                PropertyChange::Unchanged => Some((value.clone(), change.clone())),
-            })
-            .collect();
 
-        self.values.replace(new_values);
+        values.retain(|_, change| match change {
+            PropertyChange::New | PropertyChange::Edited { .. } | PropertyChange::Moved => {
+                edits = true;
+                *change = PropertyChange::Unchanged;
+                true
+            }
+            PropertyChange::Removed => {
+                edits = true;
+                false
+            }
+            PropertyChange::Unchanged => true,
+        });
+        self.committed_order.write().map_err(|_| LockError)?.take();
 
         Ok(edits)
     }
 
     fn discard_changes(&self) {
-        let new_values: HashMap<String, PropertyChange> = self
-            .values
-            .borrow()
+        let mut values = self.values.write().expect("lock poisoned");
+        let mut new_values: Values = values
             .iter()
             .filter_map(|(value, change)| match change {
                 PropertyChange::New => None,
-                PropertyChange::Removed => Some((value.clone(), PropertyChange::Unchanged)),
+                PropertyChange::Removed | PropertyChange::Moved => {
+                    Some((value.clone(), PropertyChange::Unchanged))
+                }
                 PropertyChange::Edited { old_value } => {
                     Some((old_value.clone(), PropertyChange::Unchanged))
                 }
@@ -197,13 +632,18 @@ impl Changeable for Property {
             })
             .collect();
 
-        self.values.replace(new_values);
+        if let Some(order) = self.committed_order.write().expect("lock poisoned").take() {
+            restore_order(&mut new_values, &order);
+        }
+
+        *values = new_values;
     }
 }
 
 impl Property {
     pub fn pretty_print(&self, indent: usize) {
-        for (value, change) in self.values.borrow().iter() {
+        for (value, change) in self.values.read().expect("lock poisoned").iter() {
+            let value = self.constraints.shown(value);
             match change {
                 PropertyChange::New => println!(
                     "{:indent$}{}{} {}",
@@ -227,7 +667,7 @@ impl Property {
                         "",
                         "-".red(),
                         self.key.red(),
-                        old_value.red(),
+                        self.constraints.shown(old_value).red(),
                         indent = indent * 4
                     );
                     println!(
@@ -239,6 +679,14 @@ impl Property {
                         indent = indent * 4
                     )
                 }
+                PropertyChange::Moved => println!(
+                    "{:indent$}{}{} {}",
+                    "",
+                    "~".yellow(),
+                    self.key.yellow(),
+                    value.yellow(),
+                    indent = indent * 4
+                ),
                 PropertyChange::Unchanged => {
                     println!("{:indent$}{} {}", "", self.key, value, indent = indent * 4);
                 }
@@ -1,15 +1,17 @@
-use crate::error::LoadError;
+use crate::{error::LoadError, schema::Schema};
 use chrono::prelude::*;
+use indexmap::IndexMap;
 use rp_log::*;
 use serde::Deserialize;
-use std::{collections::HashMap, io::Read, rc::Rc};
+use std::{collections::HashMap, io::Read, sync::Arc};
 
-pub fn load<T>(thing: &dyn Load, src: T) -> anyhow::Result<()>
+pub fn load<T>(thing: &dyn Load, schema: &Schema, src: T) -> anyhow::Result<()>
 where
     T: Read,
 {
     let mut source = serde_json::from_reader::<T, LoadSource>(src)?;
     trace!("Loading from source: {:?}", source);
+    source.migrate(schema)?;
     thing.load(&mut source)
 }
 
@@ -20,9 +22,12 @@ pub trait Load {
 #[derive(Debug, Deserialize)]
 pub struct LoadSource {
     timestamp: DateTime<Utc>,
-    nodes: HashMap<String, Rc<LoadNode>>,
+    // saves from before versioning don't have one
+    #[serde(default)]
+    pub(super) version: u32,
+    pub(super) nodes: IndexMap<String, Arc<LoadNode>>,
     #[serde(skip)]
-    node_stack: Vec<Rc<LoadNode>>,
+    node_stack: Vec<Arc<LoadNode>>,
 }
 
 impl LoadSource {
@@ -32,7 +37,7 @@ impl LoadSource {
         } else {
             self.nodes
                 .get(name)
-                .map(|n| Rc::clone(n))
+                .map(|n| Arc::clone(n))
                 .ok_or_else(|| LoadError::NoSuchNode(name.to_owned()).into())
         }?;
 
@@ -62,17 +67,17 @@ impl LoadSource {
     }
 }
 
-#[derive(Debug, Deserialize)]
-struct LoadNode {
-    subnodes: HashMap<String, Rc<LoadNode>>,
-    properties: HashMap<String, Vec<String>>,
+#[derive(Debug, Deserialize, Clone, Default)]
+pub(super) struct LoadNode {
+    pub(super) subnodes: IndexMap<String, Arc<LoadNode>>,
+    pub(super) properties: HashMap<String, Vec<String>>,
 }
 
 impl LoadNode {
-    pub fn get_node(&self, name: &str) -> anyhow::Result<Rc<LoadNode>> {
+    pub fn get_node(&self, name: &str) -> anyhow::Result<Arc<LoadNode>> {
         self.subnodes
             .get(name)
-            .map(|n| Rc::clone(n))
+            .map(|n| Arc::clone(n))
             .ok_or_else(|| LoadError::NoSuchNode(name.to_string()).into())
     }
 
@@ -0,0 +1,264 @@
+use super::load::{LoadNode, LoadSource};
+use crate::{
+    error::MigrationError,
+    schema::{MigrationStep, Schema, WILDCARD},
+};
+use anyhow::anyhow;
+use indexmap::IndexMap;
+use rp_log::*;
+use std::{mem, sync::Arc};
+
+type LoadNodes = IndexMap<String, Arc<LoadNode>>;
+
+impl LoadSource {
+    pub(super) fn migrate(&mut self, schema: &Schema) -> anyhow::Result<()> {
+        let schema_version = schema.version();
+        if self.version > schema_version {
+            return Err(MigrationError::NewerSave {
+                save: self.version,
+                schema: schema_version,
+            }
+            .into());
+        }
+
+        for migration in schema.migrations_from(self.version) {
+            debug!(
+                "Migrating save from version {} to {}",
+                self.version, migration.version
+            );
+
+            for step in &migration.steps {
+                apply(&mut self.nodes, step)?;
+            }
+            self.version = migration.version;
+        }
+
+        // not every version bump needs a migration
+        self.version = schema_version;
+        Ok(())
+    }
+}
+
+// steps whose source doesn't exist in the save are skipped, the save simply didn't
+// have anything to migrate there
+fn apply(nodes: &mut LoadNodes, step: &MigrationStep) -> anyhow::Result<()> {
+    let matches = resolve(nodes, step.path());
+    if matches.is_empty() {
+        trace!("Nothing to migrate in save for step {:?}", step);
+    }
+
+    for (path, captures) in matches {
+        match step {
+            MigrationStep::RenameNode { to, .. } => {
+                let mut destination = path.clone();
+                destination.pop();
+                destination.push(to.to_owned());
+                move_node(nodes, &path, &destination)?;
+            }
+            MigrationStep::MoveNode { to, .. } => {
+                move_node(nodes, &path, &substitute(to, &captures))?;
+            }
+            MigrationStep::DropNode { .. } => {
+                take_node(nodes, &path);
+            }
+            MigrationStep::RenameProperty { property, to, .. } => {
+                move_property(nodes, &path, property, &path, to)?;
+            }
+            MigrationStep::MoveProperty { property, to, .. } => {
+                move_property(nodes, &path, property, &substitute(to, &captures), property)?;
+            }
+            MigrationStep::DropProperty { property, .. } => {
+                if let Some(node) = node_mut(nodes, &path, false) {
+                    node.properties.remove(property);
+                }
+            }
+            MigrationStep::TransformProperty {
+                property, values, ..
+            } => {
+                if let Some(existing) =
+                    node_mut(nodes, &path, false).and_then(|node| node.properties.get_mut(property))
+                {
+                    for value in existing.iter_mut() {
+                        if let Some(new_value) = values.get(value) {
+                            *value = new_value.to_owned();
+                        }
+                    }
+                }
+            }
+        }
+    }
+
+    Ok(())
+}
+
+// every existing node path matching the pattern, along with the names its wildcards
+// matched
+fn resolve(nodes: &LoadNodes, pattern: &[String]) -> Vec<(Vec<String>, Vec<String>)> {
+    let mut matches = Vec::new();
+    resolve_into(
+        nodes,
+        pattern,
+        &mut Vec::new(),
+        &mut Vec::new(),
+        &mut matches,
+    );
+    matches
+}
+
+fn resolve_into(
+    nodes: &LoadNodes,
+    pattern: &[String],
+    path: &mut Vec<String>,
+    captures: &mut Vec<String>,
+    matches: &mut Vec<(Vec<String>, Vec<String>)>,
+) {
+    let (first, rest) = match pattern.split_first() {
+        Some(split) => split,
+        None => {
+            matches.push((path.clone(), captures.clone()));
+            return;
+        }
+    };
+
+    if first == WILDCARD {
+        for (name, node) in nodes {
+            path.push(name.to_owned());
+            captures.push(name.to_owned());
+            resolve_into(&node.subnodes, rest, path, captures, matches);
+            captures.pop();
+            path.pop();
+        }
+    } else if let Some(node) = nodes.get(first) {
+        path.push(first.to_owned());
+        resolve_into(&node.subnodes, rest, path, captures, matches);
+        path.pop();
+    }
+}
+
+fn substitute(pattern: &[String], captures: &[String]) -> Vec<String> {
+    let mut captures = captures.iter();
+    pattern
+        .iter()
+        .map(|name| {
+            if name == WILDCARD {
+                captures.next().unwrap_or(name).to_owned()
+            } else {
+                name.to_owned()
+            }
+        })
+        .collect()
+}
+
+// the save was just deserialised so nothing else refers to its nodes and
+// make_mut won't actually clone anything
+fn node_mut<'a>(
+    nodes: &'a mut LoadNodes,
+    path: &[String],
+    create: bool,
+) -> Option<&'a mut LoadNode> {
+    let (first, rest) = path.split_first()?;
+    let node = if create {
+        nodes.entry(first.to_owned()).or_insert_with(Arc::default)
+    } else {
+        nodes.get_mut(first)?
+    };
+    let node = Arc::make_mut(node);
+
+    if rest.is_empty() {
+        Some(node)
+    } else {
+        node_mut(&mut node.subnodes, rest, create)
+    }
+}
+
+// the node and where it was among its siblings
+fn take_node(nodes: &mut LoadNodes, path: &[String]) -> Option<(usize, Arc<LoadNode>)> {
+    let (last, parents) = path.split_last()?;
+    let siblings = if parents.is_empty() {
+        nodes
+    } else {
+        &mut node_mut(nodes, parents, false)?.subnodes
+    };
+
+    siblings
+        .shift_remove_full(last)
+        .map(|(index, _, node)| (index, node))
+}
+
+// a node moved within its parent, e.g. renamed, keeps its place so that the nodes
+// of an ordered multi node stay in order. one moved elsewhere goes last
+fn put_node(nodes: &mut LoadNodes, name: &str, node: Arc<LoadNode>, index: Option<usize>) {
+    match index {
+        Some(index) if index < nodes.len() => {
+            let mut entries: Vec<(String, Arc<LoadNode>)> = mem::take(nodes).into_iter().collect();
+            entries.insert(index, (name.to_owned(), node));
+            *nodes = entries.into_iter().collect();
+        }
+        _ => {
+            nodes.insert(name.to_owned(), node);
+        }
+    }
+}
+
+fn move_node(nodes: &mut LoadNodes, from: &[String], to: &[String]) -> anyhow::Result<()> {
+    if from == to {
+        return Ok(());
+    }
+
+    let (last, parents) = to
+        .split_last()
+        .ok_or_else(|| anyhow!("Empty migration destination for node {:?}", from))?;
+    let exists = if parents.is_empty() {
+        nodes.contains_key(last)
+    } else {
+        node_mut(nodes, parents, false).map_or(false, |parent| parent.subnodes.contains_key(last))
+    };
+
+    if exists {
+        return Err(MigrationError::DestinationExists(to.join(".")).into());
+    }
+
+    if let Some((index, node)) = take_node(nodes, from) {
+        let index = if from[..from.len() - 1] == *parents {
+            Some(index)
+        } else {
+            None
+        };
+        if parents.is_empty() {
+            put_node(nodes, last, node, index);
+        } else if let Some(parent) = node_mut(nodes, parents, true) {
+            put_node(&mut parent.subnodes, last, node, index);
+        }
+    }
+
+    Ok(())
+}
+
+fn move_property(
+    nodes: &mut LoadNodes,
+    from: &[String],
+    property: &str,
+    to: &[String],
+    to_property: &str,
+) -> anyhow::Result<()> {
+    if from == to && property == to_property {
+        return Ok(());
+    }
+
+    let values =
+        match node_mut(nodes, from, false).and_then(|node| node.properties.remove(property)) {
+            Some(values) => values,
+            None => return Ok(()),
+        };
+
+    let node = node_mut(nodes, to, true)
+        .ok_or_else(|| anyhow!("Empty migration destination for property '{}'", property))?;
+    if node.properties.contains_key(to_property) {
+        return Err(
+            MigrationError::DestinationExists(format!("{}.{}", to.join("."), to_property)).into(),
+        );
+    }
+
+    node.properties.insert(to_property.to_owned(), values);
+    Ok(())
+}
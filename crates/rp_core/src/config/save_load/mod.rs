@@ -1,2 +1,3 @@
 pub mod load;
+mod migrate;
 pub mod save;
@@ -1,15 +1,20 @@
-use crate::error::SaveError;
+use crate::error::{LockError, SaveError};
 use chrono::prelude::*;
+use indexmap::IndexMap;
 use rp_log::*;
 use serde::Serialize;
 use serde_json;
-use std::{cell::RefCell, collections::HashMap, io::Write, rc::Rc};
+use std::{
+    collections::HashMap,
+    io::Write,
+    sync::{Arc, RwLock},
+};
 
-pub fn save<T>(thing: &dyn Save, dest: T) -> anyhow::Result<()>
+pub fn save<T>(thing: &dyn Save, version: u32, dest: T) -> anyhow::Result<()>
 where
     T: Write,
 {
-    let mut builder = SaveBuilder::new();
+    let mut builder = SaveBuilder::new(version);
     thing.save(&mut builder)?;
     trace!("Built save: {:?}", builder);
     serde_json::to_writer(dest, &builder)?;
@@ -23,27 +28,29 @@ pub trait Save {
 #[derive(Debug, Serialize)]
 pub struct SaveBuilder {
     timestamp: DateTime<Utc>,
-    nodes: HashMap<String, Rc<SaveNode>>,
+    version: u32,
+    nodes: IndexMap<String, Arc<SaveNode>>,
     #[serde(skip)]
-    node_stack: Vec<Rc<SaveNode>>,
+    node_stack: Vec<Arc<SaveNode>>,
 }
 
 impl SaveBuilder {
-    fn new() -> Self {
+    fn new(version: u32) -> Self {
         SaveBuilder {
             timestamp: Utc::now(),
-            nodes: HashMap::new(),
+            version,
+            nodes: IndexMap::new(),
             node_stack: Vec::new(),
         }
     }
 
     pub fn begin_node(&mut self, name: String) -> anyhow::Result<()> {
-        let new_node = Rc::new(SaveNode::new());
+        let new_node = Arc::new(SaveNode::new());
 
         if let Some(node) = self.node_stack.last() {
-            node.insert_node(name, Rc::clone(&new_node))?;
+            node.insert_node(name, Arc::clone(&new_node))?;
         } else {
-            self.nodes.insert(name, Rc::clone(&new_node));
+            self.nodes.insert(name, Arc::clone(&new_node));
         }
 
         self.node_stack.push(new_node);
@@ -67,26 +74,30 @@ impl SaveBuilder {
 
 #[derive(Debug, Serialize)]
 struct SaveNode {
-    subnodes: RefCell<HashMap<String, Rc<SaveNode>>>,
-    properties: RefCell<HashMap<String, Vec<String>>>,
+    subnodes: RwLock<IndexMap<String, Arc<SaveNode>>>,
+    properties: RwLock<HashMap<String, Vec<String>>>,
 }
 
 impl SaveNode {
     pub fn new() -> Self {
         SaveNode {
-            subnodes: RefCell::new(HashMap::new()),
-            properties: RefCell::new(HashMap::new()),
+            subnodes: RwLock::new(IndexMap::new()),
+            properties: RwLock::new(HashMap::new()),
         }
     }
 
-    pub fn insert_node(&self, name: String, node: Rc<SaveNode>) -> anyhow::Result<()> {
-        self.subnodes.try_borrow_mut()?.insert(name, node);
+    pub fn insert_node(&self, name: String, node: Arc<SaveNode>) -> anyhow::Result<()> {
+        self.subnodes
+            .write()
+            .map_err(|_| LockError)?
+            .insert(name, node);
         Ok(())
     }
 
     pub fn set_property(&self, name: String, value: String) -> anyhow::Result<()> {
         self.properties
-            .try_borrow_mut()?
+            .write()
+            .map_err(|_| LockError)?
             .entry(name)
             .or_insert_with(Vec::new)
             .push(value);
@@ -0,0 +1,7 @@
+use thiserror::Error;
+
+#[derive(Debug, Error)]
+pub enum ApplyError {
+    #[error("Changes are already being applied from within another edit")]
+    AlreadyApplying,
+}
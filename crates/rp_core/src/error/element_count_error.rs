@@ -0,0 +1,9 @@
+use thiserror::Error;
+
+#[derive(Debug, Error)]
+pub enum ElementCountError {
+    #[error("'{item}' can't have more than {max} value(s) or node(s)")]
+    TooMany { item: String, max: usize },
+    #[error("'{item}' can't have fewer than {min} value(s) or node(s)")]
+    TooFew { item: String, min: usize },
+}
@@ -0,0 +1,7 @@
+use thiserror::Error;
+
+#[derive(Debug, Error)]
+pub enum IncludeError {
+    #[error("Schema includes itself: {}", .0.join(" -> "))]
+    Cycle(Vec<String>),
+}
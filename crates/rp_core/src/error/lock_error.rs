@@ -0,0 +1,5 @@
+use thiserror::Error;
+
+#[derive(Debug, Error)]
+#[error("Shared state was left unusable by a thread that panicked while changing it")]
+pub struct LockError;
@@ -3,6 +3,10 @@ use thiserror::Error;
 
 #[derive(Debug, Error)]
 pub enum MergeError {
-    #[error("Merge conflict: {this} conflicts with {that}")]
-    Conflict { this: String, that: String },
+    #[error("Merge conflict at '{path}': {this} conflicts with {that}")]
+    Conflict {
+        path: String,
+        this: String,
+        that: String,
+    },
 }
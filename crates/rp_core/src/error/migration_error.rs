@@ -0,0 +1,9 @@
+use thiserror::Error;
+
+#[derive(Debug, Error)]
+pub enum MigrationError {
+    #[error("Save version {save} is newer than the schema version {schema}")]
+    NewerSave { save: u32, schema: u32 },
+    #[error("Migration destination '{0}' already exists")]
+    DestinationExists(String),
+}
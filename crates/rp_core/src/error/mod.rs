@@ -1,21 +1,47 @@
+mod apply_error;
 mod command_error;
 mod constraint_error;
+mod element_count_error;
 mod format_error;
+mod include_error;
 mod load_error;
+mod lock_error;
 mod merge_error;
+mod migration_error;
+mod node_creation_error;
 mod node_removal_error;
 mod not_implemented;
+mod order_error;
+mod path_error;
 mod property_error;
+mod reference_error;
 mod save_error;
+mod schema_errors;
 mod schema_validation_error;
+mod schema_warning;
+mod source_command_error;
+mod validation_error;
 
+pub use apply_error::ApplyError;
 pub use command_error::{CommandError, ExpectedValue};
 pub use constraint_error::ConstraintError;
+pub use element_count_error::ElementCountError;
 pub use format_error::FormatError;
+pub use include_error::IncludeError;
 pub use load_error::LoadError;
+pub use lock_error::LockError;
 pub use merge_error::MergeError;
+pub use migration_error::MigrationError;
+pub use node_creation_error::NodeCreationError;
 pub use node_removal_error::NodeRemovalError;
 pub use not_implemented::NotImplemented;
+pub use order_error::OrderError;
+pub use path_error::PathError;
 pub use property_error::PropertyError;
+pub use reference_error::ReferenceError;
 pub use save_error::SaveError;
+pub use schema_errors::{SchemaError, SchemaErrors};
 pub use schema_validation_error::SchemaValidationError;
+pub use schema_warning::SchemaWarning;
+pub use source_command_error::SourceCommandError;
+pub use validation_error::{ValidationError, Violation};
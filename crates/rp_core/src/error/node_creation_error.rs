@@ -0,0 +1,11 @@
+use thiserror::Error;
+
+#[derive(Debug, Error)]
+pub enum NodeCreationError {
+    #[error("Node name '{name}' doesn't match template '{template}'")]
+    NameMismatch { name: String, template: String },
+    #[error(
+        "Cannot create node '{name}' in '{node}' (its nodes are only sourced from the system)"
+    )]
+    NotAllowed { name: String, node: String },
+}
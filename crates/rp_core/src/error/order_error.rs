@@ -0,0 +1,9 @@
+use thiserror::Error;
+
+#[derive(Debug, Error)]
+pub enum OrderError {
+    #[error("'{0}' isn't ordered")]
+    NotOrdered(String),
+    #[error("'{item}' has no value or node '{element}'")]
+    NoSuchElement { item: String, element: String },
+}
@@ -0,0 +1,15 @@
+use thiserror::Error;
+
+#[derive(Debug, Error)]
+pub enum PathError {
+    #[error("No node or property '{element}' in '{path}'")]
+    Unknown { element: String, path: String },
+    #[error("'{key}' in '{path}' doesn't match template '{template}'")]
+    KeyMismatch {
+        key: String,
+        template: String,
+        path: String,
+    },
+    #[error("'{element}' follows property '{path}'")]
+    PastProperty { element: String, path: String },
+}
@@ -0,0 +1,9 @@
+use thiserror::Error;
+
+#[derive(Debug, Error)]
+#[error("Property '{property}' refers to '{value}' which doesn't exist in '{reference}'")]
+pub struct ReferenceError {
+    pub property: String,
+    pub value: String,
+    pub reference: String,
+}
@@ -0,0 +1,30 @@
+use crate::schema::SchemaLocation;
+use std::fmt::{Debug, Display};
+use thiserror::Error;
+
+#[derive(Debug, Error)]
+#[error("Schema has {} error(s):\n   -> {}", errors.len(), errors
+                .iter()
+                .map(|e| format!("{}", e))
+                .collect::<Vec<String>>()
+                .join("\n   -> "))]
+pub struct SchemaErrors {
+    pub errors: Vec<SchemaError>,
+}
+
+impl SchemaErrors {
+    // the first error of the given type, e.g. a SchemaValidationError
+    pub fn find<E>(&self) -> Option<&E>
+    where
+        E: Display + Debug + Send + Sync + 'static,
+    {
+        self.errors.iter().find_map(|e| e.error.downcast_ref())
+    }
+}
+
+#[derive(Debug, Error)]
+#[error("{location}: {error:#}")]
+pub struct SchemaError {
+    pub location: SchemaLocation,
+    pub error: anyhow::Error,
+}
@@ -15,4 +15,39 @@ pub enum SchemaValidationError {
     NoMultipleValuesAllowed,
     #[error("Default value {0:?} doesn't match any given value")]
     InvalidDefaultValue(DefaultValue),
+    #[error("Multi node '{0}' has neither a source command nor a template")]
+    NoNodeSource(String),
+    #[error("Migration to version {version} is out of order or beyond the schema version {schema_version}")]
+    MigrationVersion { version: u32, schema_version: u32 },
+    #[error("Invalid path in migration step {0}")]
+    MigrationPath(String),
+    #[error("Reference '{0}' doesn't lead to a multi node through single nodes")]
+    InvalidReference(String),
+    #[error("Condition on '{property}' in '{node}' doesn't lead to a property or its value isn't allowed")]
+    InvalidCondition { node: String, property: String },
+    #[error("Element count limits {min:?}..{max:?} are out of order or allow multiple elements where there can only be one")]
+    ElementCount {
+        min: Option<usize>,
+        max: Option<usize>,
+    },
+    #[error("Include '{0}' not resolved, the schema has to be read from a path")]
+    UnresolvedInclude(String),
+    #[error("Only properties with multiple values can be ordered")]
+    OrderedSingleValue,
+    #[error("Default values don't fit the element count limits {min:?}..{max:?}")]
+    DefaultCount {
+        min: Option<usize>,
+        max: Option<usize>,
+    },
+    #[error("Template references form a cycle: {}", .0.join(" -> "))]
+    TemplateCycle(Vec<String>),
+    #[error("Length limits {min:?}..{max:?} are out of order")]
+    Length {
+        min: Option<usize>,
+        max: Option<usize>,
+    },
+    #[error("Composite template combines no templates")]
+    EmptyTemplate,
+    #[error("Range step {0} isn't positive or isn't whole in an integer range")]
+    Step(f64),
 }
@@ -0,0 +1,17 @@
+use thiserror::Error;
+
+// things in a schema that are valid but most likely not what was meant, found by
+// linting it
+#[derive(Debug, Error)]
+pub enum SchemaWarning {
+    #[error("Template '{0}' isn't used by any node, property or other template")]
+    UnusedTemplate(String),
+    #[error("Literal value '{literal}' is already matched by {by}")]
+    RedundantLiteral { literal: String, by: String },
+    #[error("Regex '{0}' matches the empty string, and so any value")]
+    EmptyMatch(String),
+    #[error("Range {0} has no values in it once they're rounded")]
+    EmptyRange(String),
+    #[error("Default value '{default}' breaks the condition that {condition}")]
+    UnsatisfiableDefault { default: String, condition: String },
+}
@@ -0,0 +1,12 @@
+use crate::schema::SourceCommand;
+use thiserror::Error;
+
+#[derive(Debug, Error)]
+pub enum SourceCommandError {
+    #[error("No such source function: '{0}'")]
+    UnknownFunction(String),
+    #[error("No such source property: '{0}'")]
+    UnknownProperty(String),
+    #[error("Source {0} returned no values")]
+    NoValues(SourceCommand),
+}
@@ -0,0 +1,28 @@
+use super::{ElementCountError, ReferenceError};
+use thiserror::Error;
+
+#[derive(Debug, Error)]
+#[error("Configuration has {} problem(s):\n   -> {}", violations.len(), violations
+                .iter()
+                .map(|v| format!("{}", v))
+                .collect::<Vec<String>>()
+                .join("\n   -> "))]
+pub struct ValidationError {
+    pub violations: Vec<Violation>,
+}
+
+#[derive(Debug, Error)]
+pub enum Violation {
+    #[error("Mandatory property '{0}' has no value")]
+    MissingMandatory(String),
+    #[error("{0}")]
+    Reference(#[from] ReferenceError),
+    #[error("{0}")]
+    ElementCount(#[from] ElementCountError),
+    #[error("'{item}' is only valid when {condition}")]
+    NotApplicable { item: String, condition: String },
+    #[error("'{item}' requires that {condition}")]
+    Unmet { item: String, condition: String },
+    #[error("'{item}' can't be set while {condition}")]
+    Excluded { item: String, condition: String },
+}
@@ -0,0 +1,133 @@
+use super::{JsonSchema, Schema, SchemaNode, WILDCARD};
+use serde::Serialize;
+use serde_json::{json, Map};
+
+// every node and property of the schema by path, for tools that build forms or API
+// clients from the schema rather than check saves against it. nodes in multi nodes
+// are under a wildcard, as in migrations and conditions
+impl Schema {
+    pub fn to_api_model(&self) -> anyhow::Result<serde_json::Value> {
+        let mut templates = Map::new();
+        for (name, template) in &self.templates {
+            templates.insert(
+                name.to_owned(),
+                json!({
+                    "description": template.to_string(),
+                    "definition": serde_json::to_value(template)?,
+                    "schema": template.json_schema(),
+                }),
+            );
+        }
+
+        let mut model = ApiModel::default();
+        for (name, node) in &self.nodes {
+            model.add_node(node, vec![name.to_owned()])?;
+        }
+
+        model.nodes.sort_by(|(a, _), (b, _)| a.cmp(b));
+        model.properties.sort_by(|(a, _), (b, _)| a.cmp(b));
+
+        let nodes: Vec<serde_json::Value> = model.nodes.into_iter().map(|(_, node)| node).collect();
+        let properties: Vec<serde_json::Value> = model
+            .properties
+            .into_iter()
+            .map(|(_, property)| property)
+            .collect();
+
+        Ok(json!({
+            "version": self.version(),
+            "templates": templates,
+            "nodes": nodes,
+            "properties": properties,
+        }))
+    }
+}
+
+// the entries are kept with their paths until they're sorted
+#[derive(Default)]
+struct ApiModel {
+    nodes: Vec<(Vec<String>, serde_json::Value)>,
+    properties: Vec<(Vec<String>, serde_json::Value)>,
+}
+
+impl ApiModel {
+    fn add_node(&mut self, node: &SchemaNode, path: Vec<String>) -> anyhow::Result<()> {
+        match node {
+            SchemaNode::SingleSchemaNode(single) => {
+                let entry = json!({
+                    "path": path,
+                    "kind": "single",
+                    "description": single.description,
+                });
+                self.nodes.push((path.clone(), entry));
+
+                for (name, property) in &single.properties {
+                    let mut values = Vec::new();
+                    for allowed in &property.values {
+                        let mut value = serde_json::to_value(allowed)?;
+                        if let Some(value) = value.as_object_mut() {
+                            value.insert(String::from("summary"), json!(allowed.value.to_string()));
+                        }
+                        values.push(value);
+                    }
+
+                    let property_path = [&path[..], &[name.to_owned()]].concat();
+                    let entry = json!({
+                        "path": property_path,
+                        "description": property.description,
+                        "mandatory": property.mandatory,
+                        "multiple": property.multiple,
+                        "ordered": property.ordered,
+                        "secret": property.secret,
+                        "deletable": property.deletable,
+                        "min": property.min,
+                        "max": property.max,
+                        "default": sorted(&property.default)?,
+                        "values": sorted(&values)?,
+                        "when": property.when,
+                        "must": property.must,
+                        "excludes": property.excludes,
+                    });
+                    self.properties.push((property_path, entry));
+                }
+
+                for (name, subnode) in &single.subnodes {
+                    self.add_node(subnode, [&path[..], &[name.to_owned()]].concat())?;
+                }
+            }
+            SchemaNode::MultiSchemaNode(multi) => {
+                let entry = json!({
+                    "path": path,
+                    "kind": "multi",
+                    "description": multi.description,
+                    "key": {
+                        "name": multi.source.id,
+                        "template": multi.source.template,
+                        "sourced": multi.source.command.is_some(),
+                    },
+                    "min": multi.min,
+                    "max": multi.max,
+                    "ordered": multi.ordered,
+                    "when": multi.when,
+                });
+                self.nodes.push((path.clone(), entry));
+
+                self.add_node(&multi.node, [&path[..], &[WILDCARD.to_owned()]].concat())?;
+            }
+        }
+
+        Ok(())
+    }
+}
+
+// sets in the schema come out in the same order every time
+fn sorted<'a, T: Serialize + 'a>(
+    items: impl IntoIterator<Item = &'a T>,
+) -> anyhow::Result<Vec<serde_json::Value>> {
+    let mut values = items
+        .into_iter()
+        .map(serde_json::to_value)
+        .collect::<Result<Vec<_>, _>>()?;
+    values.sort_by_key(|value| value.to_string());
+    Ok(values)
+}
@@ -20,6 +20,28 @@ impl Bound {
             Bound::Exclusive(bound) => value < bound,
         })
     }
+
+    // whether every value above the other lower bound is above this one
+    pub fn below(&self, other: Bound) -> bool {
+        match (self, other) {
+            (Bound::Exclusive(this), Bound::Inclusive(that)) => *this < that,
+            _ => self.value() <= other.value(),
+        }
+    }
+
+    // whether every value below the other upper bound is below this one
+    pub fn above(&self, other: Bound) -> bool {
+        match (self, other) {
+            (Bound::Exclusive(this), Bound::Inclusive(that)) => *this > that,
+            _ => self.value() >= other.value(),
+        }
+    }
+
+    pub fn value(&self) -> OrderedFloat<f64> {
+        match self {
+            Bound::Inclusive(value) | Bound::Exclusive(value) => *value,
+        }
+    }
 }
 
 #[cfg(test)]
@@ -39,4 +61,18 @@ mod tests {
         assert!(lower_exclusive.match_against_with(upper_inclusive, 1.5));
         assert!(lower_exclusive.match_against_with(upper_exclusive, 1.5));
     }
+
+    #[test]
+    fn covers() {
+        let inclusive = Bound::Inclusive(1.0.into());
+        let exclusive = Bound::Exclusive(1.0.into());
+
+        assert!(inclusive.below(exclusive));
+        assert!(!exclusive.below(inclusive));
+        assert!(exclusive.below(Bound::Inclusive(1.5.into())));
+
+        assert!(inclusive.above(exclusive));
+        assert!(!exclusive.above(inclusive));
+        assert!(exclusive.above(Bound::Inclusive(0.5.into())));
+    }
 }
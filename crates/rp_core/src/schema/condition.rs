@@ -0,0 +1,154 @@
+use super::{Schema, SchemaItem, SchemaNode, ValidationReport, WILDCARD};
+use crate::error;
+use serde::{Deserialize, Serialize};
+use std::sync::Arc;
+
+// goes up a node in a condition's property path
+pub const PARENT: &str = "..";
+
+// a property having a value, or a specific one, or lacking them. the property is
+// given relative to the node the condition is written in
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq, Eq, Hash)]
+pub struct Condition {
+    pub property: Vec<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub value: Option<String>,
+    #[serde(default)]
+    pub absent: bool,
+}
+
+impl Condition {
+    // the path of the property when the condition is written in the node at base,
+    // or none if it goes above the root
+    pub fn resolve(&self, base: &[String]) -> Option<Vec<String>> {
+        let mut path = base.to_vec();
+
+        for name in &self.property {
+            if name == PARENT {
+                path.pop()?;
+            } else {
+                path.push(name.to_owned());
+            }
+        }
+
+        Some(path)
+    }
+
+    pub fn holds(&self, values: &[String]) -> bool {
+        let present = match &self.value {
+            Some(value) => values.contains(value),
+            None => !values.is_empty(),
+        };
+
+        present != self.absent
+    }
+
+    // e.g. "'interfaces ethernet eth0 address' is 'dhcp'"
+    pub fn describe(&self, base: &[String]) -> String {
+        let path = self
+            .resolve(base)
+            .unwrap_or_else(|| self.property.clone())
+            .join(" ");
+
+        match (&self.value, self.absent) {
+            (None, false) => format!("'{}' is set", path),
+            (None, true) => format!("'{}' isn't set", path),
+            (Some(value), false) => format!("'{}' is '{}'", path, value),
+            (Some(value), true) => format!("'{}' isn't '{}'", path, value),
+        }
+    }
+
+    // base is the path of the node the condition is written in, with multi node
+    // children as wildcards. the property has to exist there and allow the value
+    pub fn validate_at(&self, schema: &Schema, base: &[String]) -> anyhow::Result<()> {
+        let invalid = || error::SchemaValidationError::InvalidCondition {
+            node: base.join(" "),
+            property: self.property.join(" "),
+        };
+
+        let path = self.resolve(base).ok_or_else(invalid)?;
+        let (key, node) = path.split_last().ok_or_else(invalid)?;
+        let property = match schema.find_node_at(node) {
+            Some(SchemaNode::SingleSchemaNode(single)) => single.properties.get(key),
+            _ => None,
+        }
+        .ok_or_else(invalid)?;
+
+        if let Some(value) = &self.value {
+            if !property.allows(value, schema)? {
+                return Err(invalid().into());
+            }
+        }
+
+        Ok(())
+    }
+}
+
+impl Schema {
+    // like find_multi_node but multi nodes are gone through with wildcards
+    pub fn find_node_at(&self, path: &[String]) -> Option<&SchemaNode> {
+        let (first, rest) = path.split_first()?;
+        let mut current = &**self.nodes.get(first)?;
+
+        for name in rest {
+            current = match current {
+                SchemaNode::SingleSchemaNode(single) => &**single.subnodes.get(name)?,
+                SchemaNode::MultiSchemaNode(multi) if name == WILDCARD => &multi.node,
+                SchemaNode::MultiSchemaNode(_) => return None,
+            };
+        }
+
+        Some(current)
+    }
+
+    // conditions are relative to where they're written, so they can't be checked
+    // by the nodes themselves
+    pub(super) fn validate_conditions(&self, report: &mut ValidationReport) {
+        for (name, node) in &self.nodes {
+            self.validate_node_conditions(node, &[name.to_owned()], report);
+        }
+    }
+
+    fn validate_node_conditions(
+        &self,
+        node: &SchemaNode,
+        path: &[String],
+        report: &mut ValidationReport,
+    ) {
+        match node {
+            SchemaNode::SingleSchemaNode(single) => {
+                for (name, property) in &single.properties {
+                    for condition in property.conditions() {
+                        report.check(
+                            SchemaItem::Property(Arc::clone(&single.locator), name.to_owned()),
+                            condition.validate_at(self, path),
+                        );
+                    }
+                }
+
+                for (name, subnode) in &single.subnodes {
+                    self.validate_node_conditions(
+                        subnode,
+                        &[path, &[name.to_owned()]].concat(),
+                        report,
+                    );
+                }
+            }
+            SchemaNode::MultiSchemaNode(multi) => {
+                // the multi node's conditions are about the node it's in
+                for condition in &multi.when {
+                    report.check(
+                        SchemaItem::Node(Arc::clone(&multi.locator)),
+                        condition.validate_at(self, &path[..path.len() - 1]),
+                    );
+                }
+
+                self.validate_node_conditions(
+                    &multi.node,
+                    &[path, &[WILDCARD.to_owned()]].concat(),
+                    report,
+                );
+            }
+        }
+    }
+}
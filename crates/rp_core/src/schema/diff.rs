@@ -0,0 +1,430 @@
+use super::{MigrationStep, Property, Schema, SchemaNode, Template, Value, WILDCARD};
+use std::{
+    collections::{HashMap, HashSet},
+    fmt,
+    sync::Arc,
+};
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Compatibility {
+    // saves made with the older schema load as they are, or are migrated to fit
+    Compatible,
+    // some saves made with the older schema won't load
+    Breaking,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum Change {
+    NodeAdded,
+    NodeRemoved,
+    // a single node became a multi node or the other way around
+    NodeKindChanged,
+    // the template the names of a multi node's nodes have to match
+    KeyChanged,
+    PropertyAdded,
+    PropertyRemoved,
+    ValuesWidened,
+    ValuesNarrowed,
+    DefaultChanged,
+    // whether the property takes multiple values now
+    MultipleToggled(bool),
+    // whether the property is mandatory now
+    MandatoryToggled(bool),
+    // how many values a property or nodes a multi node may have
+    LimitsChanged,
+    TemplateChanged,
+}
+
+// a change at a node or property, given by its path with multi node children as
+// wildcards, or at a template, given by its name
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct SchemaChange {
+    pub path: Vec<String>,
+    pub change: Change,
+    pub compatibility: Compatibility,
+}
+
+#[derive(Debug, Default)]
+pub struct SchemaDiff {
+    pub changes: Vec<SchemaChange>,
+}
+
+impl SchemaDiff {
+    pub fn is_compatible(&self) -> bool {
+        self.breaking().next().is_none()
+    }
+
+    pub fn breaking(&self) -> impl Iterator<Item = &SchemaChange> {
+        self.changes
+            .iter()
+            .filter(|change| change.compatibility == Compatibility::Breaking)
+    }
+}
+
+impl Schema {
+    // how saves made with this schema fare with the newer one, taking the newer
+    // one's migrations into account
+    pub fn diff(&self, newer: &Schema) -> anyhow::Result<SchemaDiff> {
+        let mut differ = Differ {
+            newer,
+            steps: newer
+                .migrations_from(self.version())
+                .flat_map(|migration| migration.steps.iter())
+                .collect(),
+            changes: Vec::new(),
+        };
+
+        differ.templates(&self.templates, &newer.templates)?;
+        differ.nodes(&self.nodes, &newer.nodes, &[])?;
+
+        // templates first, then nodes and properties in the order of their paths
+        let mut changes = differ.changes;
+        changes.sort_by_key(|change| {
+            (
+                change.change != Change::TemplateChanged,
+                change.path.clone(),
+            )
+        });
+        Ok(SchemaDiff { changes })
+    }
+}
+
+struct Differ<'a> {
+    newer: &'a Schema,
+    // the migrations saves made with the older schema go through
+    steps: Vec<&'a MigrationStep>,
+    changes: Vec<SchemaChange>,
+}
+
+impl<'a> Differ<'a> {
+    fn push(&mut self, path: Vec<String>, change: Change, breaking: bool) {
+        self.changes.push(SchemaChange {
+            path,
+            change,
+            compatibility: if breaking {
+                Compatibility::Breaking
+            } else {
+                Compatibility::Compatible
+            },
+        });
+    }
+
+    // added and removed templates show up in what uses them
+    fn templates(
+        &mut self,
+        older: &HashMap<String, Arc<Template>>,
+        newer: &HashMap<String, Arc<Template>>,
+    ) -> anyhow::Result<()> {
+        for (name, old) in older {
+            let new = match newer.get(name) {
+                Some(new) => new,
+                None => continue,
+            };
+
+            if serde_json::to_value(old)? == serde_json::to_value(new)? {
+                continue;
+            }
+
+            // there's no telling what a changed regex or composite template matches
+            let widened = match (&**old, &**new) {
+                (Template::Range(old), Template::Range(new)) => new.contains(old),
+                _ => false,
+            };
+            self.push(vec![name.to_owned()], Change::TemplateChanged, !widened);
+        }
+
+        Ok(())
+    }
+
+    fn nodes(
+        &mut self,
+        older: &HashMap<String, Box<SchemaNode>>,
+        newer: &HashMap<String, Box<SchemaNode>>,
+        path: &[String],
+    ) -> anyhow::Result<()> {
+        for (name, old) in older {
+            let path = [path, &[name.to_owned()]].concat();
+            match newer.get(name) {
+                Some(new) => self.node(old, new, path)?,
+                None => {
+                    let migrated = self.migrated(&path, None);
+                    self.push(path, Change::NodeRemoved, !migrated);
+                }
+            }
+        }
+
+        for (name, new) in newer {
+            if !older.contains_key(name) {
+                self.push(
+                    [path, &[name.to_owned()]].concat(),
+                    Change::NodeAdded,
+                    requires_values(new),
+                );
+            }
+        }
+
+        Ok(())
+    }
+
+    fn node(
+        &mut self,
+        old: &SchemaNode,
+        new: &SchemaNode,
+        path: Vec<String>,
+    ) -> anyhow::Result<()> {
+        match (old, new) {
+            (SchemaNode::SingleSchemaNode(old), SchemaNode::SingleSchemaNode(new)) => {
+                self.properties(&old.properties, &new.properties, &path)?;
+                self.nodes(&old.subnodes, &new.subnodes, &path)
+            }
+            (SchemaNode::MultiSchemaNode(old), SchemaNode::MultiSchemaNode(new)) => {
+                match (&old.source.template, &new.source.template) {
+                    (Some(old), Some(new)) if old == new => (),
+                    (None, None) => (),
+                    // any name is fine now
+                    (Some(_), None) => self.push(path.clone(), Change::KeyChanged, false),
+                    _ => self.push(path.clone(), Change::KeyChanged, true),
+                }
+
+                self.limits((old.min, old.max), (new.min, new.max), path.clone());
+                self.node(
+                    &old.node,
+                    &new.node,
+                    [path, vec![WILDCARD.to_owned()]].concat(),
+                )
+            }
+            _ => {
+                let migrated = self.migrated(&path, None);
+                self.push(path, Change::NodeKindChanged, !migrated);
+                Ok(())
+            }
+        }
+    }
+
+    fn properties(
+        &mut self,
+        older: &HashMap<String, Property>,
+        newer: &HashMap<String, Property>,
+        path: &[String],
+    ) -> anyhow::Result<()> {
+        for (name, old) in older {
+            let property_path = [path, &[name.to_owned()]].concat();
+            match newer.get(name) {
+                Some(new) => self.property(old, new, path, name)?,
+                None => {
+                    let migrated = self.migrated(path, Some(name));
+                    self.push(property_path, Change::PropertyRemoved, !migrated);
+                }
+            }
+        }
+
+        for (name, new) in newer {
+            if !older.contains_key(name) {
+                self.push(
+                    [path, &[name.to_owned()]].concat(),
+                    Change::PropertyAdded,
+                    new.mandatory && new.default.is_empty(),
+                );
+            }
+        }
+
+        Ok(())
+    }
+
+    fn property(
+        &mut self,
+        old: &Property,
+        new: &Property,
+        path: &[String],
+        name: &str,
+    ) -> anyhow::Result<()> {
+        let property_path = [path, &[name.to_owned()]].concat();
+
+        let older: HashSet<&Value> = old.values.iter().map(|allowed| &allowed.value).collect();
+        let newer: HashSet<&Value> = new.values.iter().map(|allowed| &allowed.value).collect();
+        if older != newer {
+            let mut narrowed = false;
+            for value in older.difference(&newer) {
+                narrowed |= !self.covers(new, value)?;
+            }
+
+            if narrowed {
+                let transformed = self.transformed(path, name);
+                self.push(property_path.clone(), Change::ValuesNarrowed, !transformed);
+            } else {
+                self.push(property_path.clone(), Change::ValuesWidened, false);
+            }
+        }
+
+        // saves only have the values that were set, so they don't mind
+        if old.default != new.default {
+            self.push(property_path.clone(), Change::DefaultChanged, false);
+        }
+
+        if old.multiple != new.multiple {
+            self.push(
+                property_path.clone(),
+                Change::MultipleToggled(new.multiple),
+                !new.multiple,
+            );
+        }
+
+        if old.mandatory != new.mandatory {
+            self.push(
+                property_path.clone(),
+                Change::MandatoryToggled(new.mandatory),
+                new.mandatory && new.default.is_empty(),
+            );
+        }
+
+        self.limits((old.min, old.max), (new.min, new.max), property_path);
+        Ok(())
+    }
+
+    fn limits(
+        &mut self,
+        old: (Option<usize>, Option<usize>),
+        new: (Option<usize>, Option<usize>),
+        path: Vec<String>,
+    ) {
+        if old == new {
+            return;
+        }
+
+        let min = |limit: Option<usize>| limit.unwrap_or(0);
+        let max = |limit: Option<usize>| limit.unwrap_or(usize::MAX);
+        let narrowed = min(new.0) > min(old.0) || max(new.1) < max(old.1);
+        self.push(path, Change::LimitsChanged, narrowed);
+    }
+
+    // whether a value the property no longer lists is still accepted by the new
+    // property
+    fn covers(&self, new: &Property, value: &Value) -> anyhow::Result<bool> {
+        Ok(match value {
+            Value::Literal(literal) => new.allows(literal, self.newer)?,
+            Value::Range(range) => new.values.iter().any(|allowed| match &allowed.value {
+                Value::Range(new) => new.contains(range),
+                _ => false,
+            }),
+            Value::Template(_) | Value::Reference(_) => false,
+        })
+    }
+
+    // whether saves with the node or property are migrated to the newer schema,
+    // moving or dropping it along the way
+    fn migrated(&self, path: &[String], property: Option<&str>) -> bool {
+        self.steps.iter().any(|step| match step {
+            MigrationStep::RenameNode { path: from, .. }
+            | MigrationStep::MoveNode { path: from, .. }
+            | MigrationStep::DropNode { path: from } => path.starts_with(from),
+            MigrationStep::RenameProperty {
+                path: from,
+                property: name,
+                ..
+            }
+            | MigrationStep::MoveProperty {
+                path: from,
+                property: name,
+                ..
+            }
+            | MigrationStep::DropProperty {
+                path: from,
+                property: name,
+            } => from[..] == path[..] && property == Some(name),
+            MigrationStep::TransformProperty { .. } => false,
+        })
+    }
+
+    // values a property no longer accepts can be transformed into ones it does
+    fn transformed(&self, path: &[String], property: &str) -> bool {
+        self.steps.iter().any(|step| match step {
+            MigrationStep::TransformProperty {
+                path: from,
+                property: name,
+                ..
+            } => from[..] == path[..] && name == property,
+            _ => false,
+        })
+    }
+}
+
+// whether saves made before the node existed lack values it needs
+fn requires_values(node: &SchemaNode) -> bool {
+    match node {
+        SchemaNode::SingleSchemaNode(single) => {
+            single
+                .properties
+                .values()
+                .any(|property| property.mandatory && property.default.is_empty())
+                || single.subnodes.values().any(|node| requires_values(node))
+        }
+        SchemaNode::MultiSchemaNode(multi) => multi.min.unwrap_or(0) > 0,
+    }
+}
+
+impl fmt::Display for Compatibility {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Compatibility::Compatible => write!(f, "compatible"),
+            Compatibility::Breaking => write!(f, "breaking"),
+        }
+    }
+}
+
+impl fmt::Display for Change {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Change::NodeAdded => write!(f, "node added"),
+            Change::NodeRemoved => write!(f, "node removed"),
+            Change::NodeKindChanged => write!(f, "changed between a single and a multi node"),
+            Change::KeyChanged => write!(f, "node names match a different template"),
+            Change::PropertyAdded => write!(f, "property added"),
+            Change::PropertyRemoved => write!(f, "property removed"),
+            Change::ValuesWidened => write!(f, "accepts more values"),
+            Change::ValuesNarrowed => write!(f, "accepts fewer values"),
+            Change::DefaultChanged => write!(f, "default changed"),
+            Change::MultipleToggled(true) => write!(f, "takes multiple values"),
+            Change::MultipleToggled(false) => write!(f, "no longer takes multiple values"),
+            Change::MandatoryToggled(true) => write!(f, "mandatory"),
+            Change::MandatoryToggled(false) => write!(f, "no longer mandatory"),
+            Change::LimitsChanged => write!(f, "element count limits changed"),
+            Change::TemplateChanged => write!(f, "template changed"),
+        }
+    }
+}
+
+// e.g. "breaking: 'interfaces * mtu' accepts fewer values"
+impl fmt::Display for SchemaChange {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self.change {
+            Change::TemplateChanged => write!(
+                f,
+                "{}: template '{}' changed",
+                self.compatibility,
+                self.path.join(" ")
+            ),
+            _ => write!(
+                f,
+                "{}: '{}' {}",
+                self.compatibility,
+                self.path.join(" "),
+                self.change
+            ),
+        }
+    }
+}
+
+impl fmt::Display for SchemaDiff {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        for change in &self.changes {
+            writeln!(f, "{}", change)?;
+        }
+
+        write!(
+            f,
+            "{} change(s), {} breaking",
+            self.changes.len(),
+            self.breaking().count()
+        )
+    }
+}
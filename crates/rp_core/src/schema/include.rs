@@ -0,0 +1,66 @@
+use super::{Merge, MergingStrategy, Schema};
+use crate::error::IncludeError;
+use anyhow::Context;
+use std::{
+    collections::HashSet,
+    fs, mem,
+    path::{Path, PathBuf},
+};
+
+impl Schema {
+    // reads the schema in the file along with every schema it includes. included
+    // paths are relative to the file including them and their contents may not
+    // conflict with each other
+    pub fn from_yaml_path<P: AsRef<Path>>(path: P) -> anyhow::Result<Schema> {
+        let schema =
+            Schema::from_yaml_path_including(path.as_ref(), &mut Vec::new(), &mut HashSet::new())?;
+
+        // the first file can't have been loaded already
+        schema.ok_or_else(|| anyhow::anyhow!("schema '{}' not loaded", path.as_ref().display()))
+    }
+
+    fn from_yaml_path_including(
+        path: &Path,
+        stack: &mut Vec<PathBuf>,
+        loaded: &mut HashSet<PathBuf>,
+    ) -> anyhow::Result<Option<Schema>> {
+        let path = fs::canonicalize(path)
+            .with_context(|| format!("Failed to find schema '{}'", path.display()))?;
+
+        if stack.contains(&path) {
+            let cycle = stack
+                .iter()
+                .skip_while(|included| **included != path)
+                .chain(Some(&path))
+                .map(|included| included.display().to_string())
+                .collect();
+            return Err(IncludeError::Cycle(cycle).into());
+        }
+
+        // a schema included through several others is only merged in once
+        if !loaded.insert(path.clone()) {
+            return Ok(None);
+        }
+
+        let mut schema = Schema::from_yaml_str(&fs::read_to_string(&path)?, Some(path.clone()))
+            .with_context(|| format!("Failed to read schema '{}'", path.display()))?;
+
+        let directory = path.parent().unwrap_or_else(|| Path::new(""));
+        stack.push(path.clone());
+
+        for include in mem::take(&mut schema.include) {
+            if let Some(included) =
+                Schema::from_yaml_path_including(&directory.join(&include), stack, loaded)?
+            {
+                schema
+                    .merge(included, MergingStrategy::Error)
+                    .with_context(|| {
+                        format!("Failed to include '{}' in '{}'", include, path.display())
+                    })?;
+            }
+        }
+
+        stack.pop();
+        Ok(Some(schema))
+    }
+}
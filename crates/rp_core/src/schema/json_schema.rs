@@ -0,0 +1,374 @@
+use super::{
+    value::range::Range, AddressTemplate, AllowedValue, Bound, DefaultValue, HostnameTemplate,
+    IpFamily, LengthTemplate, MultiSchemaNode, PrefixTemplate, Property, Schema, SchemaNode,
+    SingleSchemaNode, Template, Value,
+};
+use serde_json::{json, Map};
+
+const DRAFT: &str = "http://json-schema.org/draft-07/schema#";
+
+// RFC 1123 labels, as checked by the hostname template
+const LABEL: &str = "[A-Za-z0-9]([A-Za-z0-9-]{0,61}[A-Za-z0-9])?";
+const IPV4_PREFIX: &str = "^[0-9]{1,3}(\\.[0-9]{1,3}){3}/[0-9]{1,2}$";
+const IPV6_PREFIX: &str = "^[0-9A-Fa-f:.]*:[0-9A-Fa-f:.]*/[0-9]{1,3}$";
+const MAC: &str = "^[0-9A-Fa-f]{2}((:[0-9A-Fa-f]{2}){5}|(-[0-9A-Fa-f]{2}){5})$";
+
+// the part of a JSON Schema document describing what something in the schema
+// accepts. it's only as strict as JSON Schema can be, e.g. conditions and
+// references to other nodes are left for the config to check
+pub trait JsonSchema {
+    fn json_schema(&self) -> serde_json::Value;
+}
+
+impl Schema {
+    // a JSON Schema for saved configs, so they can be checked without the schema
+    pub fn to_json_schema(&self) -> serde_json::Value {
+        let definitions: Map<String, serde_json::Value> = self
+            .templates
+            .iter()
+            .map(|(name, template)| {
+                let mut definition = template.json_schema();
+                annotate(&mut definition, "title", json!(template.to_string()));
+                (name.to_owned(), definition)
+            })
+            .collect();
+
+        let nodes: Map<String, serde_json::Value> = self
+            .nodes
+            .iter()
+            .map(|(name, node)| (name.to_owned(), node.json_schema()))
+            .collect();
+
+        json!({
+            "$schema": DRAFT,
+            "type": "object",
+            "properties": {
+                "timestamp": { "type": "string", "format": "date-time" },
+                "version": { "type": "integer", "maximum": self.version() },
+                "nodes": closed(nodes, Vec::new()),
+            },
+            "required": ["nodes"],
+            "definitions": definitions,
+        })
+    }
+}
+
+impl JsonSchema for SchemaNode {
+    fn json_schema(&self) -> serde_json::Value {
+        match self {
+            SchemaNode::SingleSchemaNode(single) => single.json_schema(),
+            SchemaNode::MultiSchemaNode(multi) => multi.json_schema(),
+        }
+    }
+}
+
+impl JsonSchema for SingleSchemaNode {
+    fn json_schema(&self) -> serde_json::Value {
+        let subnodes = self
+            .subnodes
+            .iter()
+            .map(|(name, node)| (name.to_owned(), node.json_schema()))
+            .collect();
+
+        let properties = self
+            .properties
+            .iter()
+            .map(|(name, property)| (name.to_owned(), property.json_schema()))
+            .collect();
+
+        let mut mandatory: Vec<&String> = self
+            .properties
+            .iter()
+            .filter(|(_, property)| property.mandatory)
+            .map(|(name, _)| name)
+            .collect();
+        mandatory.sort();
+
+        let mut schema = node(closed(subnodes, Vec::new()), closed(properties, mandatory));
+        describe(&mut schema, &self.description);
+        schema
+    }
+}
+
+// saved as a map from the nodes' names to the nodes
+impl JsonSchema for MultiSchemaNode {
+    fn json_schema(&self) -> serde_json::Value {
+        let names = match &self.source.template {
+            Some(template) => json!({ "$ref": definition(template) }),
+            None => json!({ "type": "string" }),
+        };
+
+        let mut subnodes = json!({
+            "type": "object",
+            "propertyNames": names,
+            "additionalProperties": self.node.json_schema(),
+        });
+        annotate(&mut subnodes, "minProperties", json!(self.min));
+        annotate(&mut subnodes, "maxProperties", json!(self.max));
+        if self.ordered {
+            annotate(&mut subnodes, "x-ordered", json!(true));
+        }
+
+        // the nodes' properties are in the nodes themselves
+        let properties = json!({ "type": "object", "maxProperties": 0 });
+
+        let mut schema = node(subnodes, properties);
+        describe(&mut schema, &self.description);
+        schema
+    }
+}
+
+// saved as the list of the property's values
+impl JsonSchema for Property {
+    fn json_schema(&self) -> serde_json::Value {
+        let mut values: Vec<serde_json::Value> =
+            self.values.iter().map(JsonSchema::json_schema).collect();
+        // the values are a set, the schema shouldn't change between runs
+        values.sort_by_key(|value| value.to_string());
+
+        let items = if values.len() == 1 {
+            values.remove(0)
+        } else {
+            json!({ "anyOf": values })
+        };
+
+        let max = if self.multiple { self.max } else { Some(1) };
+
+        let mut defaults: Vec<&String> = self
+            .default
+            .iter()
+            .filter_map(|default| match default {
+                DefaultValue::Literal(literal) => Some(literal),
+                DefaultValue::Command(_) => None,
+            })
+            .collect();
+        defaults.sort();
+
+        let mut schema = json!({
+            "type": "array",
+            "items": items,
+            "uniqueItems": true,
+            "x-deletable": self.deletable,
+        });
+        describe(&mut schema, &self.description);
+        annotate(&mut schema, "minItems", json!(self.min));
+        annotate(&mut schema, "maxItems", json!(max));
+        if !defaults.is_empty() {
+            annotate(&mut schema, "default", json!(defaults));
+        }
+        if self.ordered {
+            annotate(&mut schema, "x-ordered", json!(true));
+        }
+        if self.secret {
+            annotate(&mut schema, "writeOnly", json!(true));
+        }
+        schema
+    }
+}
+
+impl JsonSchema for AllowedValue {
+    fn json_schema(&self) -> serde_json::Value {
+        let mut schema = self.value.json_schema();
+
+        // anything next to a $ref is ignored, so it has to be wrapped to keep the
+        // description
+        if self.description.is_some() && schema.get("$ref").is_some() {
+            schema = json!({ "allOf": [schema] });
+        }
+
+        describe(&mut schema, &self.description);
+        schema
+    }
+}
+
+impl JsonSchema for Value {
+    fn json_schema(&self) -> serde_json::Value {
+        match self {
+            Value::Literal(literal) => json!({ "const": literal }),
+            Value::Template(template) => json!({ "$ref": definition(template) }),
+            Value::Range(range) => range.json_schema(),
+            Value::Reference(path) => json!({
+                "type": "string",
+                "x-reference": path,
+            }),
+        }
+    }
+}
+
+impl JsonSchema for Template {
+    fn json_schema(&self) -> serde_json::Value {
+        match self {
+            // the regex matches anywhere in the value, as JSON Schema patterns do
+            Template::Regex(regex) => json!({ "type": "string", "pattern": regex.regex }),
+            Template::Range(range) => range.json_schema(),
+            Template::Address(address) => address.json_schema(),
+            Template::Prefix(prefix) => prefix.json_schema(),
+            Template::Mac(_) => json!({ "type": "string", "pattern": MAC }),
+            Template::Hostname(hostname) => hostname.json_schema(),
+            Template::Length(length) => length.json_schema(),
+            // any value is hashed when set, hashes included
+            Template::Password(_) => json!({
+                "type": "string",
+                "minLength": 1,
+                "writeOnly": true,
+            }),
+            Template::Reference(reference) => json!({ "$ref": definition(&reference.name) }),
+            Template::Any(templates) => json!({ "anyOf": schemas(templates) }),
+            Template::All(templates) => json!({ "allOf": schemas(templates) }),
+            Template::Not(template) => json!({ "not": template.json_schema() }),
+        }
+    }
+}
+
+// values are strings in a save, so the bounds can't be checked by JSON Schema and
+// are only given as an annotation
+impl JsonSchema for Range {
+    fn json_schema(&self) -> serde_json::Value {
+        let mut range = json!({});
+        match self.lower {
+            Bound::Inclusive(v) => annotate(&mut range, "minimum", number(v.into_inner())),
+            Bound::Exclusive(v) => annotate(&mut range, "exclusiveMinimum", number(v.into_inner())),
+        }
+        match self.upper {
+            Bound::Inclusive(v) => annotate(&mut range, "maximum", number(v.into_inner())),
+            Bound::Exclusive(v) => annotate(&mut range, "exclusiveMaximum", number(v.into_inner())),
+        }
+        if let Some(step) = self.step {
+            annotate(&mut range, "multipleOf", number(step.into_inner()));
+        }
+        if self.integer {
+            annotate(&mut range, "type", json!("integer"));
+        }
+        if let Some(unit) = self.unit {
+            annotate(&mut range, "unit", json!(unit));
+        }
+
+        json!({
+            "type": "string",
+            "pattern": range_pattern(self),
+            "x-range": range,
+        })
+    }
+}
+
+impl JsonSchema for AddressTemplate {
+    fn json_schema(&self) -> serde_json::Value {
+        match self.family {
+            IpFamily::Ipv4 => json!({ "type": "string", "format": "ipv4" }),
+            IpFamily::Ipv6 => json!({ "type": "string", "format": "ipv6" }),
+            IpFamily::Any => json!({
+                "type": "string",
+                "anyOf": [{ "format": "ipv4" }, { "format": "ipv6" }],
+            }),
+        }
+    }
+}
+
+// only the syntax, the address part isn't checked as closely as the template does
+impl JsonSchema for PrefixTemplate {
+    fn json_schema(&self) -> serde_json::Value {
+        match self.family {
+            IpFamily::Ipv4 => json!({ "type": "string", "pattern": IPV4_PREFIX }),
+            IpFamily::Ipv6 => json!({ "type": "string", "pattern": IPV6_PREFIX }),
+            IpFamily::Any => json!({
+                "type": "string",
+                "anyOf": [{ "pattern": IPV4_PREFIX }, { "pattern": IPV6_PREFIX }],
+            }),
+        }
+    }
+}
+
+impl JsonSchema for HostnameTemplate {
+    fn json_schema(&self) -> serde_json::Value {
+        if self.fqdn {
+            json!({
+                "type": "string",
+                "pattern": format!("^{}(\\.{})*\\.?$", LABEL, LABEL),
+                "maxLength": 254,
+            })
+        } else {
+            json!({ "type": "string", "pattern": format!("^{}$", LABEL) })
+        }
+    }
+}
+
+impl JsonSchema for LengthTemplate {
+    fn json_schema(&self) -> serde_json::Value {
+        let mut schema = json!({ "type": "string" });
+        annotate(&mut schema, "minLength", json!(self.min));
+        annotate(&mut schema, "maxLength", json!(self.max));
+        schema
+    }
+}
+
+// the numbers a range parses, see Range::parse
+fn range_pattern(range: &Range) -> String {
+    let number = if range.unit.is_some() {
+        "[+-]?[0-9]+(\\.[0-9]+)?"
+    } else if range.integer {
+        "[+-]?[0-9]+"
+    } else {
+        "[+-]?([0-9]+(\\.[0-9]*)?|\\.[0-9]+)([eE][+-]?[0-9]+)?"
+    };
+
+    match range.unit {
+        Some(unit) => {
+            let suffixes: Vec<&str> = unit.suffixes().iter().map(|(name, _)| *name).collect();
+            format!("^{}({})?$", number, suffixes.join("|"))
+        }
+        None => format!("^{}$", number),
+    }
+}
+
+fn schemas(templates: &[Template]) -> Vec<serde_json::Value> {
+    templates.iter().map(JsonSchema::json_schema).collect()
+}
+
+fn definition(template: &str) -> String {
+    format!("#/definitions/{}", template)
+}
+
+// nodes are saved with both their subnodes and properties, even if empty
+fn node(subnodes: serde_json::Value, properties: serde_json::Value) -> serde_json::Value {
+    json!({
+        "type": "object",
+        "properties": { "subnodes": subnodes, "properties": properties },
+        "required": ["subnodes", "properties"],
+        "additionalProperties": false,
+    })
+}
+
+// an object with only the given properties, as nodes in a save are
+fn closed(properties: Map<String, serde_json::Value>, required: Vec<&String>) -> serde_json::Value {
+    let mut schema = json!({
+        "type": "object",
+        "properties": properties,
+        "additionalProperties": false,
+    });
+    if !required.is_empty() {
+        annotate(&mut schema, "required", json!(required));
+    }
+    schema
+}
+
+// whole numbers without a fraction, so integer bounds read as integers
+fn number(value: f64) -> serde_json::Value {
+    if value.fract() == 0.0 && value.abs() < 2f64.powi(53) {
+        json!(value as i64)
+    } else {
+        json!(value)
+    }
+}
+
+fn describe(schema: &mut serde_json::Value, description: &Option<String>) {
+    if let Some(description) = description {
+        annotate(schema, "description", json!(description));
+    }
+}
+
+// nulls are left out, so optional values can be given as is
+fn annotate(schema: &mut serde_json::Value, key: &str, value: serde_json::Value) {
+    if let (Some(object), false) = (schema.as_object_mut(), value.is_null()) {
+        object.insert(key.to_owned(), value);
+    }
+}
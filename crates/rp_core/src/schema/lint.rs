@@ -0,0 +1,297 @@
+use super::{
+    Condition, DefaultValue, Matches, Property, Schema, SchemaItem, SchemaNode, Template,
+    ValidationReport, Value, WILDCARD,
+};
+use crate::error::{SchemaError, SchemaWarning};
+use std::{collections::HashSet, sync::Arc};
+
+impl Schema {
+    // things validation lets through but that are most likely mistakes. the schema
+    // has to have been validated first
+    pub fn lint(&self) -> anyhow::Result<Vec<SchemaError>> {
+        let mut report = ValidationReport::default();
+
+        self.lint_unused_templates(&mut report);
+        for (name, template) in &self.templates {
+            lint_template(
+                template,
+                &SchemaItem::Template(name.to_owned()),
+                &mut report,
+            )?;
+        }
+
+        for (name, node) in &self.nodes {
+            self.lint_node(node, &[name.to_owned()], &mut report)?;
+        }
+
+        Ok(report.into_errors(self))
+    }
+
+    // templates only used by unused templates are unused too
+    fn lint_unused_templates(&self, report: &mut ValidationReport) {
+        let mut used = HashSet::new();
+        let mut pending: Vec<&str> = Vec::new();
+        for node in self.nodes.values() {
+            node_templates(node, &mut pending);
+        }
+
+        while let Some(name) = pending.pop() {
+            if used.insert(name) {
+                if let Some(template) = self.templates.get(name) {
+                    pending.extend(template.references());
+                }
+            }
+        }
+
+        for name in self.templates.keys() {
+            if !used.contains(name.as_str()) {
+                report.push(
+                    SchemaItem::Template(name.to_owned()),
+                    SchemaWarning::UnusedTemplate(name.to_owned()).into(),
+                );
+            }
+        }
+    }
+
+    fn lint_node(
+        &self,
+        node: &SchemaNode,
+        path: &[String],
+        report: &mut ValidationReport,
+    ) -> anyhow::Result<()> {
+        match node {
+            SchemaNode::SingleSchemaNode(single) => {
+                for (name, property) in &single.properties {
+                    let item = SchemaItem::Property(Arc::clone(&single.locator), name.to_owned());
+                    self.lint_values(property, &item, report)?;
+                    self.lint_defaults(property, path, &item, report);
+                }
+
+                for (name, subnode) in &single.subnodes {
+                    self.lint_node(subnode, &[path, &[name.to_owned()]].concat(), report)?;
+                }
+            }
+            SchemaNode::MultiSchemaNode(multi) => {
+                self.lint_node(
+                    &multi.node,
+                    &[path, &[WILDCARD.to_owned()]].concat(),
+                    report,
+                )?;
+            }
+        }
+
+        Ok(())
+    }
+
+    fn lint_values(
+        &self,
+        property: &Property,
+        item: &SchemaItem,
+        report: &mut ValidationReport,
+    ) -> anyhow::Result<()> {
+        for allowed in &property.values {
+            let literal = match &allowed.value {
+                Value::Range(range) if range.is_empty() => {
+                    report.push(
+                        item.clone(),
+                        SchemaWarning::EmptyRange(range.to_string()).into(),
+                    );
+                    continue;
+                }
+                // a literal with a description or exclusions of its own means
+                // something the template doesn't
+                Value::Literal(literal)
+                    if allowed.description.is_none() && allowed.excludes.is_empty() =>
+                {
+                    literal
+                }
+                _ => continue,
+            };
+
+            for other in &property.values {
+                let matches = match &other.value {
+                    Value::Template(name) => match self.templates.get(name) {
+                        Some(template) => template.matches(literal)?,
+                        None => false,
+                    },
+                    Value::Range(range) => range.matches(literal)?,
+                    Value::Literal(_) | Value::Reference(_) => false,
+                };
+
+                if matches {
+                    report.push(
+                        item.clone(),
+                        SchemaWarning::RedundantLiteral {
+                            literal: literal.to_owned(),
+                            by: other.value.to_string(),
+                        }
+                        .into(),
+                    );
+                    break;
+                }
+            }
+        }
+
+        Ok(())
+    }
+
+    // the conditions are checked against the defaults of the properties they're
+    // about, which is what a fresh config has
+    fn lint_defaults(
+        &self,
+        property: &Property,
+        path: &[String],
+        item: &SchemaItem,
+        report: &mut ValidationReport,
+    ) {
+        let defaults = match literal_defaults(property) {
+            Some(defaults) if !defaults.is_empty() => defaults,
+            _ => return,
+        };
+
+        // the defaults are ignored if they don't
+        if property
+            .when
+            .iter()
+            .any(|condition| self.holds_by_default(condition, path) != Some(true))
+        {
+            return;
+        }
+
+        let mut broken: Vec<&Condition> = property
+            .must
+            .iter()
+            .filter(|condition| self.holds_by_default(condition, path) == Some(false))
+            .collect();
+
+        let excludes = property.excludes.iter().chain(
+            property
+                .values
+                .iter()
+                .filter(|allowed| {
+                    defaults
+                        .iter()
+                        .any(|default| allowed_by(&allowed.value, default, self))
+                })
+                .flat_map(|allowed| allowed.excludes.iter()),
+        );
+        broken.extend(
+            excludes.filter(|condition| self.holds_by_default(condition, path) == Some(true)),
+        );
+
+        for condition in broken {
+            report.push(
+                item.clone(),
+                SchemaWarning::UnsatisfiableDefault {
+                    default: defaults.join(", "),
+                    condition: condition.describe(path),
+                }
+                .into(),
+            );
+        }
+    }
+
+    // none if the property's defaults aren't known before a config is made
+    fn holds_by_default(&self, condition: &Condition, base: &[String]) -> Option<bool> {
+        let path = condition.resolve(base)?;
+        let (name, node) = path.split_last()?;
+        let property = match self.find_node_at(node)? {
+            SchemaNode::SingleSchemaNode(single) => single.properties.get(name)?,
+            SchemaNode::MultiSchemaNode(_) => return None,
+        };
+
+        // whether its own defaults apply is another question
+        if !property.when.is_empty() {
+            return None;
+        }
+
+        let defaults: Vec<String> = literal_defaults(property)?
+            .into_iter()
+            .map(str::to_owned)
+            .collect();
+        Some(condition.holds(&defaults))
+    }
+}
+
+// regexes and ranges anywhere in the template, referenced templates are linted on
+// their own
+fn lint_template(
+    template: &Template,
+    item: &SchemaItem,
+    report: &mut ValidationReport,
+) -> anyhow::Result<()> {
+    match template {
+        Template::Regex(regex) => {
+            if template.matches("")? {
+                report.push(
+                    item.clone(),
+                    SchemaWarning::EmptyMatch(regex.regex.to_owned()).into(),
+                );
+            }
+        }
+        Template::Range(range) if range.is_empty() => {
+            report.push(
+                item.clone(),
+                SchemaWarning::EmptyRange(range.to_string()).into(),
+            );
+        }
+        Template::Any(templates) | Template::All(templates) => {
+            for template in templates {
+                lint_template(template, item, report)?;
+            }
+        }
+        Template::Not(template) => lint_template(template, item, report)?,
+        _ => (),
+    }
+
+    Ok(())
+}
+
+fn node_templates<'a>(node: &'a SchemaNode, templates: &mut Vec<&'a str>) {
+    match node {
+        SchemaNode::SingleSchemaNode(single) => {
+            for property in single.properties.values() {
+                for allowed in &property.values {
+                    if let Value::Template(name) = &allowed.value {
+                        templates.push(name);
+                    }
+                }
+            }
+
+            for subnode in single.subnodes.values() {
+                node_templates(subnode, templates);
+            }
+        }
+        SchemaNode::MultiSchemaNode(multi) => {
+            if let Some(name) = &multi.source.template {
+                templates.push(name);
+            }
+
+            node_templates(&multi.node, templates);
+        }
+    }
+}
+
+// none if any of them come from a query
+fn literal_defaults(property: &Property) -> Option<Vec<&str>> {
+    property
+        .default
+        .iter()
+        .map(|default| match default {
+            DefaultValue::Literal(literal) => Some(literal.as_str()),
+            DefaultValue::Command(_) => None,
+        })
+        .collect()
+}
+
+fn allowed_by(value: &Value, literal: &str, schema: &Schema) -> bool {
+    match value {
+        Value::Literal(allowed) => allowed == literal,
+        Value::Template(name) => schema
+            .templates
+            .get(name)
+            .map_or(false, |template| template.matches(literal).unwrap_or(false)),
+        Value::Range(range) => range.matches(literal).unwrap_or(false),
+        Value::Reference(_) => true,
+    }
+}
@@ -0,0 +1,319 @@
+use super::{NodeLocator, Schema, SchemaNode};
+use crate::error::SchemaError;
+use std::{collections::HashMap, fmt, path::PathBuf, sync::Arc};
+use yaml_rust::{
+    parser::{Event, MarkedEventReceiver, Parser},
+    scanner::Marker,
+};
+
+// where in the schema an error was found
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct SchemaLocation {
+    // from the top-level node or section down to the item, e.g.
+    // ["interfaces", "ethernet", "template", "address"]
+    pub path: Vec<String>,
+    pub file: Option<PathBuf>,
+    // both count from one, only known for schemas read from YAML
+    pub line: Option<usize>,
+    pub column: Option<usize>,
+}
+
+impl fmt::Display for SchemaLocation {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "'{}'", self.path.join(" "))?;
+
+        if let Some(file) = &self.file {
+            write!(f, " in '{}'", file.display())?;
+        }
+
+        if let (Some(line), Some(column)) = (self.line, self.column) {
+            write!(f, " at {}:{}", line, column)?;
+        }
+
+        Ok(())
+    }
+}
+
+// the parts of a schema errors are reported for
+#[derive(Debug, Clone)]
+pub enum SchemaItem {
+    Include(String),
+    Template(String),
+    Node(Arc<NodeLocator>),
+    Property(Arc<NodeLocator>, String),
+    Migration(u32),
+}
+
+// a YAML document a schema was read from, along with the position of every key
+// and sequence element in it
+#[derive(Debug, Default)]
+pub(super) struct Document {
+    path: Option<PathBuf>,
+    positions: HashMap<Vec<String>, (usize, usize)>,
+}
+
+impl Document {
+    pub(super) fn scan(text: &str, path: Option<PathBuf>) -> Document {
+        let mut scanner = PositionScanner::default();
+
+        // the document was already parsed successfully, if the scanner disagrees
+        // errors are reported without positions
+        if Parser::new(text.chars()).load(&mut scanner, false).is_err() {
+            scanner.positions.clear();
+        }
+
+        Document {
+            path,
+            positions: scanner.positions,
+        }
+    }
+
+    fn locate(&self, path: Vec<String>, keys: &[String]) -> SchemaLocation {
+        let position = self.positions.get(keys);
+
+        SchemaLocation {
+            path,
+            file: self.path.clone(),
+            line: position.map(|(line, _)| *line),
+            column: position.map(|(_, column)| *column),
+        }
+    }
+}
+
+#[derive(Debug)]
+enum Container {
+    // the key of the value being read, if any
+    Mapping(Option<String>),
+    // the index of the next element
+    Sequence(usize),
+}
+
+#[derive(Debug, Default)]
+struct PositionScanner {
+    containers: Vec<Container>,
+    keys: Vec<String>,
+    positions: HashMap<Vec<String>, (usize, usize)>,
+}
+
+impl PositionScanner {
+    fn record(&mut self, key: String, mark: Marker) {
+        let keys = [&self.keys[..], &[key]].concat();
+        self.positions.insert(keys, (mark.line(), mark.col() + 1));
+    }
+
+    // the key a value starting at the mark is under, none at the document root
+    fn begin_value(&mut self, mark: Marker) -> Option<String> {
+        match self.containers.last()? {
+            Container::Mapping(key) => key.clone(),
+            Container::Sequence(index) => {
+                let key = index.to_string();
+                self.record(key.clone(), mark);
+                Some(key)
+            }
+        }
+    }
+
+    fn end_value(&mut self) {
+        match self.containers.last_mut() {
+            Some(Container::Mapping(key)) => *key = None,
+            Some(Container::Sequence(index)) => *index += 1,
+            None => (),
+        }
+    }
+}
+
+impl MarkedEventReceiver for PositionScanner {
+    fn on_event(&mut self, event: Event, mark: Marker) {
+        match event {
+            Event::Scalar(value, ..) => {
+                if let Some(Container::Mapping(key @ None)) = self.containers.last_mut() {
+                    *key = Some(value.clone());
+                    self.record(value, mark);
+                } else {
+                    self.begin_value(mark);
+                    self.end_value();
+                }
+            }
+            Event::Alias(_) => {
+                self.begin_value(mark);
+                self.end_value();
+            }
+            Event::MappingStart(_) | Event::SequenceStart(_) => {
+                if let Some(key) = self.begin_value(mark) {
+                    self.keys.push(key);
+                }
+
+                self.containers.push(match event {
+                    Event::MappingStart(_) => Container::Mapping(None),
+                    _ => Container::Sequence(0),
+                });
+            }
+            Event::MappingEnd | Event::SequenceEnd => {
+                self.containers.pop();
+
+                if !self.containers.is_empty() {
+                    self.keys.pop();
+                    self.end_value();
+                }
+            }
+            _ => (),
+        }
+    }
+}
+
+// the document each template, top-level node and migration was defined in, so
+// problems with them can be pointed at the right file and line
+#[derive(Debug, Default)]
+pub(super) struct Origins {
+    root: Arc<Document>,
+    templates: HashMap<String, Arc<Document>>,
+    nodes: HashMap<String, Arc<Document>>,
+    // along with the migration's index in its document
+    migrations: HashMap<u32, (Arc<Document>, usize)>,
+}
+
+impl Origins {
+    pub(super) fn record(schema: &Schema, document: Document) -> Origins {
+        let document = Arc::new(document);
+
+        Origins {
+            root: Arc::clone(&document),
+            templates: schema
+                .templates
+                .keys()
+                .map(|name| (name.to_owned(), Arc::clone(&document)))
+                .collect(),
+            nodes: schema
+                .nodes
+                .keys()
+                .map(|name| (name.to_owned(), Arc::clone(&document)))
+                .collect(),
+            migrations: schema
+                .migrations
+                .iter()
+                .enumerate()
+                .map(|(index, migration)| (migration.version, (Arc::clone(&document), index)))
+                .collect(),
+        }
+    }
+
+    // an item defined in several documents is attributed to the first one
+    pub(super) fn merge(&mut self, other: Origins) {
+        for (name, document) in other.templates {
+            self.templates.entry(name).or_insert(document);
+        }
+
+        for (name, document) in other.nodes {
+            self.nodes.entry(name).or_insert(document);
+        }
+
+        for (version, origin) in other.migrations {
+            self.migrations.entry(version).or_insert(origin);
+        }
+    }
+}
+
+impl Schema {
+    pub(super) fn locate(&self, item: &SchemaItem) -> SchemaLocation {
+        let keys =
+            |keys: &[&str]| -> Vec<String> { keys.iter().map(|key| (*key).to_owned()).collect() };
+
+        match item {
+            SchemaItem::Include(include) => self.origins.root.locate(
+                vec![String::from("include"), include.to_owned()],
+                &keys(&["include"]),
+            ),
+            SchemaItem::Template(name) => self
+                .origins
+                .templates
+                .get(name)
+                .unwrap_or(&self.origins.root)
+                .locate(
+                    vec![String::from("templates"), name.to_owned()],
+                    &keys(&["templates", name]),
+                ),
+            SchemaItem::Node(locator) => self.locate_in_node(locator, None),
+            SchemaItem::Property(locator, name) => self.locate_in_node(locator, Some(name)),
+            SchemaItem::Migration(version) => {
+                let path = vec![String::from("migrations"), version.to_string()];
+                match self.origins.migrations.get(version) {
+                    Some((document, index)) => {
+                        document.locate(path, &keys(&["migrations", &index.to_string()]))
+                    }
+                    None => self.origins.root.locate(path, &[]),
+                }
+            }
+        }
+    }
+
+    fn locate_in_node(&self, locator: &NodeLocator, property: Option<&String>) -> SchemaLocation {
+        let mut path = locator.path();
+        let mut keys = self.document_keys(&path);
+        let document = path
+            .first()
+            .and_then(|name| self.origins.nodes.get(name))
+            .unwrap_or(&self.origins.root);
+
+        if let Some(property) = property {
+            path.push(property.to_owned());
+            keys.extend(vec![String::from("properties"), property.to_owned()]);
+        }
+
+        document.locate(path, &keys)
+    }
+
+    // the keys leading to a node in the schema document, given the names in its
+    // locator
+    fn document_keys(&self, names: &[String]) -> Vec<String> {
+        let mut keys = Vec::new();
+        let mut current: Option<&SchemaNode> = None;
+
+        for name in names {
+            current = match current {
+                None => {
+                    keys.extend(vec![String::from("nodes"), name.to_owned()]);
+                    self.nodes.get(name)
+                }
+                Some(SchemaNode::SingleSchemaNode(single)) => {
+                    keys.extend(vec![String::from("subnodes"), name.to_owned()]);
+                    single.subnodes.get(name)
+                }
+                Some(SchemaNode::MultiSchemaNode(multi)) => {
+                    keys.push(String::from("node"));
+                    Some(&multi.node)
+                }
+            }
+            .map(|node| &**node);
+        }
+
+        keys
+    }
+}
+
+// errors found while validating the schema, each with the item it was found in
+#[derive(Debug, Default)]
+pub struct ValidationReport {
+    errors: Vec<(SchemaItem, anyhow::Error)>,
+}
+
+impl ValidationReport {
+    pub fn check(&mut self, item: SchemaItem, result: anyhow::Result<()>) {
+        if let Err(error) = result {
+            self.push(item, error);
+        }
+    }
+
+    pub fn push(&mut self, item: SchemaItem, error: anyhow::Error) {
+        self.errors.push((item, error));
+    }
+
+    pub(super) fn into_errors(self, schema: &Schema) -> Vec<SchemaError> {
+        self.errors
+            .into_iter()
+            .map(|(item, error)| SchemaError {
+                location: schema.locate(&item),
+                error,
+            })
+            .collect()
+    }
+}
@@ -0,0 +1,239 @@
+use super::{DefaultValue, Matches, Property, Schema, SchemaNode, WILDCARD};
+use crate::error::PathError;
+use anyhow::anyhow;
+use std::fmt;
+
+// what a path in the schema leads to, the root being the top-level nodes
+#[derive(Debug)]
+pub enum PathTarget<'a> {
+    Root,
+    Node(&'a SchemaNode),
+    Property(&'a Property),
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, PartialOrd, Ord)]
+pub enum PathElement {
+    Node(String),
+    Property(String),
+    // any key matching the template if there is one, and whatever the system has
+    // if the node's sourced
+    Key {
+        template: Option<String>,
+        sourced: bool,
+    },
+}
+
+#[derive(Debug)]
+pub struct SchemaPath<'a> {
+    // the path as given, and as in the schema with wildcards for the keys of
+    // multi nodes like in migrations and conditions
+    pub path: Vec<String>,
+    pub schema_path: Vec<String>,
+    pub target: PathTarget<'a>,
+    pub next: Vec<PathElement>,
+}
+
+impl<'a> SchemaPath<'a> {
+    pub fn node(&self) -> Option<&'a SchemaNode> {
+        match self.target {
+            PathTarget::Node(node) => Some(node),
+            _ => None,
+        }
+    }
+
+    pub fn property(&self) -> Option<&'a Property> {
+        match self.target {
+            PathTarget::Property(property) => Some(property),
+            _ => None,
+        }
+    }
+}
+
+impl Schema {
+    // a path of whitespace separated names as typed in the shell, e.g.
+    // "interfaces ethernet eth0 mtu"
+    pub fn lookup(&self, path: &str) -> anyhow::Result<SchemaPath<'_>> {
+        let path: Vec<String> = path.split_whitespace().map(str::to_owned).collect();
+        self.lookup_at(&path)
+    }
+
+    // keys of multi nodes have to match the node's template, unless the node is
+    // sourced since the names the system has aren't known without a config
+    pub fn lookup_at(&self, path: &[String]) -> anyhow::Result<SchemaPath<'_>> {
+        let mut schema_path = Vec::new();
+        let mut target = PathTarget::Root;
+
+        for (depth, element) in path.iter().enumerate() {
+            let unknown = || PathError::Unknown {
+                element: element.to_owned(),
+                path: path[..depth].join(" "),
+            };
+
+            let (next, name) = match target {
+                PathTarget::Root => (
+                    PathTarget::Node(&**self.nodes.get(element).ok_or_else(unknown)?),
+                    element.to_owned(),
+                ),
+                PathTarget::Node(SchemaNode::SingleSchemaNode(single)) => {
+                    match single.subnodes.get(element) {
+                        Some(subnode) => (PathTarget::Node(subnode), element.to_owned()),
+                        None => (
+                            PathTarget::Property(
+                                single.properties.get(element).ok_or_else(unknown)?,
+                            ),
+                            element.to_owned(),
+                        ),
+                    }
+                }
+                PathTarget::Node(SchemaNode::MultiSchemaNode(multi)) => {
+                    if let Some(name) = &multi.source.template {
+                        let template = self
+                            .templates
+                            .get(name)
+                            .ok_or_else(|| anyhow!("Template '{}' not found", name))?;
+
+                        if multi.source.command.is_none() && !template.matches(element)? {
+                            return Err(PathError::KeyMismatch {
+                                key: element.to_owned(),
+                                template: name.to_owned(),
+                                path: path[..depth].join(" "),
+                            }
+                            .into());
+                        }
+                    }
+
+                    (PathTarget::Node(&multi.node), WILDCARD.to_owned())
+                }
+                PathTarget::Property(_) => {
+                    return Err(PathError::PastProperty {
+                        element: element.to_owned(),
+                        path: path[..depth].join(" "),
+                    }
+                    .into())
+                }
+            };
+
+            target = next;
+            schema_path.push(name);
+        }
+
+        let next = self.next_elements(&target);
+        Ok(SchemaPath {
+            path: path.to_vec(),
+            schema_path,
+            target,
+            next,
+        })
+    }
+
+    fn next_elements(&self, target: &PathTarget) -> Vec<PathElement> {
+        let mut next: Vec<PathElement> = match target {
+            PathTarget::Root => self
+                .nodes
+                .keys()
+                .map(|name| PathElement::Node(name.to_owned()))
+                .collect(),
+            PathTarget::Node(SchemaNode::SingleSchemaNode(single)) => single
+                .subnodes
+                .keys()
+                .map(|name| PathElement::Node(name.to_owned()))
+                .chain(
+                    single
+                        .properties
+                        .keys()
+                        .map(|name| PathElement::Property(name.to_owned())),
+                )
+                .collect(),
+            PathTarget::Node(SchemaNode::MultiSchemaNode(multi)) => vec![PathElement::Key {
+                template: multi.source.template.clone(),
+                sourced: multi.source.command.is_some(),
+            }],
+            PathTarget::Property(_) => Vec::new(),
+        };
+
+        next.sort();
+        next
+    }
+}
+
+impl fmt::Display for PathElement {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            PathElement::Node(name) | PathElement::Property(name) => write!(f, "{}", name),
+            PathElement::Key {
+                template: Some(template),
+                sourced,
+            } => write!(
+                f,
+                "<{}>{}",
+                template,
+                if *sourced { " (or sourced)" } else { "" }
+            ),
+            PathElement::Key { template: None, .. } => write!(f, "<sourced>"),
+        }
+    }
+}
+
+// a summary for the shell, the rest is in the schema itself
+impl fmt::Display for SchemaPath<'_> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let description = match self.target {
+            PathTarget::Root => {
+                writeln!(f, "schema")?;
+                None
+            }
+            PathTarget::Node(SchemaNode::SingleSchemaNode(single)) => {
+                writeln!(f, "node '{}'", self.schema_path.join(" "))?;
+                single.description.as_ref()
+            }
+            PathTarget::Node(SchemaNode::MultiSchemaNode(multi)) => {
+                writeln!(f, "multi node '{}'", self.schema_path.join(" "))?;
+                multi.description.as_ref()
+            }
+            PathTarget::Property(property) => {
+                writeln!(f, "property '{}'", self.schema_path.join(" "))?;
+                property.description.as_ref()
+            }
+        };
+
+        if let Some(description) = description {
+            writeln!(f, "  {}", description)?;
+        }
+
+        if let PathTarget::Property(property) = self.target {
+            writeln!(
+                f,
+                "  mandatory: {}, multiple: {}, deletable: {}",
+                property.mandatory, property.multiple, property.deletable
+            )?;
+
+            let mut defaults: Vec<String> = property
+                .default
+                .iter()
+                .map(|default| match default {
+                    DefaultValue::Literal(literal) => format!("'{}'", literal),
+                    DefaultValue::Command(command) => command.to_string(),
+                })
+                .collect();
+            defaults.sort();
+            if !defaults.is_empty() {
+                writeln!(f, "  default: {}", defaults.join(", "))?;
+            }
+
+            let mut values: Vec<String> = property
+                .values
+                .iter()
+                .map(|allowed| allowed.value.to_string())
+                .collect();
+            values.sort();
+            writeln!(f, "  values: {}", values.join(", "))?;
+        }
+
+        if !self.next.is_empty() {
+            let next: Vec<String> = self.next.iter().map(ToString::to_string).collect();
+            writeln!(f, "  next: {}", next.join(", "))?;
+        }
+
+        Ok(())
+    }
+}
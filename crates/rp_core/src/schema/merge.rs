@@ -1,8 +1,28 @@
 use crate::error::MergeError;
-use std::mem;
+use std::{collections::HashSet, fmt::Debug, hash::Hash};
 
 pub trait Merge {
-    fn merge(&mut self, other: Self, strategy: MergingStrategy) -> anyhow::Result<()>;
+    // fails on the first conflict the strategy can't resolve, though everything
+    // else is still merged
+    fn merge(&mut self, other: Self, strategy: MergingStrategy) -> anyhow::Result<()>
+    where
+        Self: Sized,
+    {
+        let mut merger = Merger::new(strategy);
+        self.merge_with(other, &mut merger);
+
+        match merger.conflicts.into_iter().next() {
+            Some(conflict) => Err(MergeError::Conflict {
+                path: conflict.path.join(" "),
+                this: conflict.ours,
+                that: conflict.theirs,
+            }
+            .into()),
+            None => Ok(()),
+        }
+    }
+
+    fn merge_with(&mut self, other: Self, merger: &mut Merger);
 }
 
 #[derive(Debug, Copy, Clone)]
@@ -10,30 +30,132 @@ pub enum MergingStrategy {
     Ours,
     Theirs,
     Error,
+    // sets of values, defaults and conditions are combined, anything else has to
+    // agree like with Error
+    Union,
+}
+
+// something both schemas have but disagree on, which the strategy couldn't resolve
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct MergeConflict {
+    // the node or property names and the field, e.g. ["system", "hostname",
+    // "deletable"], or ["templates", name]
+    pub path: Vec<String>,
+    pub ours: String,
+    pub theirs: String,
+}
+
+impl std::fmt::Display for MergeConflict {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(
+            f,
+            "'{}': {} conflicts with {}",
+            self.path.join(" "),
+            self.ours,
+            self.theirs
+        )
+    }
 }
 
-impl MergingStrategy {
-    pub fn resolve<T>(self, ours: &mut T, theirs: T) -> anyhow::Result<()>
+// keeps track of where in the schema the merge is and the conflicts found so far
+#[derive(Debug)]
+pub struct Merger {
+    pub strategy: MergingStrategy,
+    path: Vec<String>,
+    conflicts: Vec<MergeConflict>,
+}
+
+impl Merger {
+    pub fn new(strategy: MergingStrategy) -> Merger {
+        Merger {
+            strategy,
+            path: Vec::new(),
+            conflicts: Vec::new(),
+        }
+    }
+
+    pub fn into_conflicts(self) -> Vec<MergeConflict> {
+        self.conflicts
+    }
+
+    // the names of nodes and properties are entered while merging them
+    pub fn enter(&mut self, name: &str) {
+        self.path.push(name.to_owned());
+    }
+
+    pub fn leave(&mut self) {
+        self.path.pop();
+    }
+
+    pub fn conflict<T: Debug + ?Sized>(&mut self, field: &str, ours: &T, theirs: &T) {
+        let mut path = self.path.clone();
+        path.push(field.to_owned());
+
+        self.conflicts.push(MergeConflict {
+            path,
+            ours: format!("{:?}", ours),
+            theirs: format!("{:?}", theirs),
+        });
+    }
+
+    // whether theirs replaces ours where the two can't be combined, after recording
+    // the conflict if the strategy doesn't allow either
+    pub fn prefer_theirs<T: Debug + ?Sized>(&mut self, field: &str, ours: &T, theirs: &T) -> bool {
+        match self.strategy {
+            MergingStrategy::Ours => false,
+            MergingStrategy::Theirs => true,
+            MergingStrategy::Error | MergingStrategy::Union => {
+                self.conflict(field, ours, theirs);
+                false
+            }
+        }
+    }
+
+    pub fn resolve<T>(&mut self, field: &str, ours: &mut T, theirs: T)
     where
-        T: std::fmt::Debug + PartialEq,
+        T: Debug + PartialEq,
     {
-        match self {
-            MergingStrategy::Ours => Ok(()),
-            MergingStrategy::Theirs => {
-                mem::replace(ours, theirs);
-                Ok(())
-            }
-            MergingStrategy::Error => {
-                if *ours != theirs {
-                    Err(MergeError::Conflict {
-                        this: format!("{:?}", ours),
-                        that: format!("{:?}", theirs),
+        if *ours != theirs && self.prefer_theirs(field, &*ours, &theirs) {
+            *ours = theirs;
+        }
+    }
+
+    // a missing optional value never conflicts with a given one
+    pub fn resolve_optional<T>(&mut self, field: &str, ours: &mut Option<T>, theirs: Option<T>)
+    where
+        T: Debug + PartialEq,
+    {
+        match (&ours, theirs) {
+            (_, None) => (),
+            (None, theirs) => *ours = theirs,
+            (Some(_), theirs) => self.resolve(field, ours, theirs),
+        }
+    }
+
+    pub fn resolve_set<T>(&mut self, field: &str, ours: &mut HashSet<T>, theirs: HashSet<T>)
+    where
+        T: Debug + Eq + Hash,
+    {
+        match self.strategy {
+            MergingStrategy::Union => ours.extend(theirs),
+            _ => self.resolve(field, ours, theirs),
+        }
+    }
+
+    // like a set but in the order the elements are first given
+    pub fn resolve_list<T>(&mut self, field: &str, ours: &mut Vec<T>, theirs: Vec<T>)
+    where
+        T: Debug + PartialEq,
+    {
+        match self.strategy {
+            MergingStrategy::Union => {
+                for element in theirs {
+                    if !ours.contains(&element) {
+                        ours.push(element);
                     }
-                    .into())
-                } else {
-                    Ok(())
                 }
             }
+            _ => self.resolve(field, ours, theirs),
         }
     }
 }
@@ -0,0 +1,107 @@
+use super::{Schema, Validate};
+use crate::error;
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+
+// matches any single node name in a migration path
+pub const WILDCARD: &str = "*";
+
+// the steps that upgrade a save made with the previous schema version to this
+// migration's version
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq)]
+pub struct Migration {
+    pub version: u32,
+    pub steps: Vec<MigrationStep>,
+}
+
+// paths are node names from the save root. Any wildcards in a destination path are
+// replaced, in order, by the names the wildcards in the source path matched
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq)]
+#[serde(rename_all = "snake_case")]
+pub enum MigrationStep {
+    RenameNode {
+        path: Vec<String>,
+        to: String,
+    },
+    MoveNode {
+        path: Vec<String>,
+        to: Vec<String>,
+    },
+    DropNode {
+        path: Vec<String>,
+    },
+    RenameProperty {
+        path: Vec<String>,
+        property: String,
+        to: String,
+    },
+    MoveProperty {
+        path: Vec<String>,
+        property: String,
+        to: Vec<String>,
+    },
+    DropProperty {
+        path: Vec<String>,
+        property: String,
+    },
+    // values missing from the map are kept as they are
+    TransformProperty {
+        path: Vec<String>,
+        property: String,
+        values: HashMap<String, String>,
+    },
+}
+
+impl MigrationStep {
+    pub fn path(&self) -> &[String] {
+        match self {
+            MigrationStep::RenameNode { path, .. }
+            | MigrationStep::MoveNode { path, .. }
+            | MigrationStep::DropNode { path }
+            | MigrationStep::RenameProperty { path, .. }
+            | MigrationStep::MoveProperty { path, .. }
+            | MigrationStep::DropProperty { path, .. }
+            | MigrationStep::TransformProperty { path, .. } => path,
+        }
+    }
+
+    fn destination(&self) -> Option<&[String]> {
+        match self {
+            MigrationStep::MoveNode { to, .. } | MigrationStep::MoveProperty { to, .. } => Some(to),
+            _ => None,
+        }
+    }
+}
+
+fn wildcards(path: &[String]) -> usize {
+    path.iter().filter(|name| *name == WILDCARD).count()
+}
+
+impl Validate for Migration {
+    fn validate(&self, schema: &Schema) -> anyhow::Result<()> {
+        for step in &self.steps {
+            step.validate(schema)?;
+        }
+
+        Ok(())
+    }
+}
+
+impl Validate for MigrationStep {
+    fn validate(&self, _schema: &Schema) -> anyhow::Result<()> {
+        let path = self.path();
+
+        // there are no properties in the save root and it can't be moved either
+        let valid = !path.is_empty()
+            && match self.destination() {
+                Some(to) => !to.is_empty() && wildcards(to) <= wildcards(path),
+                None => true,
+            };
+
+        if valid {
+            Ok(())
+        } else {
+            Err(error::SchemaValidationError::MigrationPath(format!("{:?}", self)).into())
+        }
+    }
+}
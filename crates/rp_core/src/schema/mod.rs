@@ -1,9 +1,20 @@
+mod api_model;
 mod bound;
+mod condition;
+mod diff;
+mod include;
+mod json_schema;
+mod lint;
+mod location;
+mod lookup;
 mod matches;
 mod merge;
+mod migration;
 mod node;
+mod normalise;
 mod property;
 mod query;
+mod reference;
 mod source_command;
 mod template;
 #[cfg(test)]
@@ -13,33 +24,58 @@ mod value;
 
 use crate::error;
 pub use bound::Bound;
+pub use condition::{Condition, PARENT};
+pub use diff::{Change, Compatibility, SchemaChange, SchemaDiff};
 use flate2::{read::ZlibDecoder, write::ZlibEncoder, Compression};
+pub use json_schema::JsonSchema;
+use location::{Document, Origins};
+pub use location::{SchemaItem, SchemaLocation, ValidationReport};
+pub use lookup::{PathElement, PathTarget, SchemaPath};
 pub use matches::Matches;
-pub use merge::{Merge, MergingStrategy};
+pub use merge::{Merge, MergeConflict, Merger, MergingStrategy};
+pub use migration::{Migration, MigrationStep, WILDCARD};
 pub use node::{
     MultiSchemaNode, MultiSchemaNodeSource, NodeLocator, SchemaNode, SchemaNodeTrait,
     SingleSchemaNode,
 };
+pub use normalise::Normalise;
 pub use property::Property;
 pub use query::Query;
+pub use reference::ReferenceFormat;
 use rp_log::*;
 use serde::{Deserialize, Serialize};
-use source_command::SourceCommand;
+pub use source_command::SourceCommand;
 use std::{
     collections::{hash_map::Entry, HashMap},
     io::{BufReader, Read, Write},
-    rc::Rc,
+    path::PathBuf,
+    sync::Arc,
+};
+pub use template::{
+    AddressTemplate, HostnameTemplate, IpFamily, LengthTemplate, MacTemplate, PasswordTemplate,
+    PrefixTemplate, ReferenceTemplate, Template,
 };
-pub use template::Template;
 pub use validate::Validate;
-pub use value::{DefaultValue, Value};
+pub use value::{AllowedValue, DefaultValue, Unit, Value};
 
 #[derive(Serialize, Deserialize, Debug)]
 pub struct Schema {
-    pub templates: HashMap<String, Rc<Template>>,
+    // saves made with an older version are migrated when loaded
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    version: Option<u32>,
+    #[serde(default)]
+    pub migrations: Vec<Migration>,
+    // other schema files to merge into this one, see from_yaml_path
+    #[serde(default, skip_serializing)]
+    include: Vec<String>,
+    #[serde(default)]
+    pub templates: HashMap<String, Arc<Template>>,
+    #[serde(default)]
     pub nodes: HashMap<String, Box<SchemaNode>>,
     #[serde(default)]
     regex_cache: HashMap<String, Vec<u8>>,
+    #[serde(skip)]
+    origins: Origins,
 }
 
 impl Schema {
@@ -49,39 +85,106 @@ impl Schema {
     }
 
     pub fn from_yaml_file<R: Read>(reader: R) -> anyhow::Result<Schema> {
-        let buf_reader = BufReader::new(reader);
-        Ok(serde_yaml::from_reader(buf_reader)?)
+        let mut text = String::new();
+        BufReader::new(reader).read_to_string(&mut text)?;
+        Schema::from_yaml_str(&text, None)
+    }
+
+    // the text is kept around just long enough to find where each item in it is
+    // for error messages
+    fn from_yaml_str(text: &str, path: Option<PathBuf>) -> anyhow::Result<Schema> {
+        let mut schema: Schema = serde_yaml::from_str(text)?;
+        schema.origins = Origins::record(&schema, Document::scan(text, path));
+        schema.link_templates();
+        Ok(schema)
     }
 
     pub fn from_binary(binary: &[u8]) -> anyhow::Result<Schema> {
         let decoder = ZlibDecoder::new(binary);
         let mut schema: Schema = serde_json::from_reader(decoder)?;
         schema.load_regexes_from_cache()?;
+        schema.link_templates();
         schema.populate_node_metadata();
         Ok(schema)
     }
 }
 
 impl Schema {
+    // every error in the schema is reported at once, each with where it was found
     pub fn validate(&mut self) -> anyhow::Result<()> {
-        self.validate_templates()?;
-        self.validate_nodes()?;
-        Ok(())
+        self.link_templates();
+        self.populate_node_metadata();
+
+        let mut report = ValidationReport::default();
+        for include in &self.include {
+            report.push(
+                SchemaItem::Include(include.to_owned()),
+                error::SchemaValidationError::UnresolvedInclude(include.to_owned()).into(),
+            );
+        }
+
+        self.validate_templates(&mut report);
+        self.validate_nodes(&mut report);
+        self.validate_conditions(&mut report);
+        self.validate_migrations(&mut report);
+
+        let errors = report.into_errors(self);
+        if errors.is_empty() {
+            Ok(())
+        } else {
+            Err(error::SchemaErrors { errors }.into())
+        }
     }
 
-    fn validate_templates(&mut self) -> anyhow::Result<()> {
-        for template in self.templates.values() {
-            template.validate(&self)?;
+    fn validate_migrations(&self, report: &mut ValidationReport) {
+        let mut previous = 0;
+        for migration in &self.migrations {
+            let item = SchemaItem::Migration(migration.version);
+
+            if migration.version <= previous || migration.version > self.version() {
+                report.push(
+                    item.clone(),
+                    error::SchemaValidationError::MigrationVersion {
+                        version: migration.version,
+                        schema_version: self.version(),
+                    }
+                    .into(),
+                );
+            }
+
+            report.check(item, migration.validate(&self));
+            previous = migration.version;
+        }
+    }
+
+    fn validate_templates(&self, report: &mut ValidationReport) {
+        for (name, template) in &self.templates {
+            report.check(
+                SchemaItem::Template(name.to_owned()),
+                template.validate(&self),
+            );
         }
-        Ok(())
     }
 
-    fn validate_nodes(&self) -> anyhow::Result<()> {
+    fn validate_nodes(&self, report: &mut ValidationReport) {
         for node in self.nodes.values() {
-            node.validate(&self)?;
+            node.validate(&self, report);
         }
+    }
+}
 
-        Ok(())
+impl Schema {
+    // a schema without a version predates versioning, as do saves without one
+    pub fn version(&self) -> u32 {
+        self.version.unwrap_or(0)
+    }
+
+    // the migrations needed to bring a save made with the given version up to this
+    // schema's version
+    pub fn migrations_from(&self, version: u32) -> impl Iterator<Item = &Migration> {
+        self.migrations
+            .iter()
+            .filter(move |migration| migration.version > version)
     }
 }
 
@@ -99,9 +202,15 @@ impl Schema {
     }
 
     fn populate_node_metadata(&mut self) {
-        let root_locator = Rc::new(NodeLocator::new(String::from("schema"), None));
+        let root_locator = Arc::new(NodeLocator::new(String::from("schema"), None));
         for (name, node) in self.nodes.iter_mut() {
-            node.update_locators(name.to_owned(), Rc::clone(&root_locator));
+            node.update_locators(name.to_owned(), Arc::clone(&root_locator));
+        }
+    }
+
+    fn link_templates(&self) {
+        for template in self.templates.values() {
+            template.link(&self.templates);
         }
     }
 
@@ -149,14 +258,33 @@ impl Schema {
         sum
     }
 
-    pub fn find_node(&self, locator: Rc<NodeLocator>) -> Option<&SchemaNode> {
+    // references can't go through multi nodes since there'd be no single node to
+    // refer to
+    pub fn find_multi_node(&self, path: &[String]) -> Option<&MultiSchemaNode> {
+        let (first, rest) = path.split_first()?;
+        let mut current = &**self.nodes.get(first)?;
+
+        for name in rest {
+            current = match current {
+                SchemaNode::SingleSchemaNode(single) => &**single.subnodes.get(name)?,
+                SchemaNode::MultiSchemaNode(_) => return None,
+            };
+        }
+
+        match current {
+            SchemaNode::MultiSchemaNode(multi) => Some(multi),
+            SchemaNode::SingleSchemaNode(_) => None,
+        }
+    }
+
+    pub fn find_node(&self, locator: Arc<NodeLocator>) -> Option<&SchemaNode> {
         let mut locator_stack = Vec::new();
         let mut current = Some(locator);
 
         while let Some(c) = &current {
             locator_stack.push(c.node.to_owned());
             current = if let Some(prev_rc) = &c.previous {
-                Some(Rc::clone(prev_rc))
+                Some(Arc::clone(prev_rc))
             } else {
                 None
             };
@@ -195,39 +323,71 @@ impl Schema {
 }
 
 impl Merge for Schema {
-    fn merge(&mut self, other: Self, strategy: MergingStrategy) -> anyhow::Result<()> {
+    fn merge_with(&mut self, other: Self, merger: &mut Merger) {
+        merger.resolve_optional("version", &mut self.version, other.version);
+        self.origins.merge(other.origins);
+        self.include.extend(other.include);
+
+        for migration in other.migrations {
+            if !self.migrations.contains(&migration) {
+                self.migrations.push(migration);
+            }
+        }
+        self.migrations.sort_by_key(|migration| migration.version);
+
+        // templates can't be compared, so any defined in both conflict
+        merger.enter("templates");
         for (name, template) in other.templates {
             match self.templates.entry(name) {
-                Entry::Occupied(mut existing) => match strategy {
-                    MergingStrategy::Ours => (),
-                    MergingStrategy::Theirs => {
+                Entry::Occupied(mut existing) => {
+                    if merger.prefer_theirs(existing.key(), existing.get(), &template) {
                         existing.insert(template);
                     }
-                    MergingStrategy::Error => {
-                        return Err(error::MergeError::Conflict {
-                            this: format!("{:?}", existing),
-                            that: format!("{:?}", template),
-                        }
-                        .into())
-                    }
-                },
+                }
                 Entry::Vacant(existing) => {
                     existing.insert(template);
                 }
             }
         }
+        merger.leave();
 
         for (name, node) in other.nodes {
             match self.nodes.entry(name) {
                 Entry::Occupied(mut existing) => {
-                    existing.get_mut().merge(*node, strategy)?;
+                    merger.enter(existing.key());
+                    existing.get_mut().merge_with(*node, merger);
+                    merger.leave();
                 }
                 Entry::Vacant(existing) => {
                     existing.insert(node);
                 }
             }
         }
+    }
+}
 
-        Ok(())
+impl Schema {
+    // merges like merge does but returns every conflict instead of failing on the
+    // first, so they can be resolved before merging for real. copies are merged,
+    // leaving both schemas as they were
+    pub fn dry_run_merge(&self, other: &Schema, strategy: MergingStrategy) -> Vec<MergeConflict> {
+        let mut merger = Merger::new(strategy);
+        self.merge_copy()
+            .merge_with(other.merge_copy(), &mut merger);
+        merger.into_conflicts()
+    }
+
+    // everything merging looks at. templates are only ever replaced, not changed,
+    // so they're shared
+    fn merge_copy(&self) -> Schema {
+        Schema {
+            version: self.version,
+            migrations: self.migrations.clone(),
+            include: self.include.clone(),
+            templates: self.templates.clone(),
+            nodes: self.nodes.clone(),
+            regex_cache: HashMap::new(),
+            origins: Origins::default(),
+        }
     }
 }
@@ -3,24 +3,25 @@ mod node_locator;
 mod single_schema_node;
 use enum_dispatch::enum_dispatch;
 
-use super::{Merge, MergingStrategy, Schema, Validate};
-use anyhow::anyhow;
+use super::{Merge, Merger, Schema, ValidationReport};
 pub use multi_schema_node::{MultiSchemaNode, MultiSchemaNodeSource};
 pub use node_locator::NodeLocator;
 use serde::{Deserialize, Serialize};
 pub use single_schema_node::SingleSchemaNode;
-use std::rc::Rc;
+use std::sync::Arc;
 
 #[enum_dispatch(SchemaNode)]
 pub trait SchemaNodeTrait: Merge {
     fn node_count(&self) -> usize;
     fn property_count(&self) -> usize;
-    fn update_locators(&mut self, name: String, previous: Rc<NodeLocator>);
-    fn get_locator(&self) -> Rc<NodeLocator>;
+    fn update_locators(&mut self, name: String, previous: Arc<NodeLocator>);
+    fn get_locator(&self) -> Arc<NodeLocator>;
+    // errors are reported instead of returned so one node doesn't hide another's
+    fn validate(&self, schema: &Schema, report: &mut ValidationReport);
 }
 
 #[enum_dispatch]
-#[derive(Serialize, Deserialize, Debug)]
+#[derive(Serialize, Deserialize, Debug, Clone)]
 #[serde(untagged)]
 pub enum SchemaNode {
     // Multi has to be the first variant because:
@@ -40,39 +41,33 @@ pub enum SchemaNode {
     SingleSchemaNode,
 }
 
-// enum_dispatch could be used to get rid of these boilerplate impls but it
+// enum_dispatch could be used to get rid of this boilerplate impl but it
 // doesn't seem to support linking multiple traits to a single enum, only
 // multiple enums to a single trait
-impl Validate for SchemaNode {
-    fn validate(&self, schema: &Schema) -> anyhow::Result<()> {
-        match self {
-            SchemaNode::SingleSchemaNode(node) => node.validate(schema),
-            SchemaNode::MultiSchemaNode(node) => node.validate(schema),
+impl Merge for SchemaNode {
+    fn merge_with(&mut self, other: Self, merger: &mut Merger) {
+        match (self, other) {
+            (SchemaNode::SingleSchemaNode(node), SchemaNode::SingleSchemaNode(other)) => {
+                node.merge_with(other, merger)
+            }
+            (SchemaNode::MultiSchemaNode(node), SchemaNode::MultiSchemaNode(other)) => {
+                node.merge_with(other, merger)
+            }
+            // a single node and a multi node can't be combined, only one of them kept
+            (this, other) => {
+                if merger.prefer_theirs("kind", this.kind(), other.kind()) {
+                    *this = other;
+                }
+            }
         }
     }
 }
 
-impl Merge for SchemaNode {
-    fn merge(&mut self, other: Self, strategy: MergingStrategy) -> anyhow::Result<()> {
+impl SchemaNode {
+    fn kind(&self) -> &'static str {
         match self {
-            SchemaNode::SingleSchemaNode(node) => {
-                if let SchemaNode::SingleSchemaNode(other) = other {
-                    node.merge(other, strategy)
-                } else {
-                    Err(anyhow!(
-                        "attempting to merge SingleSchemaNode with MultiSchemaNode"
-                    ))
-                }
-            }
-            SchemaNode::MultiSchemaNode(node) => {
-                if let SchemaNode::MultiSchemaNode(other) = other {
-                    node.merge(other, strategy)
-                } else {
-                    Err(anyhow!(
-                        "attempting to merge MultiSchemaNode with SingleSchemaNode"
-                    ))
-                }
-            }
+            SchemaNode::SingleSchemaNode(_) => "single node",
+            SchemaNode::MultiSchemaNode(_) => "multi node",
         }
     }
 }
@@ -1,21 +1,35 @@
 mod node_source;
 
 use super::{
-    super::{SourceCommand, Validate},
-    Merge, MergingStrategy, NodeLocator, Schema, SchemaNode, SchemaNodeTrait,
+    super::{Condition, SchemaItem, SourceCommand, Validate, ValidationReport, WILDCARD},
+    Merge, Merger, NodeLocator, Schema, SchemaNode, SchemaNodeTrait,
 };
+use crate::error;
 pub use node_source::MultiSchemaNodeSource;
 use serde::{Deserialize, Serialize};
-use std::rc::Rc;
+use std::sync::Arc;
 
-#[derive(Serialize, Deserialize, Debug)]
+#[derive(Serialize, Deserialize, Debug, Clone)]
 pub struct MultiSchemaNode {
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub description: Option<String>,
     pub source: MultiSchemaNodeSource,
     // helps a ton down the line if this node is in the heap
     // like subnodes in a single node
     pub node: Box<SchemaNode>,
+    // nodes can only be added when these hold, relative to the node this one is in
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub when: Vec<Condition>,
+    // how many nodes there may be, sourced ones included
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub min: Option<usize>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub max: Option<usize>,
+    // nodes are kept in the order they're added instead of as a set
+    #[serde(default)]
+    pub ordered: bool,
     #[serde(skip)]
-    pub locator: Rc<NodeLocator>,
+    pub locator: Arc<NodeLocator>,
 }
 
 impl SchemaNodeTrait for MultiSchemaNode {
@@ -27,29 +41,45 @@ impl SchemaNodeTrait for MultiSchemaNode {
         self.node.property_count()
     }
 
-    fn get_locator(&self) -> Rc<NodeLocator> {
-        Rc::clone(&self.locator)
+    fn get_locator(&self) -> Arc<NodeLocator> {
+        Arc::clone(&self.locator)
     }
 
-    fn update_locators(&mut self, name: String, locator: Rc<NodeLocator>) {
-        self.locator = Rc::new(NodeLocator::new(name, Some(Rc::clone(&locator))));
+    fn update_locators(&mut self, name: String, locator: Arc<NodeLocator>) {
+        self.locator = Arc::new(NodeLocator::new(name, Some(Arc::clone(&locator))));
         self.node
-            .update_locators(String::from("template"), Rc::clone(&self.locator));
+            .update_locators(String::from("template"), Arc::clone(&self.locator));
     }
-}
 
-impl Validate for MultiSchemaNode {
-    fn validate(&self, schema: &Schema) -> anyhow::Result<()> {
-        self.node.validate(schema)?;
-        self.source.validate(schema)?;
+    fn validate(&self, schema: &Schema, report: &mut ValidationReport) {
+        self.node.validate(schema, report);
+
+        let item = SchemaItem::Node(Arc::clone(&self.locator));
+        report.check(item.clone(), self.source.validate(schema));
 
-        Ok(())
+        if self.min.unwrap_or(0) > self.max.unwrap_or(usize::MAX) {
+            report.push(
+                item,
+                error::SchemaValidationError::ElementCount {
+                    min: self.min,
+                    max: self.max,
+                }
+                .into(),
+            );
+        }
     }
 }
 
 impl Merge for MultiSchemaNode {
-    fn merge(&mut self, other: Self, strategy: MergingStrategy) -> anyhow::Result<()> {
-        self.node.merge(*other.node, strategy)?;
-        Ok(())
+    fn merge_with(&mut self, other: Self, merger: &mut Merger) {
+        merger.resolve_optional("description", &mut self.description, other.description);
+        merger.resolve_list("when", &mut self.when, other.when);
+        merger.resolve_optional("min", &mut self.min, other.min);
+        merger.resolve_optional("max", &mut self.max, other.max);
+        merger.resolve("ordered", &mut self.ordered, other.ordered);
+
+        merger.enter(WILDCARD);
+        self.node.merge_with(*other.node, merger);
+        merger.leave();
     }
 }
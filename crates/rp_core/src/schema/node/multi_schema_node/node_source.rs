@@ -2,15 +2,20 @@ use super::{Schema, SourceCommand, Validate};
 use crate::error;
 use serde::{Deserialize, Serialize};
 
-#[derive(Serialize, Deserialize, Debug)]
+#[derive(Serialize, Deserialize, Debug, Clone)]
 pub struct MultiSchemaNodeSource {
     pub id: String,
     pub template: Option<String>,
-    pub command: SourceCommand,
+    #[serde(default)]
+    pub command: Option<SourceCommand>,
 }
 
 impl Validate for MultiSchemaNodeSource {
     fn validate(&self, schema: &Schema) -> anyhow::Result<()> {
+        if self.template.is_none() && self.command.is_none() {
+            return Err(error::SchemaValidationError::NoNodeSource(self.id.clone()).into());
+        }
+
         if let Some(template) = &self.template {
             if !schema.templates.contains_key(template) {
                 return Err(error::SchemaValidationError::MissingTemplate(template.clone()).into());
@@ -1,15 +1,31 @@
-use std::rc::Rc;
+use std::sync::Arc;
 
 #[derive(Debug, Clone)]
 pub struct NodeLocator {
     pub node: String,
-    pub previous: Option<Rc<NodeLocator>>,
+    pub previous: Option<Arc<NodeLocator>>,
 }
 
 impl NodeLocator {
-    pub fn new(node: String, previous: Option<Rc<NodeLocator>>) -> NodeLocator {
+    pub fn new(node: String, previous: Option<Arc<NodeLocator>>) -> NodeLocator {
         NodeLocator { node, previous }
     }
+
+    // the node names from the top-level node down to this one, without the root
+    pub fn path(&self) -> Vec<String> {
+        let mut path = Vec::new();
+        let mut current = Some(self);
+
+        while let Some(locator) = current {
+            if locator.previous.is_some() {
+                path.push(locator.node.to_owned());
+            }
+            current = locator.previous.as_ref().map(|previous| &**previous);
+        }
+
+        path.reverse();
+        path
+    }
 }
 
 impl Default for NodeLocator {
@@ -1,21 +1,23 @@
 use super::{
-    super::Property, Merge, MergingStrategy, NodeLocator, Schema, SchemaNode, SchemaNodeTrait,
-    Validate,
+    super::{Property, SchemaItem, ValidationReport},
+    Merge, Merger, NodeLocator, Schema, SchemaNode, SchemaNodeTrait,
 };
 use serde::{Deserialize, Serialize};
 use std::{
     collections::{hash_map::Entry, HashMap},
-    rc::Rc,
+    sync::Arc,
 };
 
-#[derive(Serialize, Deserialize, Debug)]
+#[derive(Serialize, Deserialize, Debug, Clone)]
 pub struct SingleSchemaNode {
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub description: Option<String>,
     #[serde(default)]
     pub subnodes: HashMap<String, Box<SchemaNode>>,
     #[serde(default)]
     pub properties: HashMap<String, Property>,
     #[serde(skip)]
-    pub locator: Rc<NodeLocator>,
+    pub locator: Arc<NodeLocator>,
 }
 
 impl SchemaNodeTrait for SingleSchemaNode {
@@ -35,39 +37,43 @@ impl SchemaNodeTrait for SingleSchemaNode {
         sum
     }
 
-    fn get_locator(&self) -> Rc<NodeLocator> {
+    fn get_locator(&self) -> Arc<NodeLocator> {
         self.locator.clone()
     }
 
-    fn update_locators(&mut self, name: String, locator: Rc<NodeLocator>) {
-        self.locator = Rc::new(NodeLocator::new(name, Some(Rc::clone(&locator))));
+    fn update_locators(&mut self, name: String, locator: Arc<NodeLocator>) {
+        self.locator = Arc::new(NodeLocator::new(name, Some(Arc::clone(&locator))));
 
         for (subname, subnode) in &mut self.subnodes {
-            subnode.update_locators(subname.to_owned(), Rc::clone(&self.locator));
+            subnode.update_locators(subname.to_owned(), Arc::clone(&self.locator));
         }
     }
-}
 
-impl Validate for SingleSchemaNode {
-    fn validate(&self, schema: &Schema) -> anyhow::Result<()> {
-        for property in self.properties.values() {
-            property.validate(schema)?;
+    fn validate(&self, schema: &Schema, report: &mut ValidationReport) {
+        for (name, property) in &self.properties {
+            property.validate(
+                schema,
+                SchemaItem::Property(Arc::clone(&self.locator), name.to_owned()),
+                report,
+            );
         }
 
         for node in self.subnodes.values() {
-            node.validate(schema)?;
+            node.validate(schema, report);
         }
-
-        Ok(())
     }
 }
 
 impl Merge for SingleSchemaNode {
-    fn merge(&mut self, other: Self, strategy: MergingStrategy) -> anyhow::Result<()> {
+    fn merge_with(&mut self, other: Self, merger: &mut Merger) {
+        merger.resolve_optional("description", &mut self.description, other.description);
+
         for (name, node) in other.subnodes {
             match self.subnodes.entry(name) {
                 Entry::Occupied(mut existing) => {
-                    existing.get_mut().merge(*node, strategy)?;
+                    merger.enter(existing.key());
+                    existing.get_mut().merge_with(*node, merger);
+                    merger.leave();
                 }
                 Entry::Vacant(existing) => {
                     existing.insert(node);
@@ -78,14 +84,14 @@ impl Merge for SingleSchemaNode {
         for (name, property) in other.properties {
             match self.properties.entry(name) {
                 Entry::Occupied(mut existing) => {
-                    existing.get_mut().merge(property, strategy)?;
+                    merger.enter(existing.key());
+                    existing.get_mut().merge_with(property, merger);
+                    merger.leave();
                 }
                 Entry::Vacant(existing) => {
                     existing.insert(property);
                 }
             }
         }
-
-        Ok(())
     }
 }
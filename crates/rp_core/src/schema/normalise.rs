@@ -0,0 +1,5 @@
+// values are only normalised after they've been matched against the same thing,
+// so implementations may assume the value is valid
+pub trait Normalise {
+    fn normalise(&self, value: &str) -> anyhow::Result<String>;
+}
@@ -1,101 +1,194 @@
 use super::{
-    value::{DefaultValue, Value},
-    Matches, Merge, MergingStrategy, Schema, Validate,
+    value::{AllowedValue, DefaultValue, Value},
+    Condition, Matches, Merge, Merger, Schema, SchemaItem, Validate, ValidationReport,
 };
 use crate::error;
 use anyhow::anyhow;
 use serde::{Deserialize, Serialize};
 use std::collections::HashSet;
 
-#[derive(Serialize, Deserialize, Debug, Default)]
+#[derive(Serialize, Deserialize, Debug, Clone, Default)]
 pub struct Property {
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub description: Option<String>,
+    #[serde(default)]
+    pub mandatory: bool,
     #[serde(default)]
     pub multiple: bool,
+    // the values are masked wherever they're shown
+    #[serde(default)]
+    pub secret: bool,
+    // multiple values are kept in the order they're given instead of as a set
+    #[serde(default)]
+    pub ordered: bool,
+    // how many values the property may have, only above one if it's multiple
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub min: Option<usize>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub max: Option<usize>,
     #[serde(default)]
     pub default: HashSet<DefaultValue>,
-    pub values: HashSet<Value>,
+    pub values: HashSet<AllowedValue>,
     #[serde(default = "Property::default_deletable")]
     pub deletable: bool,
+    // the property can only be set when these hold, otherwise its defaults are
+    // ignored
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub when: Vec<Condition>,
+    // these have to hold whenever the property has values, defaults included
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub must: Vec<Condition>,
+    // none of these may hold while the property has values
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub excludes: Vec<Condition>,
 }
 
 impl Property {
     fn default_deletable() -> bool {
         true
     }
+
+    // every condition of the property and its values
+    pub fn conditions(&self) -> impl Iterator<Item = &Condition> {
+        self.when
+            .iter()
+            .chain(self.must.iter())
+            .chain(self.excludes.iter())
+            .chain(
+                self.values
+                    .iter()
+                    .flat_map(|allowed| allowed.excludes.iter()),
+            )
+    }
+
+    // whether the value fits any of the allowed values
+    pub fn allows(&self, value: &str, schema: &Schema) -> anyhow::Result<bool> {
+        let mut referenced = false;
+
+        for allowed in &self.values {
+            match &allowed.value {
+                Value::Literal(literal) => {
+                    if value == literal {
+                        return Ok(true);
+                    }
+                }
+                Value::Template(template) => {
+                    let templ = schema.templates.get(template).ok_or_else(|| {
+                        anyhow!("Template {} not found after existence validation", template)
+                    })?;
+
+                    if templ.matches(value)? {
+                        return Ok(true);
+                    }
+                }
+                Value::Range(range) => {
+                    if range.matches(value)? {
+                        return Ok(true);
+                    }
+                }
+                // the referenced nodes only exist in a config, so any value may be
+                // one, but only if nothing else has a say
+                Value::Reference(_) => {
+                    referenced = true;
+                    continue;
+                }
+            }
+        }
+
+        Ok(referenced)
+    }
 }
 
-impl Validate for Property {
-    fn validate(&self, schema: &Schema) -> anyhow::Result<()> {
+impl Property {
+    // every problem with the property is reported, not just the first one
+    pub fn validate(&self, schema: &Schema, item: SchemaItem, report: &mut ValidationReport) {
         if self.values.is_empty() {
-            return Err(error::SchemaValidationError::NoValues.into());
+            report.push(item.clone(), error::SchemaValidationError::NoValues.into());
         }
 
         for value in &self.values {
-            value.validate(schema)?;
+            report.check(item.clone(), value.validate(schema));
         }
 
-        if !self.default.is_empty() {
-            if !self.multiple && self.default.len() > 1 {
-                return Err(error::SchemaValidationError::NoMultipleValuesAllowed.into());
-            }
+        if self.ordered && !self.multiple {
+            report.push(
+                item.clone(),
+                error::SchemaValidationError::OrderedSingleValue.into(),
+            );
+        }
 
-            let mut match_found = false;
-            for default in &self.default {
-                match default {
-                    DefaultValue::Literal(def) => {
-                        for value in &self.values {
-                            match value {
-                                Value::Literal(literal) => {
-                                    if def == literal {
-                                        match_found = true;
-                                        break;
-                                    }
-                                }
-                                Value::Template(template) => {
-                                    let templ =
-                                        schema.templates.get(template).ok_or_else(|| {
-                                            anyhow!(
-                                                "Template {} not found after existence validation",
-                                                template
-                                            )
-                                        })?;
-
-                                    if templ.matches(def)? {
-                                        match_found = true;
-                                        break;
-                                    }
-                                }
-                                Value::Range(range) => {
-                                    if range.matches(def)? {
-                                        match_found = true;
-                                        break;
-                                    }
-                                }
-                            }
-                        }
-                    }
-                    _ => match_found = true,
+        let limit = if self.multiple { usize::MAX } else { 1 };
+        if self.min.unwrap_or(0) > self.max.unwrap_or(limit) || self.max.unwrap_or(0) > limit {
+            report.push(
+                item.clone(),
+                error::SchemaValidationError::ElementCount {
+                    min: self.min,
+                    max: self.max,
                 }
+                .into(),
+            );
+        }
 
-                if !match_found {
-                    return Err(
-                        error::SchemaValidationError::InvalidDefaultValue(default.clone()).into(),
-                    );
+        if self.default.is_empty() {
+            return;
+        }
+
+        // queries may resolve to any number of values, so only literals can be
+        // counted up front
+        let literals = self
+            .default
+            .iter()
+            .all(|default| matches!(default, DefaultValue::Literal(_)));
+        if !self.multiple && self.default.len() > 1 {
+            report.push(
+                item.clone(),
+                error::SchemaValidationError::NoMultipleValuesAllowed.into(),
+            );
+        } else if self.default.len() > self.max.unwrap_or(limit)
+            || (literals && self.default.len() < self.min.unwrap_or(0))
+        {
+            report.push(
+                item.clone(),
+                error::SchemaValidationError::DefaultCount {
+                    min: self.min,
+                    max: self.max,
                 }
-            }
+                .into(),
+            );
         }
 
-        Ok(())
+        for default in &self.default {
+            let allowed = match default {
+                DefaultValue::Literal(def) => self.allows(def, schema),
+                _ => Ok(true),
+            };
+
+            match allowed {
+                Ok(true) => {}
+                Ok(false) => report.push(
+                    item.clone(),
+                    error::SchemaValidationError::InvalidDefaultValue(default.clone()).into(),
+                ),
+                Err(e) => report.push(item.clone(), e),
+            }
+        }
     }
 }
 
 impl Merge for Property {
-    fn merge(&mut self, other: Self, strategy: MergingStrategy) -> anyhow::Result<()> {
-        strategy.resolve(&mut self.multiple, other.multiple)?;
-        strategy.resolve(&mut self.deletable, other.deletable)?;
-        strategy.resolve(&mut self.values, other.values)?;
-        strategy.resolve(&mut self.default, other.default)?;
-
-        Ok(())
+    fn merge_with(&mut self, other: Self, merger: &mut Merger) {
+        merger.resolve_optional("description", &mut self.description, other.description);
+        merger.resolve("mandatory", &mut self.mandatory, other.mandatory);
+        merger.resolve("multiple", &mut self.multiple, other.multiple);
+        merger.resolve("secret", &mut self.secret, other.secret);
+        merger.resolve("ordered", &mut self.ordered, other.ordered);
+        merger.resolve_optional("min", &mut self.min, other.min);
+        merger.resolve_optional("max", &mut self.max, other.max);
+        merger.resolve("deletable", &mut self.deletable, other.deletable);
+        merger.resolve_set("values", &mut self.values, other.values);
+        merger.resolve_set("default", &mut self.default, other.default);
+        merger.resolve_list("when", &mut self.when, other.when);
+        merger.resolve_list("must", &mut self.must, other.must);
+        merger.resolve_list("excludes", &mut self.excludes, other.excludes);
     }
 }
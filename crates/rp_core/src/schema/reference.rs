@@ -0,0 +1,307 @@
+use super::{Condition, DefaultValue, Property, Schema, SchemaNode, WILDCARD};
+use std::fmt::Write;
+
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+pub enum ReferenceFormat {
+    Markdown,
+    Man,
+}
+
+impl Schema {
+    // every template, node and property, in the order they'd be looked for. the
+    // children of multi nodes are under the ID of their key, e.g.
+    // "interfaces ethernet <interface> mtu"
+    pub fn reference(&self, format: ReferenceFormat) -> String {
+        let mut sections = Vec::new();
+        for name in sorted_keys(self.nodes.keys()) {
+            add_node(
+                &self.nodes[name],
+                &[name.to_owned()],
+                &[name.to_owned()],
+                &mut sections,
+            );
+        }
+
+        let mut templates: Vec<(&str, String)> = self
+            .templates
+            .iter()
+            .map(|(name, template)| (name.as_str(), template.to_string()))
+            .collect();
+        templates.sort();
+
+        // writing to a string can't fail
+        match format {
+            ReferenceFormat::Markdown => markdown(self.version(), &templates, &sections),
+            ReferenceFormat::Man => man(self.version(), &templates, &sections),
+        }
+        .expect("writing the reference failed")
+    }
+}
+
+struct Section {
+    path: String,
+    description: Option<String>,
+    // what the keys of a multi node can be
+    key: Option<String>,
+    properties: Vec<Entry>,
+}
+
+struct Entry {
+    name: String,
+    description: Option<String>,
+    // labelled lines, e.g. ("Default", "'router'")
+    details: Vec<(&'static str, String)>,
+}
+
+fn add_node(node: &SchemaNode, path: &[String], base: &[String], sections: &mut Vec<Section>) {
+    match node {
+        SchemaNode::SingleSchemaNode(single) => {
+            let properties = sorted_keys(single.properties.keys())
+                .into_iter()
+                .map(|name| entry(name, &single.properties[name], base))
+                .collect();
+            sections.push(Section {
+                path: path.join(" "),
+                description: single.description.clone(),
+                key: None,
+                properties,
+            });
+
+            for name in sorted_keys(single.subnodes.keys()) {
+                add_node(
+                    &single.subnodes[name],
+                    &[path, &[name.to_owned()]].concat(),
+                    &[base, &[name.to_owned()]].concat(),
+                    sections,
+                );
+            }
+        }
+        SchemaNode::MultiSchemaNode(multi) => {
+            let mut key = match &multi.source.template {
+                Some(template) => format!("names matching template '{}'", template),
+                None => String::from("names from the system only"),
+            };
+            if let Some(command) = &multi.source.command {
+                if multi.source.template.is_some() {
+                    key.push_str(", and names from the system");
+                }
+                key.push_str(&format!(" ({})", command));
+            }
+            if multi.min.is_some() || multi.max.is_some() {
+                key.push_str(&format!(", {}", count(multi.min, multi.max)));
+            }
+
+            sections.push(Section {
+                path: path.join(" "),
+                description: multi.description.clone(),
+                key: Some(key),
+                properties: Vec::new(),
+            });
+
+            add_node(
+                &multi.node,
+                &[path, &[format!("<{}>", multi.source.id)]].concat(),
+                &[base, &[WILDCARD.to_owned()]].concat(),
+                sections,
+            );
+        }
+    }
+}
+
+fn entry(name: &str, property: &Property, base: &[String]) -> Entry {
+    let mut values: Vec<String> = property
+        .values
+        .iter()
+        .map(|allowed| match &allowed.description {
+            Some(description) => format!("{} ({})", allowed.value, description),
+            None => allowed.value.to_string(),
+        })
+        .collect();
+    values.sort();
+
+    let mut details = vec![("Values", values.join(", "))];
+
+    let mut defaults: Vec<String> = property
+        .default
+        .iter()
+        .map(|default| match default {
+            DefaultValue::Literal(literal) => format!("'{}'", literal),
+            DefaultValue::Command(command) => command.to_string(),
+        })
+        .collect();
+    defaults.sort();
+    if !defaults.is_empty() {
+        details.push(("Default", defaults.join(", ")));
+    }
+
+    if property.min.is_some() || property.max.is_some() {
+        details.push(("Count", count(property.min, property.max)));
+    }
+
+    let flags: Vec<&str> = [
+        (property.mandatory, "mandatory"),
+        (property.multiple, "multiple"),
+        (property.ordered, "ordered"),
+        (property.secret, "secret"),
+        (!property.deletable, "not deletable"),
+    ]
+    .iter()
+    .filter(|(set, _)| *set)
+    .map(|(_, flag)| *flag)
+    .collect();
+    if !flags.is_empty() {
+        details.push(("Flags", flags.join(", ")));
+    }
+
+    for (label, conditions) in &[
+        ("Only when", &property.when),
+        ("Requires", &property.must),
+        ("Excludes", &property.excludes),
+    ] {
+        if !conditions.is_empty() {
+            details.push((label, describe(conditions, base)));
+        }
+    }
+
+    Entry {
+        name: name.to_owned(),
+        description: property.description.clone(),
+        details,
+    }
+}
+
+fn describe(conditions: &[Condition], base: &[String]) -> String {
+    conditions
+        .iter()
+        .map(|condition| condition.describe(base))
+        .collect::<Vec<String>>()
+        .join(", ")
+}
+
+fn count(min: Option<usize>, max: Option<usize>) -> String {
+    match (min, max) {
+        (Some(min), Some(max)) => format!("{} to {}", min, max),
+        (Some(min), None) => format!("at least {}", min),
+        (None, Some(max)) => format!("at most {}", max),
+        (None, None) => String::from("any"),
+    }
+}
+
+fn sorted_keys<'a>(keys: impl Iterator<Item = &'a String>) -> Vec<&'a String> {
+    let mut keys: Vec<&String> = keys.collect();
+    keys.sort();
+    keys
+}
+
+fn markdown(
+    version: u32,
+    templates: &[(&str, String)],
+    sections: &[Section],
+) -> Result<String, std::fmt::Error> {
+    let mut out = String::new();
+    writeln!(out, "# Configuration reference\n")?;
+    writeln!(out, "Schema version {}.", version)?;
+
+    if !templates.is_empty() {
+        writeln!(out, "\n## Templates\n")?;
+        for (name, template) in templates {
+            writeln!(out, "- {}: {}", code(name), code(template))?;
+        }
+    }
+
+    writeln!(out, "\n## Nodes")?;
+    for section in sections {
+        writeln!(out, "\n### {}", code(&section.path))?;
+        if let Some(description) = &section.description {
+            writeln!(out, "\n{}", description)?;
+        }
+        if let Some(key) = &section.key {
+            writeln!(out, "\nKeyed by {}.", key)?;
+        }
+        if !section.properties.is_empty() {
+            writeln!(out)?;
+        }
+
+        for property in &section.properties {
+            write!(out, "- {}", code(&property.name))?;
+            match &property.description {
+                Some(description) => writeln!(out, ": {}", description)?,
+                None => writeln!(out)?,
+            }
+            for (label, text) in &property.details {
+                writeln!(out, "  - {}: {}", label, text)?;
+            }
+        }
+    }
+
+    Ok(out)
+}
+
+// long enough a fence that backticks in the text don't end it
+fn code(text: &str) -> String {
+    if text.contains('`') {
+        format!("`` {} ``", text)
+    } else {
+        format!("`{}`", text)
+    }
+}
+
+fn man(
+    version: u32,
+    templates: &[(&str, String)],
+    sections: &[Section],
+) -> Result<String, std::fmt::Error> {
+    let mut out = String::new();
+    writeln!(out, ".TH RP_SHELL 5 \"\" \"\" \"Configuration reference\"")?;
+    writeln!(out, ".SH NAME")?;
+    writeln!(out, "rp_shell \\- configuration reference")?;
+    writeln!(out, ".SH DESCRIPTION")?;
+    writeln!(out, "Schema version {}.", version)?;
+
+    if !templates.is_empty() {
+        writeln!(out, ".SH TEMPLATES")?;
+        for (name, template) in templates {
+            writeln!(out, ".TP\n.B {}\n{}", roff(name), roff_line(template))?;
+        }
+    }
+
+    writeln!(out, ".SH NODES")?;
+    for section in sections {
+        writeln!(out, ".SS {}", roff(&section.path))?;
+        if let Some(description) = &section.description {
+            writeln!(out, "{}", roff_line(description))?;
+        }
+        if let Some(key) = &section.key {
+            writeln!(out, ".PP\nKeyed by {}.", roff(key))?;
+        }
+
+        for property in &section.properties {
+            writeln!(out, ".TP\n.B {}", roff(&property.name))?;
+            if let Some(description) = &property.description {
+                writeln!(out, "{}\n.br", roff_line(description))?;
+            }
+            for (index, (label, text)) in property.details.iter().enumerate() {
+                if index > 0 {
+                    writeln!(out, ".br")?;
+                }
+                writeln!(out, "{}: {}", label, roff(text))?;
+            }
+        }
+    }
+
+    Ok(out)
+}
+
+fn roff(text: &str) -> String {
+    text.replace('\\', "\\e").replace('-', "\\-")
+}
+
+// text starting a line can't be taken for a request
+fn roff_line(text: &str) -> String {
+    let text = roff(text);
+    if text.starts_with('.') || text.starts_with('\'') {
+        format!("\\&{}", text)
+    } else {
+        text
+    }
+}
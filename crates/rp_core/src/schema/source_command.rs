@@ -1,3 +1,4 @@
+use crate::common::{source_registry, Context};
 use serde::{Deserialize, Serialize};
 
 #[derive(Debug, Serialize, Deserialize, Clone, PartialEq, Eq, Hash)]
@@ -6,3 +7,23 @@ pub enum SourceCommand {
     Function(String),
     Property(String),
 }
+
+impl SourceCommand {
+    pub fn resolve(&self, context: &Context) -> anyhow::Result<Vec<String>> {
+        match self {
+            SourceCommand::Function(name) => source_registry::resolve_function(name, context),
+            SourceCommand::Property(property) => {
+                source_registry::resolve_property(property, context)
+            }
+        }
+    }
+}
+
+impl std::fmt::Display for SourceCommand {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            SourceCommand::Function(name) => write!(f, "function '{}'", name),
+            SourceCommand::Property(property) => write!(f, "property '{}'", property),
+        }
+    }
+}
@@ -0,0 +1,107 @@
+use super::{Matches, Normalise, Schema, Validate};
+use serde::{Deserialize, Serialize};
+
+const MAX_LABEL_LENGTH: usize = 63;
+const MAX_NAME_LENGTH: usize = 253;
+
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq, Eq, Hash)]
+pub struct HostnameTemplate {
+    // a plain hostname is a single label, an FQDN may have several separated by dots
+    #[serde(default)]
+    pub fqdn: bool,
+}
+
+impl Matches for HostnameTemplate {
+    fn matches(&self, value: &str) -> anyhow::Result<bool> {
+        if !self.fqdn {
+            return Ok(valid_label(value));
+        }
+
+        // a single trailing dot marks the name as rooted
+        let name = if value.ends_with('.') {
+            &value[..value.len() - 1]
+        } else {
+            value
+        };
+
+        Ok(!name.is_empty() && name.len() <= MAX_NAME_LENGTH && name.split('.').all(valid_label))
+    }
+}
+
+// RFC 1123 labels: letters, digits and hyphens, not beginning or ending with a
+// hyphen
+fn valid_label(label: &str) -> bool {
+    !label.is_empty()
+        && label.len() <= MAX_LABEL_LENGTH
+        && !label.starts_with('-')
+        && !label.ends_with('-')
+        && label.chars().all(|c| c.is_ascii_alphanumeric() || c == '-')
+}
+
+// host names are case insensitive, and a rooted name is the same name
+impl Normalise for HostnameTemplate {
+    fn normalise(&self, value: &str) -> anyhow::Result<String> {
+        let name = if self.fqdn && value.ends_with('.') {
+            &value[..value.len() - 1]
+        } else {
+            value
+        };
+
+        Ok(name.to_ascii_lowercase())
+    }
+}
+
+impl Validate for HostnameTemplate {
+    fn validate(&self, _schema: &Schema) -> anyhow::Result<()> {
+        Ok(())
+    }
+}
+
+impl std::fmt::Display for HostnameTemplate {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        if self.fqdn {
+            write!(f, "<FQDN>")
+        } else {
+            write!(f, "<hostname>")
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn hostname() -> anyhow::Result<()> {
+        let hostname = HostnameTemplate { fqdn: false };
+
+        assert!(hostname.matches("router")?);
+        assert!(hostname.matches("router-1")?);
+        assert!(!hostname.matches("-router")?);
+        assert!(!hostname.matches("router.example.com")?);
+        assert!(!hostname.matches(&"a".repeat(64))?);
+        Ok(())
+    }
+
+    #[test]
+    fn fqdn() -> anyhow::Result<()> {
+        let fqdn = HostnameTemplate { fqdn: true };
+
+        assert!(fqdn.matches("router.example.com")?);
+        assert!(fqdn.matches("router.example.com.")?);
+        assert!(fqdn.matches("1.pool.ntp.org")?);
+        assert!(!fqdn.matches("router..example.com")?);
+        assert!(!fqdn.matches(".")?);
+        assert!(!fqdn.matches("router_1.example.com")?);
+        Ok(())
+    }
+
+    #[test]
+    fn normalise() -> anyhow::Result<()> {
+        let fqdn = HostnameTemplate { fqdn: true };
+
+        assert_eq!(fqdn.normalise("Router.Example.COM")?, "router.example.com");
+        assert_eq!(fqdn.normalise("a.example.")?, fqdn.normalise("a.example")?);
+        Ok(())
+    }
+}
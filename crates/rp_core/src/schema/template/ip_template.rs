@@ -0,0 +1,247 @@
+use super::{Matches, Normalise, Schema, Validate};
+use serde::{Deserialize, Serialize};
+use std::net::{IpAddr, Ipv4Addr, Ipv6Addr};
+
+#[derive(Serialize, Deserialize, Debug, Clone, Copy, PartialEq, Eq, Hash)]
+#[serde(rename_all = "lowercase")]
+pub enum IpFamily {
+    Ipv4,
+    Ipv6,
+    Any,
+}
+
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq, Eq, Hash)]
+pub struct AddressTemplate {
+    pub family: IpFamily,
+}
+
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq, Eq, Hash)]
+pub struct PrefixTemplate {
+    pub family: IpFamily,
+    // an interface address (192.168.0.1/24) has host bits set while a network
+    // prefix (192.168.0.0/24) doesn't
+    #[serde(default)]
+    pub allow_host_bits: bool,
+}
+
+impl IpFamily {
+    fn parse_address(self, value: &str) -> Option<IpAddr> {
+        match self {
+            IpFamily::Ipv4 => value.parse::<Ipv4Addr>().ok().map(IpAddr::V4),
+            IpFamily::Ipv6 => value.parse::<Ipv6Addr>().ok().map(IpAddr::V6),
+            IpFamily::Any => IpFamily::Ipv4
+                .parse_address(value)
+                .or_else(|| IpFamily::Ipv6.parse_address(value)),
+        }
+    }
+}
+
+// both address families as plain bits so prefix checks can be done uniformly
+#[derive(Debug, Clone, Copy)]
+struct IpBits {
+    bits: u128,
+    width: u32,
+}
+
+impl From<Ipv4Addr> for IpBits {
+    fn from(addr: Ipv4Addr) -> Self {
+        IpBits {
+            bits: u128::from(u32::from(addr)),
+            width: 32,
+        }
+    }
+}
+
+impl From<Ipv6Addr> for IpBits {
+    fn from(addr: Ipv6Addr) -> Self {
+        IpBits {
+            bits: u128::from(addr),
+            width: 128,
+        }
+    }
+}
+
+impl From<IpAddr> for IpBits {
+    fn from(addr: IpAddr) -> Self {
+        match addr {
+            IpAddr::V4(addr) => IpBits::from(addr),
+            IpAddr::V6(addr) => IpBits::from(addr),
+        }
+    }
+}
+
+impl IpBits {
+    fn host_bits_set(self, prefix_length: u32) -> bool {
+        let host_width = self.width - prefix_length;
+        if host_width == 0 {
+            false
+        } else {
+            self.bits & (u128::max_value() >> (128 - host_width)) != 0
+        }
+    }
+}
+
+impl Matches for AddressTemplate {
+    fn matches(&self, value: &str) -> anyhow::Result<bool> {
+        Ok(self.family.parse_address(value).is_some())
+    }
+}
+
+impl PrefixTemplate {
+    fn parse_prefix(&self, value: &str) -> Option<(IpAddr, u32)> {
+        let mut split = value.splitn(2, '/');
+        let (address, length) = match (split.next(), split.next()) {
+            (Some(address), Some(length)) => (address, length),
+            _ => return None,
+        };
+
+        let address = self.family.parse_address(address)?;
+
+        // u32's parser accepts a leading + which isn't valid in a prefix length
+        if length.is_empty() || !length.bytes().all(|b| b.is_ascii_digit()) {
+            return None;
+        }
+
+        let bits = IpBits::from(address);
+        match length.parse::<u32>() {
+            Ok(length) if length <= bits.width => {
+                if self.allow_host_bits || !bits.host_bits_set(length) {
+                    Some((address, length))
+                } else {
+                    None
+                }
+            }
+            _ => None,
+        }
+    }
+}
+
+impl Matches for PrefixTemplate {
+    fn matches(&self, value: &str) -> anyhow::Result<bool> {
+        Ok(self.parse_prefix(value).is_some())
+    }
+}
+
+// the standard library already formats addresses canonically: IPv6 is lowercased
+// and its longest run of zero groups compressed
+impl Normalise for AddressTemplate {
+    fn normalise(&self, value: &str) -> anyhow::Result<String> {
+        Ok(self
+            .family
+            .parse_address(value)
+            .map_or_else(|| value.to_owned(), |address| address.to_string()))
+    }
+}
+
+impl Normalise for PrefixTemplate {
+    fn normalise(&self, value: &str) -> anyhow::Result<String> {
+        Ok(self.parse_prefix(value).map_or_else(
+            || value.to_owned(),
+            |(address, length)| format!("{}/{}", address, length),
+        ))
+    }
+}
+
+impl Validate for AddressTemplate {
+    fn validate(&self, _schema: &Schema) -> anyhow::Result<()> {
+        Ok(())
+    }
+}
+
+impl Validate for PrefixTemplate {
+    fn validate(&self, _schema: &Schema) -> anyhow::Result<()> {
+        Ok(())
+    }
+}
+
+impl std::fmt::Display for IpFamily {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            IpFamily::Ipv4 => write!(f, "IPv4"),
+            IpFamily::Ipv6 => write!(f, "IPv6"),
+            IpFamily::Any => write!(f, "IPv4 or IPv6"),
+        }
+    }
+}
+
+impl std::fmt::Display for AddressTemplate {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "<{} address>", self.family)
+    }
+}
+
+impl std::fmt::Display for PrefixTemplate {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        if self.allow_host_bits {
+            write!(f, "<{} address/prefix length>", self.family)
+        } else {
+            write!(f, "<{} prefix>", self.family)
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn address() -> anyhow::Result<()> {
+        let v4 = AddressTemplate {
+            family: IpFamily::Ipv4,
+        };
+        let any = AddressTemplate {
+            family: IpFamily::Any,
+        };
+
+        assert!(v4.matches("192.0.2.1")?);
+        assert!(!v4.matches("192.0.2.256")?);
+        assert!(!v4.matches("2001:db8::1")?);
+        assert!(any.matches("2001:db8::1")?);
+        assert!(any.matches("192.0.2.1")?);
+        assert!(!any.matches("router")?);
+        Ok(())
+    }
+
+    #[test]
+    fn prefix() -> anyhow::Result<()> {
+        let strict = PrefixTemplate {
+            family: IpFamily::Any,
+            allow_host_bits: false,
+        };
+        let interface = PrefixTemplate {
+            family: IpFamily::Ipv4,
+            allow_host_bits: true,
+        };
+
+        assert!(strict.matches("192.0.2.0/24")?);
+        assert!(!strict.matches("192.0.2.1/24")?);
+        assert!(strict.matches("192.0.2.1/32")?);
+        assert!(strict.matches("0.0.0.0/0")?);
+        assert!(strict.matches("2001:db8::/32")?);
+        assert!(!strict.matches("2001:db8::1/64")?);
+        assert!(!strict.matches("192.0.2.0/33")?);
+        assert!(!strict.matches("192.0.2.0/+24")?);
+        assert!(!strict.matches("192.0.2.0")?);
+
+        assert!(interface.matches("192.0.2.1/24")?);
+        assert!(!interface.matches("2001:db8::1/64")?);
+        Ok(())
+    }
+
+    #[test]
+    fn normalise() -> anyhow::Result<()> {
+        let address = AddressTemplate {
+            family: IpFamily::Any,
+        };
+        let prefix = PrefixTemplate {
+            family: IpFamily::Any,
+            allow_host_bits: true,
+        };
+
+        assert_eq!(address.normalise("2001:DB8:0:0:0:0:0:1")?, "2001:db8::1");
+        assert_eq!(address.normalise("192.0.2.1")?, "192.0.2.1");
+        assert_eq!(prefix.normalise("2001:0db8::0001/064")?, "2001:db8::1/64");
+        assert_eq!(prefix.normalise("192.0.2.1/024")?, "192.0.2.1/24");
+        Ok(())
+    }
+}
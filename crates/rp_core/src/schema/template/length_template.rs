@@ -0,0 +1,69 @@
+use super::{Matches, Normalise, Schema, Validate};
+use crate::error;
+use serde::{Deserialize, Serialize};
+
+// any string with a number of characters within the limits
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq, Eq, Hash)]
+pub struct LengthTemplate {
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub min: Option<usize>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub max: Option<usize>,
+}
+
+impl Matches for LengthTemplate {
+    fn matches(&self, value: &str) -> anyhow::Result<bool> {
+        let length = value.chars().count();
+        Ok(length >= self.min.unwrap_or(0) && length <= self.max.unwrap_or(usize::MAX))
+    }
+}
+
+impl Normalise for LengthTemplate {
+    fn normalise(&self, value: &str) -> anyhow::Result<String> {
+        Ok(value.to_owned())
+    }
+}
+
+impl Validate for LengthTemplate {
+    fn validate(&self, _schema: &Schema) -> anyhow::Result<()> {
+        if self.min.unwrap_or(0) > self.max.unwrap_or(usize::MAX) {
+            Err(error::SchemaValidationError::Length {
+                min: self.min,
+                max: self.max,
+            }
+            .into())
+        } else {
+            Ok(())
+        }
+    }
+}
+
+impl std::fmt::Display for LengthTemplate {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match (self.min, self.max) {
+            (Some(min), Some(max)) => write!(f, "<{} to {} characters>", min, max),
+            (Some(min), None) => write!(f, "<at least {} characters>", min),
+            (None, Some(max)) => write!(f, "<at most {} characters>", max),
+            (None, None) => write!(f, "<any string>"),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn matches() -> anyhow::Result<()> {
+        let length = LengthTemplate {
+            min: Some(2),
+            max: Some(4),
+        };
+
+        assert!(length.matches("ab")?);
+        assert!(length.matches("äöüß")?);
+        assert!(!length.matches("a")?);
+        assert!(!length.matches("abcde")?);
+        Ok(())
+    }
+}
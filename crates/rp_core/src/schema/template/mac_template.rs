@@ -0,0 +1,68 @@
+use super::{Matches, Normalise, Schema, Validate};
+use serde::{Deserialize, Serialize};
+
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq, Eq, Hash)]
+pub struct MacTemplate {}
+
+impl Matches for MacTemplate {
+    fn matches(&self, value: &str) -> anyhow::Result<bool> {
+        // six groups of two hex digits, separated consistently by either colons or
+        // dashes
+        let separator = match value.chars().nth(2) {
+            Some(c) if c == ':' || c == '-' => c,
+            _ => return Ok(false),
+        };
+
+        let groups: Vec<&str> = value.split(separator).collect();
+        Ok(groups.len() == 6
+            && groups
+                .iter()
+                .all(|group| group.len() == 2 && group.chars().all(|c| c.is_ascii_hexdigit())))
+    }
+}
+
+// lowercase and colon separated, the way the kernel reports hardware addresses
+impl Normalise for MacTemplate {
+    fn normalise(&self, value: &str) -> anyhow::Result<String> {
+        Ok(value.to_ascii_lowercase().replace('-', ":"))
+    }
+}
+
+impl Validate for MacTemplate {
+    fn validate(&self, _schema: &Schema) -> anyhow::Result<()> {
+        Ok(())
+    }
+}
+
+impl std::fmt::Display for MacTemplate {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "<MAC address>")
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn matches() -> anyhow::Result<()> {
+        let mac = MacTemplate {};
+
+        assert!(mac.matches("11:22:33:44:55:66")?);
+        assert!(mac.matches("aa-BB-cc-DD-ee-FF")?);
+        assert!(!mac.matches("aa:bb-cc:dd:ee:ff")?);
+        assert!(!mac.matches("aa:bb:cc:dd:ee")?);
+        assert!(!mac.matches("aa:bb:cc:dd:ee:fg")?);
+        assert!(!mac.matches("aaa:bb:cc:dd:ee:ff")?);
+        Ok(())
+    }
+
+    #[test]
+    fn normalise() -> anyhow::Result<()> {
+        let mac = MacTemplate {};
+
+        assert_eq!(mac.normalise("AA-bb-CC-dd-EE-ff")?, "aa:bb:cc:dd:ee:ff");
+        assert_eq!(mac.normalise("11:22:33:44:55:66")?, "11:22:33:44:55:66");
+        Ok(())
+    }
+}
@@ -1,9 +1,23 @@
+mod hostname_template;
+mod ip_template;
+mod length_template;
+mod mac_template;
+mod password_template;
+mod reference_template;
 mod regex_template;
 
-use super::{value::range::Range, Matches, Schema, Validate};
+use super::{value::range::Range, Matches, Normalise, Schema, Validate};
+use crate::error;
+pub use hostname_template::HostnameTemplate;
+pub use ip_template::{AddressTemplate, IpFamily, PrefixTemplate};
+pub use length_template::LengthTemplate;
+pub use mac_template::MacTemplate;
+pub use password_template::PasswordTemplate;
+pub use reference_template::ReferenceTemplate;
 use regex_template::RegexTemplate;
 use rp_log::*;
 use serde::{Deserialize, Serialize};
+use std::{collections::HashMap, sync::Arc};
 
 #[derive(Serialize, Deserialize, Debug)]
 pub enum Template {
@@ -11,6 +25,28 @@ pub enum Template {
     Regex(Box<RegexTemplate>),
     #[serde(rename = "range")]
     Range(Range),
+    #[serde(rename = "address")]
+    Address(AddressTemplate),
+    #[serde(rename = "prefix")]
+    Prefix(PrefixTemplate),
+    #[serde(rename = "mac")]
+    Mac(MacTemplate),
+    #[serde(rename = "hostname")]
+    Hostname(HostnameTemplate),
+    #[serde(rename = "length")]
+    Length(LengthTemplate),
+    #[serde(rename = "password")]
+    Password(PasswordTemplate),
+    #[serde(rename = "template")]
+    Reference(ReferenceTemplate),
+    // matches values any of the templates matches
+    #[serde(rename = "any")]
+    Any(Vec<Template>),
+    // matches values every template matches
+    #[serde(rename = "all")]
+    All(Vec<Template>),
+    #[serde(rename = "not")]
+    Not(Box<Template>),
 }
 
 impl Matches for Template {
@@ -18,6 +54,65 @@ impl Matches for Template {
         match self {
             Template::Regex(regex) => regex.matches(value),
             Template::Range(range) => range.matches(value),
+            Template::Address(address) => address.matches(value),
+            Template::Prefix(prefix) => prefix.matches(value),
+            Template::Mac(mac) => mac.matches(value),
+            Template::Hostname(hostname) => hostname.matches(value),
+            Template::Length(length) => length.matches(value),
+            Template::Password(password) => password.matches(value),
+            Template::Reference(reference) => reference.matches(value),
+            Template::Any(templates) => {
+                for template in templates {
+                    if template.matches(value)? {
+                        return Ok(true);
+                    }
+                }
+                Ok(false)
+            }
+            Template::All(templates) => {
+                for template in templates {
+                    if !template.matches(value)? {
+                        return Ok(false);
+                    }
+                }
+                Ok(true)
+            }
+            Template::Not(template) => Ok(!template.matches(value)?),
+        }
+    }
+}
+
+impl Normalise for Template {
+    fn normalise(&self, value: &str) -> anyhow::Result<String> {
+        match self {
+            // a regex can't tell which of the strings it accepts are equivalent
+            Template::Regex(_) => Ok(value.to_owned()),
+            Template::Range(range) => range.normalise(value),
+            Template::Address(address) => address.normalise(value),
+            Template::Prefix(prefix) => prefix.normalise(value),
+            Template::Mac(mac) => mac.normalise(value),
+            Template::Hostname(hostname) => hostname.normalise(value),
+            Template::Length(length) => length.normalise(value),
+            Template::Password(password) => password.normalise(value),
+            Template::Reference(reference) => reference.normalise(value),
+            // the value is in the form of whichever template it matched first
+            Template::Any(templates) => {
+                for template in templates {
+                    if template.matches(value)? {
+                        return template.normalise(value);
+                    }
+                }
+                Ok(value.to_owned())
+            }
+            // every template has to agree on the value, so each gets to normalise it
+            Template::All(templates) => {
+                let mut value = value.to_owned();
+                for template in templates {
+                    value = template.normalise(&value)?;
+                }
+                Ok(value)
+            }
+            Template::Not(_) => Ok(value.to_owned()),
         }
     }
 }
@@ -33,7 +128,7 @@ impl Template {
                 }
             }
         } else {
-            warn!("Tried to load regex into non-regex template");
+            trace!("Tried to load regex into non-regex template");
             Ok(())
         }
     }
@@ -45,6 +140,51 @@ impl Template {
         }
     }
 
+    // references are resolved by name after the whole schema has been loaded
+    pub fn link(&self, templates: &HashMap<String, Arc<Template>>) {
+        match self {
+            Template::Reference(reference) => reference.link(templates),
+            Template::Any(inner) | Template::All(inner) => {
+                for template in inner {
+                    template.link(templates);
+                }
+            }
+            Template::Not(template) => template.link(templates),
+            _ => (),
+        }
+    }
+
+    // the names of the templates this one refers to directly
+    pub fn references(&self) -> Vec<&str> {
+        match self {
+            Template::Reference(reference) => vec![&reference.name],
+            Template::Any(inner) | Template::All(inner) => inner
+                .iter()
+                .flat_map(|template| template.references())
+                .collect(),
+            Template::Not(template) => template.references(),
+            _ => Vec::new(),
+        }
+    }
+
+    // whether the stored value is what the value was stored as, for values that
+    // don't normalise the same way twice
+    pub fn stored_as(&self, value: &str, stored: &str) -> anyhow::Result<bool> {
+        match self {
+            Template::Password(password) => Ok(password.verifies(value, stored)),
+            Template::Reference(reference) => reference.stored_as(value, stored),
+            Template::Any(templates) | Template::All(templates) => {
+                for template in templates {
+                    if template.stored_as(value, stored)? {
+                        return Ok(true);
+                    }
+                }
+                Ok(false)
+            }
+            _ => Ok(false),
+        }
+    }
+
     pub fn compiled_regex_size(&self) -> usize {
         match self {
             Template::Regex(regex) => regex.compiled_regex_size(),
@@ -58,6 +198,24 @@ impl Validate for Template {
         match self {
             Template::Regex(regex) => regex.validate(schema),
             Template::Range(range) => range.validate(schema),
+            Template::Address(address) => address.validate(schema),
+            Template::Prefix(prefix) => prefix.validate(schema),
+            Template::Mac(mac) => mac.validate(schema),
+            Template::Hostname(hostname) => hostname.validate(schema),
+            Template::Length(length) => length.validate(schema),
+            Template::Password(password) => password.validate(schema),
+            Template::Reference(reference) => reference.validate(schema),
+            Template::Any(templates) | Template::All(templates) => {
+                if templates.is_empty() {
+                    return Err(error::SchemaValidationError::EmptyTemplate.into());
+                }
+
+                for template in templates {
+                    template.validate(schema)?;
+                }
+                Ok(())
+            }
+            Template::Not(template) => template.validate(schema),
         }
     }
 }
@@ -67,6 +225,30 @@ impl std::fmt::Display for Template {
         match self {
             Template::Regex(regex_templ) => write!(f, "{}", regex_templ),
             Template::Range(range) => write!(f, "{}", range),
+            Template::Address(address) => write!(f, "{}", address),
+            Template::Prefix(prefix) => write!(f, "{}", prefix),
+            Template::Mac(mac) => write!(f, "{}", mac),
+            Template::Hostname(hostname) => write!(f, "{}", hostname),
+            Template::Length(length) => write!(f, "{}", length),
+            Template::Password(password) => write!(f, "{}", password),
+            Template::Reference(reference) => write!(f, "{}", reference),
+            Template::Any(templates) => write_joined(f, templates, " | "),
+            Template::All(templates) => write_joined(f, templates, " & "),
+            Template::Not(template) => write!(f, "not {}", template),
+        }
+    }
+}
+
+fn write_joined(
+    f: &mut std::fmt::Formatter<'_>,
+    templates: &[Template],
+    separator: &str,
+) -> std::fmt::Result {
+    for (index, template) in templates.iter().enumerate() {
+        if index > 0 {
+            write!(f, "{}", separator)?;
         }
+        write!(f, "{}", template)?;
     }
+    Ok(())
 }
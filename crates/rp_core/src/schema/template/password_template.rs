@@ -0,0 +1,76 @@
+use super::{Matches, Normalise, Schema, Validate};
+use pwhash::sha512_crypt;
+use serde::{Deserialize, Serialize};
+
+const HASH_PREFIX: &str = "$6$";
+const HASH_LENGTH: usize = 86;
+
+// any password, stored as a SHA-512 crypt hash the way /etc/shadow has them
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq, Eq, Hash)]
+pub struct PasswordTemplate {}
+
+// e.g. "$6$rounds=10000$salt$hash", the rounds being optional
+fn is_hash(value: &str) -> bool {
+    let crypt_char = |c: char| c.is_ascii_alphanumeric() || c == '.' || c == '/';
+
+    value.starts_with(HASH_PREFIX)
+        && value.rsplit('$').next().map_or(false, |hash| {
+            hash.len() == HASH_LENGTH && hash.chars().all(crypt_char)
+        })
+}
+
+impl PasswordTemplate {
+    // hashing the same password again gives another hash, so a stored one is found
+    // by checking the password against it
+    pub fn verifies(&self, value: &str, hash: &str) -> bool {
+        is_hash(hash) && sha512_crypt::verify(value, hash)
+    }
+}
+
+impl Matches for PasswordTemplate {
+    fn matches(&self, value: &str) -> anyhow::Result<bool> {
+        Ok(!value.is_empty())
+    }
+}
+
+// plaintext is hashed with a fresh salt while hashes are kept as they are, so a
+// loaded config compares equal to the one that was saved
+impl Normalise for PasswordTemplate {
+    fn normalise(&self, value: &str) -> anyhow::Result<String> {
+        if is_hash(value) {
+            Ok(value.to_owned())
+        } else {
+            Ok(sha512_crypt::hash(value)?)
+        }
+    }
+}
+
+impl Validate for PasswordTemplate {
+    fn validate(&self, _schema: &Schema) -> anyhow::Result<()> {
+        Ok(())
+    }
+}
+
+impl std::fmt::Display for PasswordTemplate {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "<password>")
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn normalise() -> anyhow::Result<()> {
+        let password = PasswordTemplate {};
+        let hash = password.normalise("hunter2")?;
+
+        assert!(is_hash(&hash));
+        assert!(sha512_crypt::verify("hunter2", &hash));
+        assert_eq!(password.normalise(&hash)?, hash);
+        assert!(password.verifies("hunter2", &hash));
+        assert!(!password.verifies("hunter3", &hash));
+        Ok(())
+    }
+}
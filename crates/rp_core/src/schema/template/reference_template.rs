@@ -0,0 +1,181 @@
+use super::{Matches, Normalise, Schema, Template, Validate};
+use crate::error::{self, LockError};
+use anyhow::anyhow;
+use serde::{
+    de::{self, Deserializer, Visitor},
+    ser::Serializer,
+    Deserialize, Serialize,
+};
+use std::{
+    cell::RefCell,
+    collections::{HashMap, HashSet},
+    fmt,
+    sync::{Arc, RwLock, Weak},
+};
+
+thread_local! {
+    // the references this thread is following, so a cycle that slipped past
+    // validation fails instead of overflowing the stack. other threads may be
+    // following the same ones at the same time
+    static FOLLOWING: RefCell<HashSet<usize>> = RefCell::new(HashSet::new());
+}
+
+// another template in the schema by name. the target is linked once every
+// template has been loaded
+#[derive(Debug)]
+pub struct ReferenceTemplate {
+    pub name: String,
+    target: RwLock<Weak<Template>>,
+}
+
+impl ReferenceTemplate {
+    pub fn link(&self, templates: &HashMap<String, Arc<Template>>) {
+        if let Some(target) = templates.get(&self.name) {
+            *self.target.write().expect("lock poisoned") = Arc::downgrade(target);
+        }
+    }
+
+    fn follow<T>(&self, f: impl FnOnce(&Template) -> anyhow::Result<T>) -> anyhow::Result<T> {
+        let target = self
+            .target
+            .read()
+            .map_err(|_| LockError)?
+            .upgrade()
+            .ok_or_else(|| anyhow!("Template '{}' used before being linked", self.name))?;
+
+        if !self.enter() {
+            return Err(
+                error::SchemaValidationError::TemplateCycle(vec![self.name.clone()]).into(),
+            );
+        }
+
+        let result = f(&target);
+        self.leave();
+        result
+    }
+
+    pub fn stored_as(&self, value: &str, stored: &str) -> anyhow::Result<bool> {
+        self.follow(|target| target.stored_as(value, stored))
+    }
+
+    // false if this thread is already following this reference
+    fn enter(&self) -> bool {
+        FOLLOWING.with(|following| following.borrow_mut().insert(self.address()))
+    }
+
+    fn leave(&self) {
+        FOLLOWING.with(|following| following.borrow_mut().remove(&self.address()));
+    }
+
+    fn address(&self) -> usize {
+        self as *const ReferenceTemplate as usize
+    }
+
+    // the names leading from this reference back to itself, if they do
+    fn find_cycle(
+        &self,
+        schema: &Schema,
+        name: &str,
+        path: &mut Vec<String>,
+        visited: &mut HashSet<String>,
+    ) -> bool {
+        path.push(name.to_owned());
+
+        if name == self.name && path.len() > 1 {
+            return true;
+        }
+
+        if visited.insert(name.to_owned()) {
+            if let Some(template) = schema.templates.get(name) {
+                for next in template.references() {
+                    if self.find_cycle(schema, next, path, visited) {
+                        return true;
+                    }
+                }
+            }
+        }
+
+        path.pop();
+        false
+    }
+}
+
+impl Matches for ReferenceTemplate {
+    fn matches(&self, value: &str) -> anyhow::Result<bool> {
+        self.follow(|target| target.matches(value))
+    }
+}
+
+impl Normalise for ReferenceTemplate {
+    fn normalise(&self, value: &str) -> anyhow::Result<String> {
+        self.follow(|target| target.normalise(value))
+    }
+}
+
+impl Validate for ReferenceTemplate {
+    fn validate(&self, schema: &Schema) -> anyhow::Result<()> {
+        if !schema.templates.contains_key(&self.name) {
+            return Err(error::SchemaValidationError::MissingTemplate(self.name.clone()).into());
+        }
+
+        let mut path = Vec::new();
+        if self.find_cycle(schema, &self.name, &mut path, &mut HashSet::new()) {
+            return Err(error::SchemaValidationError::TemplateCycle(path).into());
+        }
+
+        Ok(())
+    }
+}
+
+// this allows a reference be deserialised directly from the template's name
+struct ReferenceTemplateVisitor;
+impl<'de> Visitor<'de> for ReferenceTemplateVisitor {
+    type Value = ReferenceTemplate;
+
+    fn expecting(&self, formatter: &mut fmt::Formatter) -> fmt::Result {
+        formatter.write_str("a template name")
+    }
+
+    fn visit_str<E>(self, value: &str) -> Result<Self::Value, E>
+    where
+        E: de::Error,
+    {
+        Ok(ReferenceTemplate {
+            name: value.to_owned(),
+            target: RwLock::new(Weak::new()),
+        })
+    }
+}
+
+impl<'de> Deserialize<'de> for ReferenceTemplate {
+    fn deserialize<D>(deserializer: D) -> Result<ReferenceTemplate, D::Error>
+    where
+        D: Deserializer<'de>,
+    {
+        deserializer.deserialize_str(ReferenceTemplateVisitor)
+    }
+}
+
+impl Serialize for ReferenceTemplate {
+    fn serialize<S>(&self, serializer: S) -> Result<S::Ok, S::Error>
+    where
+        S: Serializer,
+    {
+        serializer.serialize_str(&self.name)
+    }
+}
+
+impl std::fmt::Display for ReferenceTemplate {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        let target = self.target.read().ok().and_then(|target| target.upgrade());
+
+        match target {
+            Some(target) if self.enter() => {
+                let result = write!(f, "{}", target);
+                self.leave();
+                result
+            }
+            _ => write!(f, "<{}>", self.name),
+        }
+    }
+}
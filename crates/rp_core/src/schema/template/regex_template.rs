@@ -1,5 +1,5 @@
 use super::{Matches, Schema, Validate};
-use crate::error;
+use crate::error::{self, LockError};
 use anyhow::anyhow;
 use regex_automata::{DenseDFA, DFA};
 use serde::{
@@ -7,12 +7,12 @@ use serde::{
     ser::Serializer,
     Deserialize, Serialize,
 };
-use std::{cell::RefCell, error::Error, fmt};
+use std::{error::Error, fmt, sync::RwLock};
 
 #[derive(Debug)]
 pub struct RegexTemplate {
     pub regex: String,
-    compiled_regex: RefCell<Option<DenseDFA<Vec<usize>, usize>>>,
+    compiled_regex: RwLock<Option<DenseDFA<Vec<usize>, usize>>>,
 }
 
 impl RegexTemplate {
@@ -20,7 +20,8 @@ impl RegexTemplate {
         self.compile_regex()?;
         let bytes = self
             .compiled_regex
-            .borrow()
+            .read()
+            .map_err(|_| LockError)?
             .as_ref()
             .ok_or_else(|| anyhow!("Compiled regex cache empty while serialising"))?
             .to_u16()?
@@ -29,20 +30,26 @@ impl RegexTemplate {
     }
 
     pub fn compile_regex(&self) -> anyhow::Result<()> {
-        if self.compiled_regex.borrow().is_none() {
-            *self.compiled_regex.borrow_mut() = Some(DenseDFA::new(&self.regex)?);
+        if self.compiled_regex.read().map_err(|_| LockError)?.is_some() {
+            return Ok(());
+        }
+
+        // another thread may have compiled it in the meantime
+        let mut compiled_regex = self.compiled_regex.write().map_err(|_| LockError)?;
+        if compiled_regex.is_none() {
+            *compiled_regex = Some(DenseDFA::new(&self.regex)?);
         }
         Ok(())
     }
 
     pub fn deserialise_regex(&self, bytes: &[u8]) -> anyhow::Result<()> {
         let dfa: DenseDFA<Vec<u16>, u16> = unsafe { DenseDFA::from_bytes(bytes).to_owned() };
-        *self.compiled_regex.borrow_mut() = Some(dfa.to_sized()?);
+        *self.compiled_regex.write().map_err(|_| LockError)? = Some(dfa.to_sized()?);
         Ok(())
     }
 
     pub fn compiled_regex_size(&self) -> usize {
-        match self.compiled_regex.borrow().as_ref() {
+        match self.compiled_regex.read().expect("lock poisoned").as_ref() {
             Some(dfa) => dfa.memory_usage(),
             None => 0,
         }
@@ -52,7 +59,7 @@ impl RegexTemplate {
 impl Matches for RegexTemplate {
     fn matches(&self, value: &str) -> anyhow::Result<bool> {
         self.compile_regex()?;
-        let regex_option = self.compiled_regex.borrow();
+        let regex_option = self.compiled_regex.read().map_err(|_| LockError)?;
         let regex = regex_option
             .as_ref()
             .ok_or_else(|| anyhow!("Compiled regex cache empty while checking match"))?;
@@ -64,7 +71,7 @@ impl Validate for RegexTemplate {
     fn validate(&self, _schema: &Schema) -> anyhow::Result<()> {
         match DenseDFA::new(&self.regex) {
             Ok(r) => {
-                *self.compiled_regex.borrow_mut() = Some(r);
+                *self.compiled_regex.write().map_err(|_| LockError)? = Some(r);
                 Ok(())
             }
             Err(e) => Err(error::SchemaValidationError::Regex {
@@ -91,7 +98,7 @@ impl<'de> Visitor<'de> for RegexTemplateVisitor {
     {
         Ok(RegexTemplate {
             regex: value.to_owned(),
-            compiled_regex: RefCell::new(None),
+            compiled_regex: RwLock::new(None),
         })
     }
 }
@@ -70,6 +70,33 @@ nodes:
     Schema::from_yaml_file(buf)
 }
 
+pub fn get_union_schema() -> anyhow::Result<Schema> {
+    let mut buf = buffer();
+    let schema = r#"---
+templates: {}
+nodes:
+  "system":
+    properties:
+      "hostname":
+        deletable: false
+        values:
+          - literal: localhost
+    subnodes:
+      "ntp":
+        properties:
+          "server":
+            multiple: true
+            default:
+              - literal: 2.pool.ntp.org
+            values:
+              - template: string"#;
+
+    write!(buf, "{}", schema)?;
+    buf.seek(SeekFrom::Start(0))?;
+
+    Schema::from_yaml_file(buf)
+}
+
 pub fn get_new_node_schema() -> anyhow::Result<Schema> {
     let mut buf = buffer();
     let schema = r#"---
@@ -323,3 +350,485 @@ nodes:
 
     Schema::from_yaml_file(buf)
 }
+
+pub fn get_invalid_migration_version_schema() -> anyhow::Result<Schema> {
+    let mut buf = buffer();
+    let schema = r#"---
+version: 1
+migrations:
+  - version: 2
+    steps: []
+templates: {}
+nodes: {}"#;
+
+    write!(buf, "{}", schema)?;
+    buf.seek(SeekFrom::Start(0))?;
+
+    Schema::from_yaml_file(buf)
+}
+
+pub fn get_invalid_migration_path_schema() -> anyhow::Result<Schema> {
+    let mut buf = buffer();
+    let schema = r#"---
+version: 1
+migrations:
+  - version: 1
+    steps:
+      - move_node:
+          path: [node, child]
+          to: [node, "*"]
+templates: {}
+nodes: {}"#;
+
+    write!(buf, "{}", schema)?;
+    buf.seek(SeekFrom::Start(0))?;
+
+    Schema::from_yaml_file(buf)
+}
+
+pub fn get_invalid_singlenode_prop_value_reference_schema() -> anyhow::Result<Schema> {
+    let mut buf = buffer();
+    let schema = r#"---
+templates: {}
+nodes:
+  "single":
+    subnodes:
+      "child": {}
+  "invalid":
+    properties:
+      "invalid":
+        values:
+          - reference: [single, child]"#;
+
+    write!(buf, "{}", schema)?;
+    buf.seek(SeekFrom::Start(0))?;
+
+    Schema::from_yaml_file(buf)
+}
+
+pub fn get_valid_condition_schema() -> anyhow::Result<Schema> {
+    let mut buf = buffer();
+    let schema = r#"---
+templates:
+  "string":
+    regex: ".*"
+nodes:
+  "interfaces":
+    source:
+      id: interface
+      template: string
+    node:
+      subnodes:
+        "vlan":
+          source:
+            id: vlan
+            template: string
+          node:
+            properties:
+              "mtu":
+                must:
+                  - property: ["..", "..", address]
+                    value: dhcp
+                values:
+                  - template: string
+      properties:
+        "address":
+          values:
+            - literal: dhcp
+            - literal: none"#;
+
+    write!(buf, "{}", schema)?;
+    buf.seek(SeekFrom::Start(0))?;
+
+    Schema::from_yaml_file(buf)
+}
+
+pub fn get_reference_values_schema() -> anyhow::Result<Schema> {
+    let mut buf = buffer();
+    let schema = r#"---
+templates:
+  "digit":
+    regex: "[0-9]"
+nodes:
+  "interfaces":
+    source:
+      id: interface
+      template: digit
+    node: {}
+  "route":
+    properties:
+      "interface":
+        values:
+          - literal: blackhole
+          - template: digit
+          - reference: [interfaces]
+      "metric":
+        values:
+          - literal: auto
+          - template: digit"#;
+
+    write!(buf, "{}", schema)?;
+    buf.seek(SeekFrom::Start(0))?;
+
+    Schema::from_yaml_file(buf)
+}
+
+pub fn get_invalid_condition_property_schema() -> anyhow::Result<Schema> {
+    let mut buf = buffer();
+    let schema = r#"---
+templates:
+  "string":
+    regex: ".*"
+nodes:
+  "interfaces":
+    source:
+      id: interface
+      template: string
+    node:
+      subnodes:
+        "vlan":
+          source:
+            id: vlan
+            template: string
+          node:
+            properties:
+              "mtu":
+                must:
+                  - property: ["..", "..", nonexistent]
+                values:
+                  - template: string
+      properties:
+        "address":
+          values:
+            - literal: dhcp
+            - literal: none"#;
+
+    write!(buf, "{}", schema)?;
+    buf.seek(SeekFrom::Start(0))?;
+
+    Schema::from_yaml_file(buf)
+}
+
+pub fn get_invalid_condition_value_schema() -> anyhow::Result<Schema> {
+    let mut buf = buffer();
+    let schema = r#"---
+templates:
+  "string":
+    regex: ".*"
+nodes:
+  "interfaces":
+    source:
+      id: interface
+      template: string
+    node:
+      subnodes:
+        "vlan":
+          source:
+            id: vlan
+            template: string
+          node:
+            properties:
+              "mtu":
+                must:
+                  - property: ["..", "..", address]
+                    value: static
+                values:
+                  - template: string
+      properties:
+        "address":
+          values:
+            - literal: dhcp
+            - literal: none"#;
+
+    write!(buf, "{}", schema)?;
+    buf.seek(SeekFrom::Start(0))?;
+
+    Schema::from_yaml_file(buf)
+}
+
+pub fn get_invalid_condition_parent_schema() -> anyhow::Result<Schema> {
+    let mut buf = buffer();
+    let schema = r#"---
+templates:
+  "string":
+    regex: ".*"
+nodes:
+  "interfaces":
+    source:
+      id: interface
+      template: string
+    node:
+      subnodes:
+        "vlan":
+          source:
+            id: vlan
+            template: string
+          node:
+            properties:
+              "mtu":
+                must:
+                  - property: ["..", "..", "..", "..", "..", address]
+                values:
+                  - template: string
+      properties:
+        "address":
+          values:
+            - literal: dhcp
+            - literal: none"#;
+
+    write!(buf, "{}", schema)?;
+    buf.seek(SeekFrom::Start(0))?;
+
+    Schema::from_yaml_file(buf)
+}
+
+pub fn get_invalid_prop_element_count_schema() -> anyhow::Result<Schema> {
+    let mut buf = buffer();
+    let schema = r#"---
+templates:
+  "string":
+    regex: ".*"
+nodes:
+  "invalid":
+    properties:
+      "invalid":
+        multiple: true
+        min: 3
+        max: 2
+        values:
+          - template: string"#;
+
+    write!(buf, "{}", schema)?;
+    buf.seek(SeekFrom::Start(0))?;
+
+    Schema::from_yaml_file(buf)
+}
+
+pub fn get_invalid_single_prop_element_count_schema() -> anyhow::Result<Schema> {
+    let mut buf = buffer();
+    let schema = r#"---
+templates:
+  "string":
+    regex: ".*"
+nodes:
+  "invalid":
+    properties:
+      "invalid":
+        max: 2
+        values:
+          - template: string"#;
+
+    write!(buf, "{}", schema)?;
+    buf.seek(SeekFrom::Start(0))?;
+
+    Schema::from_yaml_file(buf)
+}
+
+pub fn get_invalid_prop_default_count_schema() -> anyhow::Result<Schema> {
+    let mut buf = buffer();
+    let schema = r#"---
+templates:
+  "string":
+    regex: ".*"
+nodes:
+  "invalid":
+    properties:
+      "invalid":
+        multiple: true
+        max: 1
+        default:
+          - literal: a
+          - literal: b
+        values:
+          - template: string"#;
+
+    write!(buf, "{}", schema)?;
+    buf.seek(SeekFrom::Start(0))?;
+
+    Schema::from_yaml_file(buf)
+}
+
+pub fn get_invalid_multinode_element_count_schema() -> anyhow::Result<Schema> {
+    let mut buf = buffer();
+    let schema = r#"---
+templates:
+  "string":
+    regex: ".*"
+nodes:
+  "invalid":
+    min: 2
+    max: 1
+    source:
+      id: invalid
+      template: string
+    node:
+      properties:
+        "invalid":
+          values:
+            - template: string"#;
+
+    write!(buf, "{}", schema)?;
+    buf.seek(SeekFrom::Start(0))?;
+
+    Schema::from_yaml_file(buf)
+}
+
+pub fn get_invalid_ordered_single_prop_schema() -> anyhow::Result<Schema> {
+    let mut buf = buffer();
+    let schema = r#"---
+templates:
+  "string":
+    regex: ".*"
+nodes:
+  "invalid":
+    properties:
+      "invalid":
+        ordered: true
+        values:
+          - template: string"#;
+
+    write!(buf, "{}", schema)?;
+    buf.seek(SeekFrom::Start(0))?;
+
+    Schema::from_yaml_file(buf)
+}
+
+// ordered without being multiple, and with a default that isn't allowed
+pub fn get_invalid_property_two_errors_schema() -> anyhow::Result<Schema> {
+    let mut buf = buffer();
+    let schema = r#"---
+templates: {}
+nodes:
+  "invalid":
+    properties:
+      "invalid":
+        ordered: true
+        default:
+          - literal: other
+        values:
+          - literal: allowed"#;
+
+    write!(buf, "{}", schema)?;
+    buf.seek(SeekFrom::Start(0))?;
+
+    Schema::from_yaml_file(buf)
+}
+
+pub fn get_several_errors_schema() -> anyhow::Result<Schema> {
+    let mut buf = buffer();
+    let schema = r#"---
+templates:
+  "string":
+    regex: ".*"
+nodes:
+  "system":
+    properties:
+      "hostname":
+        values: []
+    subnodes:
+      "ntp":
+        properties:
+          "server":
+            values:
+              - template: missing
+  "interfaces":
+    source:
+      id: name
+    node:
+      properties:
+        "mtu":
+          ordered: true
+          values:
+            - template: string"#;
+
+    write!(buf, "{}", schema)?;
+    buf.seek(SeekFrom::Start(0))?;
+
+    Schema::from_yaml_file(buf)
+}
+
+pub fn get_template_cycle_schema() -> anyhow::Result<Schema> {
+    let mut buf = buffer();
+    let schema = r#"---
+templates:
+  "a":
+    any:
+      - template: b
+      - hostname: {}
+  "b":
+    not:
+      template: a
+nodes: {}"#;
+
+    write!(buf, "{}", schema)?;
+    buf.seek(SeekFrom::Start(0))?;
+
+    Schema::from_yaml_file(buf)
+}
+
+pub fn get_missing_template_reference_schema() -> anyhow::Result<Schema> {
+    let mut buf = buffer();
+    let schema = r#"---
+templates:
+  "name":
+    all:
+      - template: missing
+      - length:
+          max: 16
+nodes: {}"#;
+
+    write!(buf, "{}", schema)?;
+    buf.seek(SeekFrom::Start(0))?;
+
+    Schema::from_yaml_file(buf)
+}
+
+pub fn get_invalid_length_template_schema() -> anyhow::Result<Schema> {
+    let mut buf = buffer();
+    let schema = r#"---
+templates:
+  "name":
+    length:
+      min: 8
+      max: 4
+nodes: {}"#;
+
+    write!(buf, "{}", schema)?;
+    buf.seek(SeekFrom::Start(0))?;
+
+    Schema::from_yaml_file(buf)
+}
+
+pub fn get_empty_composite_template_schema() -> anyhow::Result<Schema> {
+    let mut buf = buffer();
+    let schema = r#"---
+templates:
+  "name":
+    any: []
+nodes: {}"#;
+
+    write!(buf, "{}", schema)?;
+    buf.seek(SeekFrom::Start(0))?;
+
+    Schema::from_yaml_file(buf)
+}
+
+pub fn get_invalid_range_step_schema() -> anyhow::Result<Schema> {
+    let mut buf = buffer();
+    let schema = r#"---
+templates:
+  "vid":
+    range:
+      lower:
+        exclusive: 0
+      upper:
+        exclusive: 4095
+      integer: true
+      step: 0.5
+nodes: {}"#;
+
+    write!(buf, "{}", schema)?;
+    buf.seek(SeekFrom::Start(0))?;
+
+    Schema::from_yaml_file(buf)
+}
@@ -0,0 +1,39 @@
+use super::*;
+
+#[test]
+fn invalid_migration_version() -> anyhow::Result<()> {
+    let mut schema = common::get_invalid_migration_version_schema()?;
+    let result = schema.validate();
+
+    match result {
+        Ok(_) => Err(anyhow!("migration validation succeeded")),
+        Err(e) => {
+            if let Some(crate::error::SchemaValidationError::MigrationVersion { .. }) =
+                validation_error(&e)
+            {
+                Ok(())
+            } else {
+                Err(e)
+            }
+        }
+    }
+}
+
+#[test]
+fn invalid_migration_path() -> anyhow::Result<()> {
+    let mut schema = common::get_invalid_migration_path_schema()?;
+    let result = schema.validate();
+
+    match result {
+        Ok(_) => Err(anyhow!("migration validation succeeded")),
+        Err(e) => {
+            if let Some(crate::error::SchemaValidationError::MigrationPath(_)) =
+                validation_error(&e)
+            {
+                Ok(())
+            } else {
+                Err(e)
+            }
+        }
+    }
+}
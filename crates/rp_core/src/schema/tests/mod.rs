@@ -1,10 +1,21 @@
 mod common;
+mod migration;
 mod property;
 mod template;
 
 use super::*;
+use crate::error::SchemaErrors;
 use anyhow::anyhow;
 
+// the first error of the given type among those validation reported
+fn validation_error<E>(e: &anyhow::Error) -> Option<&E>
+where
+    E: std::fmt::Display + std::fmt::Debug + Send + Sync + 'static,
+{
+    e.downcast_ref::<SchemaErrors>()
+        .and_then(|errors| errors.find())
+}
+
 #[test]
 fn schema_from_yaml() -> anyhow::Result<()> {
     common::get_valid_schema()?;
@@ -36,11 +47,11 @@ fn invalid_regex_cache() -> anyhow::Result<()> {
 #[test]
 fn find_existing_node() -> anyhow::Result<()> {
     let schema = common::get_valid_schema()?;
-    let locator = Rc::new(NodeLocator::new(
+    let locator = Arc::new(NodeLocator::new(
         String::from("ntp"),
-        Some(Rc::new(NodeLocator::new(
+        Some(Arc::new(NodeLocator::new(
             String::from("system"),
-            Some(Rc::new(NodeLocator::new(String::from("schema"), None))),
+            Some(Arc::new(NodeLocator::new(String::from("schema"), None))),
         ))),
     ));
 
@@ -53,11 +64,11 @@ fn find_existing_node() -> anyhow::Result<()> {
 #[test]
 fn find_nonexistent_node() -> anyhow::Result<()> {
     let schema = common::get_valid_schema()?;
-    let locator = Rc::new(NodeLocator::new(
+    let locator = Arc::new(NodeLocator::new(
         String::from("nonexistent"),
-        Some(Rc::new(NodeLocator::new(
+        Some(Arc::new(NodeLocator::new(
             String::from("system"),
-            Some(Rc::new(NodeLocator::new(String::from("schema"), None))),
+            Some(Arc::new(NodeLocator::new(String::from("schema"), None))),
         ))),
     ));
 
@@ -162,9 +173,9 @@ fn merge_ours_node() -> anyhow::Result<()> {
     let new_schema = common::get_merge_node_schema()?;
 
     schema.merge(new_schema, MergingStrategy::Ours)?;
-    let locator = Rc::new(NodeLocator::new(
+    let locator = Arc::new(NodeLocator::new(
         String::from("system"),
-        Some(Rc::new(NodeLocator::new(String::from("schema"), None))),
+        Some(Arc::new(NodeLocator::new(String::from("schema"), None))),
     ));
 
     if let Some(node) = schema.find_node(locator) {
@@ -192,9 +203,9 @@ fn merge_theirs_node() -> anyhow::Result<()> {
     let new_schema = common::get_merge_node_schema()?;
 
     schema.merge(new_schema, MergingStrategy::Theirs)?;
-    let locator = Rc::new(NodeLocator::new(
+    let locator = Arc::new(NodeLocator::new(
         String::from("system"),
-        Some(Rc::new(NodeLocator::new(String::from("schema"), None))),
+        Some(Arc::new(NodeLocator::new(String::from("schema"), None))),
     ));
 
     if let Some(node) = schema.find_node(locator) {
@@ -222,9 +233,9 @@ fn merge_new_node() -> anyhow::Result<()> {
     let new_schema = common::get_new_node_schema()?;
 
     schema.merge(new_schema, MergingStrategy::Error)?;
-    let locator = Rc::new(NodeLocator::new(
+    let locator = Arc::new(NodeLocator::new(
         String::from("interfaces"),
-        Some(Rc::new(NodeLocator::new(String::from("schema"), None))),
+        Some(Arc::new(NodeLocator::new(String::from("schema"), None))),
     ));
 
     if let Some(node) = schema.find_node(locator) {
@@ -237,3 +248,148 @@ fn merge_new_node() -> anyhow::Result<()> {
         Err(anyhow!("interfaces node not in schema"))
     }
 }
+
+#[test]
+fn merge_union_sets() -> anyhow::Result<()> {
+    let mut schema = common::get_valid_schema()?;
+    let new_schema = common::get_union_schema()?;
+
+    schema.merge(new_schema, MergingStrategy::Union)?;
+    let system = match schema.find_node_at(&[String::from("system")]) {
+        Some(SchemaNode::SingleSchemaNode(system)) => system,
+        _ => return Err(anyhow!("system node not a SingleSchemaNode")),
+    };
+    let server = match schema.find_node_at(&[String::from("system"), String::from("ntp")]) {
+        Some(SchemaNode::SingleSchemaNode(ntp)) => ntp
+            .properties
+            .get("server")
+            .ok_or_else(|| anyhow!("server property not in ntp node"))?,
+        _ => return Err(anyhow!("ntp node not a SingleSchemaNode")),
+    };
+
+    let hostname = system
+        .properties
+        .get("hostname")
+        .ok_or_else(|| anyhow!("hostname property not in system node"))?;
+    if hostname.values.len() != 2 {
+        Err(anyhow!("hostname values weren't combined"))
+    } else if server.default.len() != 2 {
+        Err(anyhow!("server defaults weren't combined"))
+    } else {
+        Ok(())
+    }
+}
+
+#[test]
+fn merge_conflict_path() -> anyhow::Result<()> {
+    let mut schema = common::get_valid_schema()?;
+    let new_schema = common::get_merge_node_schema()?;
+
+    match schema.merge(new_schema, MergingStrategy::Union) {
+        Ok(_) => Err(anyhow!("merging a conflicting property succeeded")),
+        Err(e) => match e.downcast_ref() {
+            Some(crate::error::MergeError::Conflict { path, .. })
+                if path == "system hostname deletable" =>
+            {
+                Ok(())
+            }
+            _ => Err(e),
+        },
+    }
+}
+
+#[test]
+fn dry_run_merge_conflicts() -> anyhow::Result<()> {
+    let mut new_schema = common::get_merge_node_schema()?;
+    new_schema.merge(common::get_merge_template_schema()?, MergingStrategy::Error)?;
+
+    let mut schema = common::get_valid_schema()?;
+    let mut conflicts: Vec<String> = schema
+        .dry_run_merge(&new_schema, MergingStrategy::Error)
+        .iter()
+        .map(|conflict| conflict.path.join(" "))
+        .collect();
+    conflicts.sort();
+
+    if conflicts != ["system hostname deletable", "templates string"] {
+        return Err(anyhow!("unexpected conflicts: {:?}", conflicts));
+    }
+
+    // neither schema is touched, so the conflicts can then be resolved by merging
+    // them for real
+    let deletable = |schema: &Schema| match &*schema.nodes["system"] {
+        SchemaNode::SingleSchemaNode(system) => Ok(system.properties["hostname"].deletable),
+        _ => Err(anyhow!("'system' isn't a single node")),
+    };
+    if !schema
+        .dry_run_merge(&new_schema, MergingStrategy::Theirs)
+        .is_empty()
+        || deletable(&schema)?
+    {
+        return Err(anyhow!("dry run changed the schema"));
+    }
+
+    schema.merge(new_schema, MergingStrategy::Theirs)?;
+    if deletable(&schema)? {
+        Ok(())
+    } else {
+        Err(anyhow!("merging after the dry run didn't merge"))
+    }
+}
+
+#[test]
+fn all_errors_reported() -> anyhow::Result<()> {
+    let mut schema = common::get_several_errors_schema()?;
+    let e = match schema.validate() {
+        Ok(_) => return Err(anyhow!("schema validation succeeded")),
+        Err(e) => e,
+    };
+
+    let errors = e
+        .downcast_ref::<SchemaErrors>()
+        .ok_or_else(|| anyhow!("errors not collected: {:#}", e))?;
+    let mut paths: Vec<String> = errors
+        .errors
+        .iter()
+        .map(|error| error.location.path.join(" "))
+        .collect();
+    paths.sort();
+
+    if paths
+        == vec![
+            "interfaces",
+            "interfaces template mtu",
+            "system hostname",
+            "system ntp server",
+        ]
+    {
+        Ok(())
+    } else {
+        Err(anyhow!("wrong errors reported: {:?}", paths))
+    }
+}
+
+#[test]
+fn error_position_in_yaml() -> anyhow::Result<()> {
+    let mut schema = common::get_several_errors_schema()?;
+    let e = match schema.validate() {
+        Ok(_) => return Err(anyhow!("schema validation succeeded")),
+        Err(e) => e,
+    };
+
+    let errors = e
+        .downcast_ref::<SchemaErrors>()
+        .ok_or_else(|| anyhow!("errors not collected: {:#}", e))?;
+    let location = errors
+        .errors
+        .iter()
+        .map(|error| &error.location)
+        .find(|location| location.path.join(" ") == "interfaces template mtu")
+        .ok_or_else(|| anyhow!("mtu error not reported"))?;
+
+    if (location.line, location.column) == (Some(21), Some(9)) {
+        Ok(())
+    } else {
+        Err(anyhow!("wrong position: {}", location))
+    }
+}
@@ -8,7 +8,7 @@ fn invalid_property_no_values() -> anyhow::Result<()> {
     match result {
         Ok(_) => Err(anyhow!("property validation succeeded")),
         Err(e) => {
-            if let Some(crate::error::SchemaValidationError::NoValues) = e.downcast_ref() {
+            if let Some(crate::error::SchemaValidationError::NoValues) = validation_error(&e) {
                 Ok(())
             } else {
                 Err(e)
@@ -25,7 +25,8 @@ fn invalid_property_value_template_missing() -> anyhow::Result<()> {
     match result {
         Ok(_) => Err(anyhow!("property validation succeeded")),
         Err(e) => {
-            if let Some(crate::error::SchemaValidationError::MissingTemplate(_)) = e.downcast_ref()
+            if let Some(crate::error::SchemaValidationError::MissingTemplate(_)) =
+                validation_error(&e)
             {
                 Ok(())
             } else {
@@ -43,7 +44,7 @@ fn invalid_property_value_range() -> anyhow::Result<()> {
     match result {
         Ok(_) => Err(anyhow!("property validation succeeded")),
         Err(e) => {
-            if let Some(crate::error::SchemaValidationError::Range { .. }) = e.downcast_ref() {
+            if let Some(crate::error::SchemaValidationError::Range { .. }) = validation_error(&e) {
                 Ok(())
             } else {
                 Err(e)
@@ -61,7 +62,7 @@ fn invalid_property_multiple_default() -> anyhow::Result<()> {
         Ok(_) => Err(anyhow!("property validation succeeded")),
         Err(e) => {
             if let Some(crate::error::SchemaValidationError::NoMultipleValuesAllowed) =
-                e.downcast_ref()
+                validation_error(&e)
             {
                 Ok(())
             } else {
@@ -79,7 +80,8 @@ fn invalid_property_invalid_default_literal() -> anyhow::Result<()> {
     match result {
         Ok(_) => Err(anyhow!("property validation succeeded")),
         Err(e) => {
-            if let Some(error::SchemaValidationError::InvalidDefaultValue(_)) = e.downcast_ref() {
+            if let Some(error::SchemaValidationError::InvalidDefaultValue(_)) = validation_error(&e)
+            {
                 Ok(())
             } else {
                 Err(e)
@@ -96,7 +98,8 @@ fn invalid_property_invalid_default_template() -> anyhow::Result<()> {
     match result {
         Ok(_) => Err(anyhow!("property validation succeeded")),
         Err(e) => {
-            if let Some(error::SchemaValidationError::InvalidDefaultValue(_)) = e.downcast_ref() {
+            if let Some(error::SchemaValidationError::InvalidDefaultValue(_)) = validation_error(&e)
+            {
                 Ok(())
             } else {
                 Err(e)
@@ -113,7 +116,158 @@ fn invalid_property_invalid_default_range() -> anyhow::Result<()> {
     match result {
         Ok(_) => Err(anyhow!("property validation succeeded")),
         Err(e) => {
-            if let Some(error::SchemaValidationError::InvalidDefaultValue(_)) = e.downcast_ref() {
+            if let Some(error::SchemaValidationError::InvalidDefaultValue(_)) = validation_error(&e)
+            {
+                Ok(())
+            } else {
+                Err(e)
+            }
+        }
+    }
+}
+
+#[test]
+fn invalid_property_value_reference() -> anyhow::Result<()> {
+    let mut schema = common::get_invalid_singlenode_prop_value_reference_schema()?;
+    let result = schema.validate();
+
+    match result {
+        Ok(_) => Err(anyhow!("property validation succeeded")),
+        Err(e) => {
+            if let Some(crate::error::SchemaValidationError::InvalidReference(_)) =
+                validation_error(&e)
+            {
+                Ok(())
+            } else {
+                Err(e)
+            }
+        }
+    }
+}
+
+#[test]
+fn valid_property_condition() -> anyhow::Result<()> {
+    let mut schema = common::get_valid_condition_schema()?;
+    schema.validate()
+}
+
+#[test]
+fn allows_through_reference() -> anyhow::Result<()> {
+    let mut schema = common::get_reference_values_schema()?;
+    schema.build_regex_cache()?;
+    let route = match schema.find_node_at(&[String::from("route")]) {
+        Some(SchemaNode::SingleSchemaNode(route)) => route,
+        _ => return Err(anyhow!("no 'route' node")),
+    };
+    let property = |key: &str| {
+        route
+            .properties
+            .get(key)
+            .ok_or_else(|| anyhow!("no '{}' property", key))
+    };
+
+    // any value may name a referenced node, but the other values are still
+    // checked
+    for value in &["blackhole", "1", "lan"] {
+        if !property("interface")?.allows(value, &schema)? {
+            return Err(anyhow!("'{}' not allowed", value));
+        }
+    }
+
+    if property("metric")?.allows("lan", &schema)? {
+        Err(anyhow!("value allowed without a reference"))
+    } else {
+        Ok(())
+    }
+}
+
+#[test]
+fn invalid_property_condition_property() -> anyhow::Result<()> {
+    let mut schema = common::get_invalid_condition_property_schema()?;
+    let result = schema.validate();
+
+    match result {
+        Ok(_) => Err(anyhow!("property validation succeeded")),
+        Err(e) => {
+            if let Some(crate::error::SchemaValidationError::InvalidCondition { .. }) =
+                validation_error(&e)
+            {
+                Ok(())
+            } else {
+                Err(e)
+            }
+        }
+    }
+}
+
+#[test]
+fn invalid_property_condition_value() -> anyhow::Result<()> {
+    let mut schema = common::get_invalid_condition_value_schema()?;
+    let result = schema.validate();
+
+    match result {
+        Ok(_) => Err(anyhow!("property validation succeeded")),
+        Err(e) => {
+            if let Some(crate::error::SchemaValidationError::InvalidCondition { .. }) =
+                validation_error(&e)
+            {
+                Ok(())
+            } else {
+                Err(e)
+            }
+        }
+    }
+}
+
+#[test]
+fn invalid_property_condition_parent() -> anyhow::Result<()> {
+    let mut schema = common::get_invalid_condition_parent_schema()?;
+    let result = schema.validate();
+
+    match result {
+        Ok(_) => Err(anyhow!("property validation succeeded")),
+        Err(e) => {
+            if let Some(crate::error::SchemaValidationError::InvalidCondition { .. }) =
+                validation_error(&e)
+            {
+                Ok(())
+            } else {
+                Err(e)
+            }
+        }
+    }
+}
+
+#[test]
+fn invalid_property_element_count() -> anyhow::Result<()> {
+    let mut schema = common::get_invalid_prop_element_count_schema()?;
+    let result = schema.validate();
+
+    match result {
+        Ok(_) => Err(anyhow!("element count validation succeeded")),
+        Err(e) => {
+            if let Some(crate::error::SchemaValidationError::ElementCount { .. }) =
+                validation_error(&e)
+            {
+                Ok(())
+            } else {
+                Err(e)
+            }
+        }
+    }
+}
+
+#[test]
+fn invalid_single_property_element_count() -> anyhow::Result<()> {
+    let mut schema = common::get_invalid_single_prop_element_count_schema()?;
+    let result = schema.validate();
+
+    match result {
+        Ok(_) => Err(anyhow!("element count validation succeeded")),
+        Err(e) => {
+            if let Some(crate::error::SchemaValidationError::ElementCount { .. }) =
+                validation_error(&e)
+            {
                 Ok(())
             } else {
                 Err(e)
@@ -121,3 +275,93 @@ fn invalid_property_invalid_default_range() -> anyhow::Result<()> {
         }
     }
 }
+
+#[test]
+fn invalid_property_default_count() -> anyhow::Result<()> {
+    let mut schema = common::get_invalid_prop_default_count_schema()?;
+    let result = schema.validate();
+
+    match result {
+        Ok(_) => Err(anyhow!("element count validation succeeded")),
+        Err(e) => {
+            if let Some(crate::error::SchemaValidationError::DefaultCount { .. }) =
+                validation_error(&e)
+            {
+                Ok(())
+            } else {
+                Err(e)
+            }
+        }
+    }
+}
+
+#[test]
+fn invalid_multinode_element_count() -> anyhow::Result<()> {
+    let mut schema = common::get_invalid_multinode_element_count_schema()?;
+    let result = schema.validate();
+
+    match result {
+        Ok(_) => Err(anyhow!("element count validation succeeded")),
+        Err(e) => {
+            if let Some(crate::error::SchemaValidationError::ElementCount { .. }) =
+                validation_error(&e)
+            {
+                Ok(())
+            } else {
+                Err(e)
+            }
+        }
+    }
+}
+
+#[test]
+fn invalid_ordered_single_property() -> anyhow::Result<()> {
+    let mut schema = common::get_invalid_ordered_single_prop_schema()?;
+    let result = schema.validate();
+
+    match result {
+        Ok(_) => Err(anyhow!(
+            "ordered single value property validation succeeded"
+        )),
+        Err(e) => {
+            if let Some(crate::error::SchemaValidationError::OrderedSingleValue) =
+                validation_error(&e)
+            {
+                Ok(())
+            } else {
+                Err(e)
+            }
+        }
+    }
+}
+
+#[test]
+fn all_property_errors_reported() -> anyhow::Result<()> {
+    let mut schema = common::get_invalid_property_two_errors_schema()?;
+    let e = match schema.validate() {
+        Ok(_) => return Err(anyhow!("property validation succeeded")),
+        Err(e) => e,
+    };
+
+    let errors = e
+        .downcast_ref::<SchemaErrors>()
+        .ok_or_else(|| anyhow!("errors not collected: {:#}", e))?;
+    let ordered = errors.errors.iter().any(|error| {
+        matches!(
+            error.error.downcast_ref(),
+            Some(crate::error::SchemaValidationError::OrderedSingleValue)
+        )
+    });
+    let default = errors.errors.iter().any(|error| {
+        matches!(
+            error.error.downcast_ref(),
+            Some(crate::error::SchemaValidationError::InvalidDefaultValue(_))
+        )
+    });
+
+    if ordered && default && errors.errors.len() == 2 {
+        Ok(())
+    } else {
+        Err(anyhow!("wrong errors reported: {:#}", e))
+    }
+}
@@ -9,7 +9,7 @@ fn invalid_regex_template() -> anyhow::Result<()> {
     match result {
         Ok(_) => Err(anyhow!("template validation succeeded")),
         Err(e) => {
-            if let Some(crate::error::SchemaValidationError::Regex { .. }) = e.downcast_ref() {
+            if let Some(crate::error::SchemaValidationError::Regex { .. }) = validation_error(&e) {
                 Ok(())
             } else {
                 Err(e)
@@ -26,7 +26,7 @@ fn invalid_incl_incl_range_template() -> anyhow::Result<()> {
     match result {
         Ok(_) => Err(anyhow!("template validation succeeded")),
         Err(e) => {
-            if let Some(crate::error::SchemaValidationError::Range { .. }) = e.downcast_ref() {
+            if let Some(crate::error::SchemaValidationError::Range { .. }) = validation_error(&e) {
                 Ok(())
             } else {
                 Err(e)
@@ -43,7 +43,7 @@ fn invalid_excl_incl_range_template() -> anyhow::Result<()> {
     match result {
         Ok(_) => Err(anyhow!("template validation succeeded")),
         Err(e) => {
-            if let Some(crate::error::SchemaValidationError::Range { .. }) = e.downcast_ref() {
+            if let Some(crate::error::SchemaValidationError::Range { .. }) = validation_error(&e) {
                 Ok(())
             } else {
                 Err(e)
@@ -60,7 +60,7 @@ fn invalid_incl_excl_range_template() -> anyhow::Result<()> {
     match result {
         Ok(_) => Err(anyhow!("template validation succeeded")),
         Err(e) => {
-            if let Some(crate::error::SchemaValidationError::Range { .. }) = e.downcast_ref() {
+            if let Some(crate::error::SchemaValidationError::Range { .. }) = validation_error(&e) {
                 Ok(())
             } else {
                 Err(e)
@@ -77,7 +77,100 @@ fn invalid_excl_excl_range_template() -> anyhow::Result<()> {
     match result {
         Ok(_) => Err(anyhow!("template validation succeeded")),
         Err(e) => {
-            if let Some(crate::error::SchemaValidationError::Range { .. }) = e.downcast_ref() {
+            if let Some(crate::error::SchemaValidationError::Range { .. }) = validation_error(&e) {
+                Ok(())
+            } else {
+                Err(e)
+            }
+        }
+    }
+}
+
+#[test]
+fn invalid_template_cycle() -> anyhow::Result<()> {
+    let mut schema = common::get_template_cycle_schema()?;
+    let result = schema.validate();
+
+    match result {
+        Ok(_) => Err(anyhow!("template validation succeeded")),
+        Err(e) => {
+            if let Some(crate::error::SchemaValidationError::TemplateCycle(cycle)) =
+                validation_error(&e)
+            {
+                if cycle.len() == 3 && cycle.first() == cycle.last() {
+                    Ok(())
+                } else {
+                    Err(anyhow!("wrong cycle: {:?}", cycle))
+                }
+            } else {
+                Err(e)
+            }
+        }
+    }
+}
+
+#[test]
+fn invalid_template_reference() -> anyhow::Result<()> {
+    let mut schema = common::get_missing_template_reference_schema()?;
+    let result = schema.validate();
+
+    match result {
+        Ok(_) => Err(anyhow!("template validation succeeded")),
+        Err(e) => {
+            if let Some(crate::error::SchemaValidationError::MissingTemplate(_)) =
+                validation_error(&e)
+            {
+                Ok(())
+            } else {
+                Err(e)
+            }
+        }
+    }
+}
+
+#[test]
+fn invalid_length_template() -> anyhow::Result<()> {
+    let mut schema = common::get_invalid_length_template_schema()?;
+    let result = schema.validate();
+
+    match result {
+        Ok(_) => Err(anyhow!("template validation succeeded")),
+        Err(e) => {
+            if let Some(crate::error::SchemaValidationError::Length { .. }) = validation_error(&e) {
+                Ok(())
+            } else {
+                Err(e)
+            }
+        }
+    }
+}
+
+#[test]
+fn invalid_empty_composite_template() -> anyhow::Result<()> {
+    let mut schema = common::get_empty_composite_template_schema()?;
+    let result = schema.validate();
+
+    match result {
+        Ok(_) => Err(anyhow!("template validation succeeded")),
+        Err(e) => {
+            if let Some(crate::error::SchemaValidationError::EmptyTemplate) = validation_error(&e) {
+                Ok(())
+            } else {
+                Err(e)
+            }
+        }
+    }
+}
+
+#[test]
+fn invalid_range_step() -> anyhow::Result<()> {
+    let mut schema = common::get_invalid_range_step_schema()?;
+    let result = schema.validate();
+
+    match result {
+        Ok(_) => Err(anyhow!("template validation succeeded")),
+        Err(e) => {
+            if let Some(crate::error::SchemaValidationError::Step(_)) = validation_error(&e) {
                 Ok(())
             } else {
                 Err(e)
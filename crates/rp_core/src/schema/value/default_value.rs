@@ -1,5 +1,5 @@
 use super::SourceCommand;
-use crate::common::Context;
+use crate::{common::Context, error::SourceCommandError};
 use serde::{Deserialize, Serialize};
 
 #[derive(Serialize, Deserialize, Debug, Clone, PartialEq, Eq, Hash)]
@@ -13,7 +13,14 @@ impl DefaultValue {
     pub fn resolve(&self, context: &Context) -> anyhow::Result<Vec<String>> {
         match self {
             DefaultValue::Literal(literal) => Ok(vec![literal.to_owned()]),
-            DefaultValue::Command(command) => unimplemented!(),
+            DefaultValue::Command(command) => {
+                let values = command.resolve(context)?;
+                if values.is_empty() {
+                    Err(SourceCommandError::NoValues(command.clone()).into())
+                } else {
+                    Ok(values)
+                }
+            }
         }
     }
 }
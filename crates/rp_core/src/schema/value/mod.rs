@@ -1,10 +1,12 @@
 mod default_value;
 pub mod range;
+mod unit;
 
-use super::{Schema, SourceCommand, Validate};
+use super::{Condition, Schema, SourceCommand, Validate};
 pub use default_value::DefaultValue;
 use range::Range;
 use serde::{Deserialize, Serialize};
+pub use unit::Unit;
 
 #[derive(Serialize, Deserialize, Debug, Clone, PartialEq, Eq, Hash)]
 pub enum Value {
@@ -14,6 +16,22 @@ pub enum Value {
     Template(String),
     #[serde(rename = "range")]
     Range(Range),
+    // the name of any node in the multi node at this path, checked when committing
+    #[serde(rename = "reference")]
+    Reference(Vec<String>),
+}
+
+// a value as given in a property's list of allowed values, along with what it means
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq, Eq, Hash)]
+pub struct AllowedValue {
+    #[serde(flatten)]
+    pub value: Value,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub description: Option<String>,
+    // none of these may hold while the property has this value. a condition on the
+    // property itself is about its other values
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub excludes: Vec<Condition>,
 }
 
 impl std::fmt::Display for Value {
@@ -22,6 +40,7 @@ impl std::fmt::Display for Value {
             Value::Literal(value) => write!(f, "'{}'", value),
             Value::Template(template) => write!(f, "template '{}'", template),
             Value::Range(range) => write!(f, "{}", range),
+            Value::Reference(path) => write!(f, "a node in '{}'", path.join(" ")),
         }
     }
 }
@@ -40,7 +59,23 @@ impl Validate for Value {
                 }
             }
             Self::Range(range) => range.validate(schema),
+            Self::Reference(path) => {
+                if schema.find_multi_node(path).is_some() {
+                    Ok(())
+                } else {
+                    Err(
+                        crate::error::SchemaValidationError::InvalidReference(path.join(" "))
+                            .into(),
+                    )
+                }
+            }
             _ => Ok(()),
         }
     }
 }
+
+impl Validate for AllowedValue {
+    fn validate(&self, schema: &Schema) -> anyhow::Result<()> {
+        self.value.validate(schema)
+    }
+}
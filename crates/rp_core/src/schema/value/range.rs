@@ -1,26 +1,151 @@
+use super::unit::Unit;
 use crate::{
     error,
-    schema::{Bound, Matches, Schema, Validate},
+    schema::{Bound, Matches, Normalise, Schema, Validate},
 };
+use ordered_float::OrderedFloat;
 use serde::{Deserialize, Serialize};
 
 #[derive(Serialize, Deserialize, Debug, Clone, PartialEq, Eq, Hash)]
 pub struct Range {
     pub lower: Bound,
     pub upper: Bound,
+    // only whole numbers, written without exponents or fractions
+    #[serde(default)]
+    pub integer: bool,
+    // values have to be multiples of this
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub step: Option<OrderedFloat<f64>>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub unit: Option<Unit>,
+}
+
+impl Range {
+    // the value in the base unit, or none if it isn't one this range accepts
+    // regardless of the bounds
+    fn parse(&self, value: &str) -> Option<f64> {
+        let (number, multiplier) = match self.unit {
+            Some(unit) => {
+                let (number, suffix) =
+                    value.split_at(value.find(char::is_alphabetic).unwrap_or(value.len()));
+                (number, unit.multiplier(suffix)?)
+            }
+            None => (value, 1.0),
+        };
+
+        // anything f64 understands is fine as long as the range is a plain one, as it
+        // always has been
+        let number = if self.integer || self.unit.is_some() {
+            parse_decimal(number)?
+        } else {
+            number.parse::<f64>().ok()?
+        };
+
+        let value = number * multiplier;
+        if self.integer && value.fract() != 0.0 {
+            return None;
+        }
+
+        if let Some(step) = self.step {
+            let steps = value / step.into_inner();
+            if (steps - steps.round()).abs() > f64::EPSILON * steps.abs().max(1.0) {
+                return None;
+            }
+        }
+
+        Some(value)
+    }
+}
+
+impl Range {
+    // whether every value the other range accepts is accepted by this one too
+    pub fn contains(&self, other: &Range) -> bool {
+        let steps = match (self.step, other.step) {
+            (None, _) => true,
+            (Some(step), Some(other_step)) => {
+                let steps = other_step.into_inner() / step.into_inner();
+                (steps - steps.round()).abs() <= f64::EPSILON * steps.abs().max(1.0)
+            }
+            (Some(_), None) => false,
+        };
+
+        // with the same unit, a range only takes fewer ways of writing numbers than
+        // the other when it's integer and the other isn't
+        self.lower.below(other.lower)
+            && self.upper.above(other.upper)
+            && (!self.integer || other.integer)
+            && self.unit == other.unit
+            && steps
+    }
+
+    // whether no value fits between the bounds once values are whole numbers or
+    // multiples of the step
+    pub fn is_empty(&self) -> bool {
+        let step = match (self.step, self.integer) {
+            (Some(step), _) => step.into_inner(),
+            (None, true) => 1.0,
+            (None, false) => return false,
+        };
+
+        // the smallest multiple of the step above the lower bound
+        let lower = self.lower.value().into_inner();
+        let mut first = (lower / step).ceil() * step;
+        if let Bound::Exclusive(_) = self.lower {
+            if first <= lower {
+                first += step;
+            }
+        }
+
+        !self.lower.match_against_with(self.upper, first)
+    }
+}
+
+// digits with an optional sign and fraction, unlike f64's parsing which also takes
+// exponents, infinities and NaN
+fn parse_decimal(number: &str) -> Option<f64> {
+    let digits = number.trim_start_matches(|c| c == '-' || c == '+');
+    if number.len() - digits.len() > 1 {
+        return None;
+    }
+
+    let mut parts = digits.splitn(2, '.');
+    let whole = parts.next()?;
+    let valid = |part: &str| !part.is_empty() && part.chars().all(|c| c.is_ascii_digit());
+
+    if valid(whole) && parts.next().map_or(true, valid) {
+        number.parse().ok()
+    } else {
+        None
+    }
 }
 
 impl Matches for Range {
     fn matches(&self, value: &str) -> anyhow::Result<bool> {
-        value
-            .parse::<f64>()
-            .map(|v| self.lower.match_against_with(self.upper, v))
-            .or(Ok(false))
+        Ok(self
+            .parse(value)
+            .map_or(false, |v| self.lower.match_against_with(self.upper, v)))
+    }
+}
+
+// so that e.g. "1500", "1500.0" and "01500" are all stored as "1500", and "10M"
+// as "10000000"
+impl Normalise for Range {
+    fn normalise(&self, value: &str) -> anyhow::Result<String> {
+        Ok(self
+            .parse(value)
+            .map_or_else(|| value.to_owned(), |v| v.to_string()))
     }
 }
 
 impl Validate for Range {
     fn validate(&self, _schema: &Schema) -> anyhow::Result<()> {
+        if let Some(step) = self.step {
+            let step = step.into_inner();
+            if step <= 0.0 || (self.integer && step.fract() != 0.0) {
+                return Err(error::SchemaValidationError::Step(step).into());
+            }
+        }
+
         match (self.lower, self.upper) {
             (Bound::Inclusive(lower_v), Bound::Inclusive(upper_v))
             | (Bound::Exclusive(lower_v), Bound::Inclusive(upper_v)) => {
@@ -52,14 +177,28 @@ impl Validate for Range {
 
 impl std::fmt::Display for Range {
     fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        if self.integer {
+            write!(f, "integer ")?;
+        }
+
         match self.lower {
             Bound::Inclusive(v) => write!(f, "[{}, ", v)?,
             Bound::Exclusive(v) => write!(f, "]{}, ", v)?,
         };
         match self.upper {
-            Bound::Inclusive(v) => write!(f, "{}]", v),
-            Bound::Exclusive(v) => write!(f, "{}[", v),
+            Bound::Inclusive(v) => write!(f, "{}]", v)?,
+            Bound::Exclusive(v) => write!(f, "{}[", v)?,
+        };
+
+        if let Some(step) = self.step {
+            write!(f, " in steps of {}", step)?;
         }
+
+        if let Some(unit) = self.unit {
+            write!(f, " ({})", unit)?;
+        }
+
+        Ok(())
     }
 }
 
@@ -73,6 +212,9 @@ mod tests {
         if !(Range {
             lower: Bound::Inclusive(0.0.into()),
             upper: Bound::Inclusive(1.0.into()),
+            integer: false,
+            step: None,
+            unit: None,
         })
         .matches("0.5")?
         {
@@ -80,6 +222,9 @@ mod tests {
         } else if (Range {
             lower: Bound::Inclusive(0.0.into()),
             upper: Bound::Inclusive(1.0.into()),
+            integer: false,
+            step: None,
+            unit: None,
         })
         .matches("-1.0")?
         {
@@ -87,6 +232,9 @@ mod tests {
         } else if (Range {
             lower: Bound::Inclusive(0.0.into()),
             upper: Bound::Inclusive(1.0.into()),
+            integer: false,
+            step: None,
+            unit: None,
         })
         .matches("2.0")?
         {
@@ -95,4 +243,159 @@ mod tests {
             Ok(())
         }
     }
+
+    #[test]
+    fn normalise() -> anyhow::Result<()> {
+        let range = Range {
+            lower: Bound::Inclusive(0.0.into()),
+            upper: Bound::Inclusive(2000.0.into()),
+            integer: false,
+            step: None,
+            unit: None,
+        };
+
+        if range.normalise("01500.0")? != "1500" {
+            Err(anyhow!("integral value not normalised"))
+        } else if range.normalise("0.50")? != "0.5" {
+            Err(anyhow!("fractional value not normalised"))
+        } else {
+            Ok(())
+        }
+    }
+
+    #[test]
+    fn integer() -> anyhow::Result<()> {
+        let range = Range {
+            lower: Bound::Inclusive(68.0.into()),
+            upper: Bound::Inclusive(9000.0.into()),
+            integer: true,
+            step: None,
+            unit: None,
+        };
+
+        if !range.matches("1500")? {
+            Err(anyhow!("integer doesn't match"))
+        } else if range.matches("1500.5")? {
+            Err(anyhow!("fraction matches"))
+        } else if range.matches("1e3")? {
+            Err(anyhow!("exponent matches"))
+        } else if range.matches("inf")? {
+            Err(anyhow!("infinity matches"))
+        } else {
+            Ok(())
+        }
+    }
+
+    #[test]
+    fn step() -> anyhow::Result<()> {
+        let range = Range {
+            lower: Bound::Inclusive(0.0.into()),
+            upper: Bound::Inclusive(1.0.into()),
+            integer: false,
+            step: Some(0.25.into()),
+            unit: None,
+        };
+
+        if !range.matches("0.75")? {
+            Err(anyhow!("multiple of the step doesn't match"))
+        } else if range.matches("0.3")? {
+            Err(anyhow!("value between steps matches"))
+        } else {
+            Ok(())
+        }
+    }
+
+    #[test]
+    fn units() -> anyhow::Result<()> {
+        let rate = Range {
+            lower: Bound::Inclusive(0.0.into()),
+            upper: Bound::Inclusive(10e9.into()),
+            integer: true,
+            step: None,
+            unit: Some(Unit::Si),
+        };
+        let timeout = Range {
+            lower: Bound::Exclusive(0.0.into()),
+            upper: Bound::Inclusive(60e3.into()),
+            integer: true,
+            step: None,
+            unit: Some(Unit::Time),
+        };
+
+        if rate.normalise("10M")? != "10000000" || rate.normalise("1.5G")? != "1500000000" {
+            Err(anyhow!("rate not normalised to the base unit"))
+        } else if timeout.normalise("500ms")? != "500" || timeout.normalise("2s")? != "2000" {
+            Err(anyhow!("duration not normalised to the base unit"))
+        } else if rate.matches("10X")? || rate.matches("11G")? {
+            Err(anyhow!("unknown suffix or out of range value matches"))
+        } else if timeout.matches("1.5ms")? {
+            Err(anyhow!("fraction of the base unit matches"))
+        } else {
+            Ok(())
+        }
+    }
+
+    #[test]
+    fn contains() -> anyhow::Result<()> {
+        let mtu = Range {
+            lower: Bound::Inclusive(68.0.into()),
+            upper: Bound::Inclusive(9000.0.into()),
+            integer: true,
+            step: None,
+            unit: None,
+        };
+        let jumbo = Range {
+            upper: Bound::Inclusive(16000.0.into()),
+            ..mtu.clone()
+        };
+        let even = Range {
+            step: Some(2.0.into()),
+            ..mtu.clone()
+        };
+        let plain = Range {
+            integer: false,
+            ..mtu.clone()
+        };
+
+        if !jumbo.contains(&mtu) || mtu.contains(&jumbo) {
+            Err(anyhow!("wider bounds not contained"))
+        } else if !mtu.contains(&even) || even.contains(&mtu) {
+            Err(anyhow!("stepped range not contained"))
+        } else if !plain.contains(&mtu) || mtu.contains(&plain) {
+            Err(anyhow!("integer range not contained"))
+        } else {
+            Ok(())
+        }
+    }
+
+    #[test]
+    fn empty() -> anyhow::Result<()> {
+        let between = Range {
+            lower: Bound::Exclusive(0.0.into()),
+            upper: Bound::Exclusive(1.0.into()),
+            integer: true,
+            step: None,
+            unit: None,
+        };
+        let stepped = Range {
+            lower: Bound::Inclusive(0.1.into()),
+            upper: Bound::Inclusive(0.2.into()),
+            integer: false,
+            step: Some(0.25.into()),
+            unit: None,
+        };
+        let inclusive = Range {
+            lower: Bound::Inclusive(1.0.into()),
+            upper: Bound::Inclusive(1.0.into()),
+            ..between.clone()
+        };
+
+        if !between.is_empty() || !stepped.is_empty() {
+            Err(anyhow!("range without whole numbers or steps isn't empty"))
+        } else if inclusive.is_empty() {
+            Err(anyhow!("range with a single whole number is empty"))
+        } else {
+            Ok(())
+        }
+    }
 }
@@ -0,0 +1,57 @@
+use serde::{Deserialize, Serialize};
+
+// the suffixes a range's values may have. values are normalised to the base unit
+// and the range's bounds are given in it
+#[derive(Serialize, Deserialize, Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum Unit {
+    // decimal multiples, e.g. bit rates
+    #[serde(rename = "si")]
+    Si,
+    // multiples of 1024, e.g. memory sizes
+    #[serde(rename = "binary")]
+    Binary,
+    // durations in milliseconds
+    #[serde(rename = "time")]
+    Time,
+}
+
+impl Unit {
+    pub(crate) fn suffixes(self) -> &'static [(&'static str, f64)] {
+        match self {
+            Unit::Si => &[("k", 1e3), ("K", 1e3), ("M", 1e6), ("G", 1e9), ("T", 1e12)],
+            Unit::Binary => &[
+                ("K", 1024.0),
+                ("M", 1_048_576.0),
+                ("G", 1_073_741_824.0),
+                ("T", 1_099_511_627_776.0),
+            ],
+            Unit::Time => &[
+                ("ms", 1.0),
+                ("s", 1e3),
+                ("m", 60e3),
+                ("h", 3_600e3),
+                ("d", 86_400e3),
+            ],
+        }
+    }
+
+    // how many base units the suffix stands for, a value without one is already
+    // in the base unit
+    pub fn multiplier(self, suffix: &str) -> Option<f64> {
+        if suffix.is_empty() {
+            return Some(1.0);
+        }
+
+        self.suffixes()
+            .iter()
+            .find(|(name, _)| *name == suffix)
+            .map(|(_, multiplier)| *multiplier)
+    }
+}
+
+impl std::fmt::Display for Unit {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        let suffixes: Vec<&str> = self.suffixes().iter().map(|(name, _)| *name).collect();
+        write!(f, "{}", suffixes.join("/"))
+    }
+}
@@ -1,16 +1,89 @@
 #![allow(dead_code)]
 
-use rp_core::{config::Config, schema::Schema};
+use rp_core::{common::source_registry, config::Config, schema::Schema};
 use std::{
+    fs,
     io::{Cursor, Read, Seek, SeekFrom, Write},
-    rc::Rc,
+    sync::{
+        atomic::{AtomicBool, Ordering},
+        Arc,
+    },
 };
 
+// whether the links of the unplugging config have been unplugged
+pub static UNPLUGGED: AtomicBool = AtomicBool::new(false);
+
 fn buffer() -> Cursor<Vec<u8>> {
     Cursor::new(Vec::new())
 }
 
-pub fn get_valid_config() -> anyhow::Result<(Config, Rc<Schema>)> {
+pub fn register_sources() -> anyhow::Result<()> {
+    source_registry::register_function("test::cat", |context| {
+        cat(context.format(String::from("{mock}/test"))?)
+    })?;
+    source_registry::register_function("test::cat_nonexistent", |context| {
+        cat(context.format(String::from("{mock}/nonexistent"))?)
+    })?;
+    source_registry::register_function("test::ls", |context| {
+        ls(context.format(String::from("{mock}/"))?)
+    })?;
+    source_registry::register_function("test::ls_nonexistent", |context| {
+        ls(context.format(String::from("{mock}/nonexistent"))?)
+    })?;
+    source_registry::register_function("test::empty", |_context| Ok(Vec::new()))?;
+    source_registry::register_function("test::links", |_context| {
+        Ok(vec![String::from("eth0"), String::from("eth1")])
+    })?;
+    source_registry::register_function("test::unplugging_links", |_context| {
+        let mut links = vec![String::from("eth0")];
+        if !UNPLUGGED.load(Ordering::SeqCst) {
+            links.extend(vec![String::from("eth1"), String::from("eth2")]);
+        }
+        Ok(links)
+    })?;
+    source_registry::register_property("{link}.name", |context| {
+        Ok(vec![context.format(String::from("{link}"))?])
+    })?;
+    Ok(())
+}
+
+fn cat(path: String) -> anyhow::Result<Vec<String>> {
+    Ok(vec![fs::read_to_string(&path)?.trim().to_owned()])
+}
+
+fn ls(path: String) -> anyhow::Result<Vec<String>> {
+    let mut names = Vec::new();
+    for entry in fs::read_dir(path)? {
+        names.push(entry?.file_name().to_string_lossy().into_owned());
+    }
+    Ok(names)
+}
+
+// goes through the validated binary schema like the shell does, which also sets up
+// the node locators multi nodes need
+fn config_from_yaml(schema: &str) -> anyhow::Result<(Config, Arc<Schema>)> {
+    let mut schema = schema_from_yaml(schema)?;
+    schema.build_regex_cache()?;
+
+    let mut buf = buffer();
+    schema.to_binary_file(&mut buf)?;
+    let schema = Arc::new(Schema::from_binary(buf.get_ref())?);
+    Ok((Config::from_schema(Arc::downgrade(&schema))?, schema))
+}
+
+fn schema_from_yaml(schema: &str) -> anyhow::Result<Schema> {
+    let mut temp = buffer();
+    write!(temp, "{}", schema)?;
+    temp.seek(SeekFrom::Start(0))?;
+
+    let mut schema = Schema::from_yaml_file(temp)?;
+    schema.validate()?;
+    Ok(schema)
+}
+
+pub fn get_valid_config() -> anyhow::Result<(Config, Arc<Schema>)> {
+    register_sources()?;
+
     let mut schema = get_valid_schema()?;
     schema.build_regex_cache()?;
 
@@ -21,10 +94,10 @@ pub fn get_valid_config() -> anyhow::Result<(Config, Rc<Schema>)> {
 
     let mut bytes = Vec::new();
     buf.read_to_end(&mut bytes)?;
-    let schema = Rc::new(Schema::from_binary(&bytes)?);
+    let schema = Arc::new(Schema::from_binary(&bytes)?);
 
-    // it's important to return ownership of the schema Rc
-    Ok((Config::from_schema(Rc::downgrade(&schema))?, schema))
+    // it's important to return ownership of the schema Arc
+    Ok((Config::from_schema(Arc::downgrade(&schema))?, schema))
 }
 
 pub fn get_valid_save_data() -> Cursor<String> {
@@ -63,10 +136,8 @@ nodes:
           - template: string
       "query_default":
         default:
-          - query:
-              id: test
-              command:
-                cat: /test
+          - command:
+              function: "test::cat"
         values:
           - template: string"#;
 
@@ -76,6 +147,580 @@ nodes:
     Schema::from_yaml_file(temp)
 }
 
+pub fn get_sourced_multinode_config() -> anyhow::Result<(Config, Arc<Schema>)> {
+    register_sources()?;
+
+    let mut temp = buffer();
+    let schema = r#"---
+templates:
+ "string":
+   regex: ".*"
+nodes:
+  "multinode":
+    source:
+      id: link
+      command:
+        function: "test::links"
+    node:
+      properties:
+        "name":
+          default:
+            - command:
+                property: "{link}.name"
+          values:
+            - template: string"#;
+
+    write!(temp, "{}", schema)?;
+    temp.seek(SeekFrom::Start(0))?;
+
+    let schema = Arc::new(Schema::from_yaml_file(temp)?);
+    Ok((Config::from_schema(Arc::downgrade(&schema))?, schema))
+}
+
+// eth1 and eth2 go away once unplugged
+pub fn get_unplugging_config() -> anyhow::Result<(Config, Arc<Schema>)> {
+    register_sources()?;
+
+    let schema = r#"---
+templates:
+ "string":
+   regex: ".*"
+nodes:
+  "links":
+    source:
+      id: link
+      command:
+        function: "test::unplugging_links"
+    node:
+      properties:
+        "mtu":
+          values:
+            - template: string"#;
+
+    config_from_yaml(schema)
+}
+
+pub fn get_template_multinode_config() -> anyhow::Result<(Config, Arc<Schema>)> {
+    let mut temp = buffer();
+    let schema = r#"---
+templates:
+ "digit":
+   regex: "[0-9]"
+nodes:
+  "multinode":
+    source:
+      id: number
+      template: digit
+    node:
+      properties:
+        "simple":
+          values:
+            - literal: a"#;
+
+    write!(temp, "{}", schema)?;
+    temp.seek(SeekFrom::Start(0))?;
+
+    let schema = Arc::new(Schema::from_yaml_file(temp)?);
+    Ok((Config::from_schema(Arc::downgrade(&schema))?, schema))
+}
+
+pub fn get_invalid_multinode_save_data() -> Cursor<String> {
+    Cursor::new(String::from(
+        r#"{
+    "timestamp":"2019-11-25T17:28:20.203048562Z",
+    "nodes":{
+        "multinode":{
+            "subnodes":{
+                "a":{
+                    "subnodes":{},
+                    "properties":{}
+                }
+            },
+            "properties":{}
+        }
+    }
+}"#,
+    ))
+}
+
+pub fn get_normalising_config() -> anyhow::Result<(Config, Arc<Schema>)> {
+    let mut temp = buffer();
+    let schema = r#"---
+templates:
+ "mac":
+   mac: {}
+ "address":
+   address:
+     family: any
+nodes:
+  "interface":
+    properties:
+      "hardware":
+        values:
+          - template: mac
+      "address":
+        multiple: true
+        values:
+          - template: address"#;
+
+    write!(temp, "{}", schema)?;
+    temp.seek(SeekFrom::Start(0))?;
+
+    let schema = Arc::new(Schema::from_yaml_file(temp)?);
+    Ok((Config::from_schema(Arc::downgrade(&schema))?, schema))
+}
+
+pub fn get_unnormalised_save_data() -> Cursor<String> {
+    Cursor::new(String::from(
+        r#"{
+    "timestamp":"2019-11-25T17:28:20.203048562Z",
+    "nodes":{
+        "interface":{
+            "subnodes":{},
+            "properties":{
+                "hardware":[
+                    "AA-BB-CC-DD-EE-FF"
+                ],
+                "address":[
+                    "2001:DB8:0:0:0:0:0:1",
+                    "192.0.2.1"
+                ]
+            }
+        }
+    }
+}"#,
+    ))
+}
+
+pub fn get_migrating_config() -> anyhow::Result<(Config, Arc<Schema>)> {
+    let schema = r#"---
+version: 2
+migrations:
+  - version: 1
+    steps:
+      - rename_node:
+          path: [oldnode]
+          to: singlenode
+      - rename_property:
+          path: [singlenode]
+          property: old_simple
+          to: simple
+  - version: 2
+    steps:
+      - transform_property:
+          path: [singlenode]
+          property: simple
+          values:
+            a: b
+      - drop_property:
+          path: [singlenode]
+          property: gone
+      - move_property:
+          path: [multinode, "*"]
+          property: simple
+          to: [multinode, "*", subnode]
+templates:
+ "string":
+   regex: ".*"
+ "digit":
+   regex: "[0-9]"
+nodes:
+  "singlenode":
+    properties:
+      "simple":
+        values:
+          - template: string
+  "multinode":
+    source:
+      id: number
+      template: digit
+    node:
+      subnodes:
+        "subnode":
+          properties:
+            "simple":
+              values:
+                - literal: a"#;
+
+    config_from_yaml(schema)
+}
+
+pub fn get_old_version_save_data() -> Cursor<String> {
+    Cursor::new(String::from(
+        r#"{
+    "timestamp":"2019-11-25T17:28:20.203048562Z",
+    "nodes":{
+        "oldnode":{
+            "subnodes":{},
+            "properties":{
+                "old_simple":[
+                    "a"
+                ],
+                "gone":[
+                    "x"
+                ]
+            }
+        },
+        "multinode":{
+            "subnodes":{
+                "1":{
+                    "subnodes":{},
+                    "properties":{
+                        "simple":[
+                            "a"
+                        ]
+                    }
+                }
+            },
+            "properties":{}
+        }
+    }
+}"#,
+    ))
+}
+
+// rule 20 has been renamed to 25, which has to stay between the other rules
+pub fn get_ordered_migrating_config() -> anyhow::Result<(Config, Arc<Schema>)> {
+    let schema = r#"---
+version: 1
+migrations:
+  - version: 1
+    steps:
+      - rename_node:
+          path: [rules, "20"]
+          to: "25"
+templates:
+ "string":
+   regex: ".*"
+nodes:
+  "rules":
+    ordered: true
+    source:
+      id: rule
+      template: string
+    node:
+      properties:
+        "action":
+          values:
+            - template: string"#;
+
+    config_from_yaml(schema)
+}
+
+pub fn get_old_version_ordered_save_data() -> Cursor<String> {
+    Cursor::new(String::from(
+        r#"{
+    "timestamp":"2019-11-25T17:28:20.203048562Z",
+    "nodes":{
+        "rules":{
+            "subnodes":{
+                "30":{
+                    "subnodes":{},
+                    "properties":{}
+                },
+                "20":{
+                    "subnodes":{},
+                    "properties":{
+                        "action":[
+                            "drop"
+                        ]
+                    }
+                },
+                "10":{
+                    "subnodes":{},
+                    "properties":{}
+                }
+            },
+            "properties":{}
+        }
+    }
+}"#,
+    ))
+}
+
+// eth2 isn't one of the links anymore
+pub fn get_vanished_link_save_data() -> Cursor<String> {
+    Cursor::new(String::from(
+        r#"{
+    "timestamp":"2019-11-25T17:28:20.203048562Z",
+    "nodes":{
+        "multinode":{
+            "subnodes":{
+                "eth0":{
+                    "subnodes":{},
+                    "properties":{
+                        "name":[
+                            "lan"
+                        ]
+                    }
+                },
+                "eth2":{
+                    "subnodes":{},
+                    "properties":{
+                        "name":[
+                            "wan"
+                        ]
+                    }
+                }
+            },
+            "properties":{}
+        }
+    }
+}"#,
+    ))
+}
+
+pub fn get_newer_version_save_data() -> Cursor<String> {
+    Cursor::new(String::from(
+        r#"{
+    "timestamp":"2019-11-25T17:28:20.203048562Z",
+    "version":3,
+    "nodes":{}
+}"#,
+    ))
+}
+
+pub fn get_referencing_config() -> anyhow::Result<(Config, Arc<Schema>)> {
+    let schema = r#"---
+templates:
+ "name":
+   regex: "[a-z]+[0-9]"
+nodes:
+  "interfaces":
+    source:
+      id: interface
+      template: name
+    node: {}
+  "route":
+    properties:
+      "interface":
+        values:
+          - literal: blackhole
+          - reference: [interfaces]"#;
+
+    config_from_yaml(schema)
+}
+
+pub fn get_mandatory_config() -> anyhow::Result<(Config, Arc<Schema>)> {
+    let schema = r#"---
+templates:
+ "string":
+   regex: ".*"
+nodes:
+  "system":
+    properties:
+      "hostname":
+        mandatory: true
+        values:
+          - template: string
+      "domain":
+        mandatory: true
+        values:
+          - template: string
+      "location":
+        values:
+          - template: string
+  "users":
+    source:
+      id: user
+      template: string
+    node:
+      properties:
+        "password":
+          mandatory: true
+          values:
+            - template: string"#;
+
+    config_from_yaml(schema)
+}
+
+pub fn get_conditional_config() -> anyhow::Result<(Config, Arc<Schema>)> {
+    let schema = r#"---
+templates:
+ "string":
+   regex: ".*"
+ "cidr":
+   regex: "[0-9.]+/[0-9]+"
+nodes:
+  "interfaces":
+    source:
+      id: interface
+      template: string
+    node:
+      subnodes:
+        "vlan":
+          source:
+            id: vlan
+            template: string
+          when:
+            - property: [address]
+              absent: true
+          node:
+            properties:
+              "mtu":
+                must:
+                  - property: ["..", "..", mtu]
+                values:
+                  - template: string
+      properties:
+        "address":
+          multiple: true
+          values:
+            - literal: dhcp
+              excludes:
+                - property: [address]
+            - template: cidr
+        "dhcp-hostname":
+          must:
+            - property: [address]
+              value: dhcp
+          values:
+            - template: string
+        "bridge":
+          excludes:
+            - property: [address]
+          values:
+            - template: string
+        "autoneg":
+          values:
+            - literal: "on"
+            - literal: "off"
+        "speed":
+          default:
+            - literal: auto
+          when:
+            - property: [autoneg]
+              value: "off"
+          values:
+            - literal: auto
+            - template: string
+        "mtu":
+          values:
+            - template: string"#;
+
+    config_from_yaml(schema)
+}
+
+pub fn get_element_count_config() -> anyhow::Result<(Config, Arc<Schema>)> {
+    let schema = r#"---
+templates:
+ "string":
+   regex: ".*"
+nodes:
+  "system":
+    properties:
+      "nameserver":
+        multiple: true
+        min: 1
+        max: 2
+        values:
+          - template: string
+  "users":
+    min: 1
+    max: 2
+    source:
+      id: user
+      template: string
+    node:
+      properties:
+        "password":
+          values:
+            - template: string"#;
+
+    config_from_yaml(schema)
+}
+
+pub fn get_too_many_values_save_data() -> Cursor<String> {
+    Cursor::new(String::from(
+        r#"{
+    "timestamp":"2019-11-25T17:28:20.203048562Z",
+    "nodes":{
+        "system":{
+            "subnodes":{},
+            "properties":{
+                "nameserver":[
+                    "192.0.2.1",
+                    "192.0.2.2",
+                    "192.0.2.3"
+                ]
+            }
+        },
+        "users":{
+            "subnodes":{
+                "admin":{
+                    "subnodes":{},
+                    "properties":{}
+                }
+            },
+            "properties":{}
+        }
+    }
+}"#,
+    ))
+}
+
+// made before the system's hostname and domain were mandatory
+pub fn get_missing_mandatory_save_data() -> Cursor<String> {
+    Cursor::new(String::from(
+        r#"{
+    "timestamp":"2019-11-25T17:28:20.203048562Z",
+    "nodes":{
+        "system":{
+            "subnodes":{},
+            "properties":{
+                "location":[
+                    "basement"
+                ]
+            }
+        },
+        "users":{
+            "subnodes":{},
+            "properties":{}
+        }
+    }
+}"#,
+    ))
+}
+
+pub fn get_ordered_config() -> anyhow::Result<(Config, Arc<Schema>)> {
+    let schema = r#"---
+templates:
+ "string":
+   regex: ".*"
+nodes:
+  "system":
+    properties:
+      "nameserver":
+        multiple: true
+        ordered: true
+        values:
+          - template: string
+      "resolver":
+        multiple: true
+        ordered: true
+        default:
+          - literal: 1.1.1.1
+          - literal: 1.0.0.1
+        values:
+          - template: string
+      "ntp":
+        multiple: true
+        values:
+          - template: string
+  "rules":
+    ordered: true
+    source:
+      id: rule
+      template: string
+    node:
+      properties:
+        "action":
+          values:
+            - template: string"#;
+
+    config_from_yaml(schema)
+}
+
 pub fn get_nonexistent_default_cat_query_schema() -> anyhow::Result<Schema> {
     let mut temp = buffer();
     let schema = r#"---
@@ -85,10 +730,8 @@ nodes:
     properties:
       "query_default":
         default:
-          - query:
-              id: test
-              command:
-                cat: /nonexistent
+          - command:
+              function: "test::cat_nonexistent"
         values:
           - literal: a"#;
 
@@ -109,10 +752,8 @@ nodes:
     properties:
       "query_default":
         default:
-          - query:
-              id: test
-              command:
-                cat: /test
+          - command:
+              function: "test::cat"
         values:
           - template: digit"#;
 
@@ -133,10 +774,8 @@ nodes:
     properties:
       "query_default":
         default:
-          - query:
-              id: test
-              command:
-                ls: /nonexistent
+          - command:
+              function: "test::ls_nonexistent"
         values:
           - template: digit"#;
 
@@ -157,10 +796,8 @@ nodes:
     properties:
       "query_default":
         default:
-          - query:
-              id: test
-              command:
-                ls: /
+          - command:
+              function: "test::ls"
         values:
           - template: string"#;
 
@@ -169,3 +806,406 @@ nodes:
 
     Schema::from_yaml_file(temp)
 }
+
+pub fn get_unknown_default_function_schema() -> anyhow::Result<Schema> {
+    let mut temp = buffer();
+    let schema = r#"---
+templates: {}
+nodes:
+  "singlenode":
+    properties:
+      "command_default":
+        default:
+          - command:
+              function: "test::unknown"
+        values:
+          - literal: a"#;
+
+    write!(temp, "{}", schema)?;
+    temp.seek(SeekFrom::Start(0))?;
+
+    Schema::from_yaml_file(temp)
+}
+
+pub fn get_empty_default_function_schema() -> anyhow::Result<Schema> {
+    let mut temp = buffer();
+    let schema = r#"---
+templates: {}
+nodes:
+  "singlenode":
+    properties:
+      "command_default":
+        default:
+          - command:
+              function: "test::empty"
+        values:
+          - literal: a"#;
+
+    write!(temp, "{}", schema)?;
+    temp.seek(SeekFrom::Start(0))?;
+
+    Schema::from_yaml_file(temp)
+}
+
+pub fn get_composite_template_config() -> anyhow::Result<(Config, Arc<Schema>)> {
+    let schema = r#"---
+templates:
+  "hostname":
+    hostname: {}
+  "address":
+    address:
+      family: any
+  "host":
+    any:
+      - template: hostname
+      - template: address
+  "short":
+    all:
+      - regex: "[a-z]*"
+      - length:
+          max: 8
+  "unreserved":
+    all:
+      - template: hostname
+      - not:
+          regex: "localhost"
+nodes:
+  "system":
+    properties:
+      "server":
+        values:
+          - template: host
+      "name":
+        values:
+          - template: short
+      "hostname":
+        values:
+          - template: unreserved"#;
+
+    config_from_yaml(schema)
+}
+
+pub fn get_secret_config() -> anyhow::Result<(Config, Arc<Schema>)> {
+    let schema = r#"---
+templates:
+  "password":
+    password: {}
+  "string":
+    regex: ".*"
+nodes:
+  "user":
+    properties:
+      "password":
+        secret: true
+        values:
+          - template: password
+      "token":
+        secret: true
+        values:
+          - literal: abc"#;
+
+    config_from_yaml(schema)
+}
+
+pub fn get_exported_config() -> anyhow::Result<(Config, Arc<Schema>)> {
+    let schema = r#"---
+version: 2
+templates:
+  "hostname":
+    hostname: {}
+  "interface":
+    regex: "eth[0-9]+"
+  "address":
+    any:
+      - address:
+          family: ipv4
+      - template: hostname
+nodes:
+  "system":
+    description: System-wide settings
+    properties:
+      "hostname":
+        mandatory: true
+        deletable: false
+        default:
+          - literal: router
+        values:
+          - template: hostname
+      "nameserver":
+        multiple: true
+        ordered: true
+        max: 2
+        values:
+          - template: address
+      "password":
+        secret: true
+        values:
+          - literal: hunter2
+  "interfaces":
+    max: 4
+    source:
+      id: interface
+      template: interface
+    node:
+      properties:
+        "mtu":
+          values:
+            - range:
+                lower:
+                  inclusive: 68
+                upper:
+                  inclusive: 9000
+                integer: true
+            - literal: auto
+              description: Whatever the driver picks
+        "speed":
+          values:
+            - range:
+                lower:
+                  inclusive: 0
+                upper:
+                  inclusive: 100000000000
+                unit: si"#;
+
+    config_from_yaml(schema)
+}
+
+// the same schema before and after an upgrade
+pub fn get_diffed_schemas() -> anyhow::Result<(Schema, Schema)> {
+    let older = r#"---
+version: 1
+templates:
+  "string":
+    regex: ".*"
+  "mtu":
+    range:
+      lower:
+        inclusive: 68
+      upper:
+        inclusive: 1500
+nodes:
+  "system":
+    properties:
+      "hostname":
+        values:
+          - literal: router
+      "domain":
+        multiple: true
+        values:
+          - template: string
+      "timezone":
+        default:
+          - literal: UTC
+        values:
+          - template: string
+      "motd":
+        values:
+          - template: string
+      "banner":
+        values:
+          - template: string
+  "interfaces":
+    source:
+      id: interface
+      template: string
+    node:
+      properties:
+        "mtu":
+          values:
+            - template: mtu
+        "speed":
+          values:
+            - literal: "10"
+            - literal: "100"
+            - literal: "1000"
+        "duplex":
+          values:
+            - literal: half
+            - literal: full"#;
+
+    let newer = r#"---
+version: 2
+migrations:
+  - version: 2
+    steps:
+      - drop_property:
+          path: [system]
+          property: banner
+      - transform_property:
+          path: [interfaces, "*"]
+          property: duplex
+          values:
+            half: full
+templates:
+  "string":
+    regex: ".*"
+  "mtu":
+    range:
+      lower:
+        inclusive: 68
+      upper:
+        inclusive: 9000
+nodes:
+  "system":
+    properties:
+      "hostname":
+        values:
+          - template: string
+      "domain":
+        values:
+          - template: string
+      "timezone":
+        default:
+          - literal: Europe/Helsinki
+        values:
+          - template: string
+      "contact":
+        mandatory: true
+        values:
+          - template: string
+  "interfaces":
+    source:
+      id: interface
+      template: string
+    node:
+      properties:
+        "mtu":
+          values:
+            - template: mtu
+        "speed":
+          values:
+            - literal: "100"
+            - literal: "1000"
+        "duplex":
+          values:
+            - literal: full
+  "services":
+    properties:
+      "ssh":
+        values:
+          - literal: enabled"#;
+
+    Ok((schema_from_yaml(older)?, schema_from_yaml(newer)?))
+}
+
+pub fn get_linted_schema() -> anyhow::Result<Schema> {
+    let schema = r#"---
+templates:
+  "word":
+    regex: "[a-z]+"
+  "anything":
+    regex: "x*"
+  "unused":
+    template: word
+  "fraction":
+    range:
+      lower:
+        exclusive: 0
+      upper:
+        exclusive: 1
+      integer: true
+nodes:
+  "system":
+    properties:
+      "mode":
+        values:
+          - template: word
+          - literal: auto
+          - literal: default
+            description: Whatever the platform prefers
+      "comment":
+        values:
+          - template: anything
+      "ratio":
+        values:
+          - template: fraction
+      "dhcp":
+        default:
+          - literal: "on"
+        values:
+          - literal: "on"
+          - literal: "off"
+      "address":
+        values:
+          - template: word
+      "gateway":
+        default:
+          - literal: router
+        must:
+          - property: [address]
+        values:
+          - template: word
+      "static":
+        default:
+          - literal: "yes"
+        excludes:
+          - property: [dhcp]
+            value: "on"
+        values:
+          - literal: "yes"
+          - literal: "no""#;
+
+    schema_from_yaml(schema)
+}
+
+pub fn get_introspected_schema() -> anyhow::Result<Schema> {
+    register_sources()?;
+
+    let schema = r#"---
+templates:
+  "ethernet":
+    regex: "eth[0-9]+"
+  "vlan":
+    range:
+      lower:
+        inclusive: 1
+      upper:
+        inclusive: 4094
+      integer: true
+  "address":
+    address:
+      family: ipv4
+nodes:
+  "system":
+    properties:
+      "hostname":
+        deletable: false
+        default:
+          - literal: router
+        values:
+          - literal: router
+  "interfaces":
+    subnodes:
+      "ethernet":
+        source:
+          id: interface
+          template: ethernet
+        node:
+          subnodes:
+            "vlan":
+              source:
+                id: vlan
+                template: vlan
+              node:
+                properties:
+                  "address":
+                    multiple: true
+                    values:
+                      - template: address
+          properties:
+            "mtu":
+              values:
+                - literal: auto
+      "link":
+        source:
+          id: link
+          command:
+            function: "test::links"
+        node:
+          properties:
+            "up":
+              values:
+                - literal: "yes""#;
+
+    schema_from_yaml(schema)
+}
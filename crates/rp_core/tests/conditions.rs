@@ -0,0 +1,228 @@
+mod common;
+
+use anyhow::anyhow;
+use rp_core::{
+    config::{Changeable, Config, ConfigNode, Node},
+    error::{ValidationError, Violation},
+};
+use std::sync::Arc;
+
+fn node(config: &Config, path: &[&str]) -> anyhow::Result<Arc<ConfigNode>> {
+    let (first, rest) = path
+        .split_first()
+        .ok_or_else(|| anyhow!("empty node path"))?;
+    let mut node = config
+        .get_node_with_name(first)
+        .ok_or_else(|| anyhow!("'{}' node not in config", first))?;
+
+    for name in rest {
+        node = node
+            .get_node_with_name(name)?
+            .ok_or_else(|| anyhow!("'{}' node not found", name))?;
+    }
+
+    Ok(node)
+}
+
+fn set(config: &Config, path: &[&str], property: &str, value: &str) -> anyhow::Result<()> {
+    node(config, path)?
+        .get_property(property)
+        .ok_or_else(|| anyhow!("node doesn't have property '{}'", property))?
+        .set(value)
+}
+
+// the violation messages, sorted
+fn violations(result: anyhow::Result<bool>) -> anyhow::Result<Vec<String>> {
+    match result {
+        Ok(_) => Err(anyhow!("applying an invalid config succeeded")),
+        Err(e) => {
+            if let Some(ValidationError { violations }) = e.downcast_ref() {
+                let mut messages = Vec::new();
+                for violation in violations {
+                    match violation {
+                        Violation::NotApplicable { .. }
+                        | Violation::Unmet { .. }
+                        | Violation::Excluded { .. } => messages.push(violation.to_string()),
+                        _ => return Err(anyhow!("unexpected violation: {}", violation)),
+                    }
+                }
+                messages.sort();
+                Ok(messages)
+            } else {
+                Err(e)
+            }
+        }
+    }
+}
+
+fn expect(result: anyhow::Result<bool>, expected: &[&str]) -> anyhow::Result<()> {
+    let messages = violations(result)?;
+    if messages == expected {
+        Ok(())
+    } else {
+        Err(anyhow!("wrong violations: {:?}", messages))
+    }
+}
+
+#[test]
+fn value_excludes_other_values() -> anyhow::Result<()> {
+    let (config, _schema) = common::get_conditional_config()?;
+    set(&config, &["interfaces", "eth0"], "address", "dhcp")?;
+    set(&config, &["interfaces", "eth0"], "address", "10.0.0.1/24")?;
+
+    expect(
+        config.apply_changes(),
+        &["'interfaces eth0 address dhcp' can't be set while 'interfaces eth0 address' has other values"],
+    )
+}
+
+#[test]
+fn value_excludes_nothing_alone() -> anyhow::Result<()> {
+    let (config, _schema) = common::get_conditional_config()?;
+    set(&config, &["interfaces", "eth0"], "address", "dhcp")?;
+    config.apply_changes()?;
+
+    Ok(())
+}
+
+#[test]
+fn property_excludes() -> anyhow::Result<()> {
+    let (config, _schema) = common::get_conditional_config()?;
+    set(&config, &["interfaces", "eth0"], "address", "10.0.0.1/24")?;
+    set(&config, &["interfaces", "eth0"], "bridge", "br0")?;
+
+    expect(
+        config.apply_changes(),
+        &["'interfaces eth0 bridge' can't be set while 'interfaces eth0 address' is set"],
+    )
+}
+
+#[test]
+fn must_unmet() -> anyhow::Result<()> {
+    let (config, _schema) = common::get_conditional_config()?;
+    set(&config, &["interfaces", "eth0"], "address", "10.0.0.1/24")?;
+    set(&config, &["interfaces", "eth0"], "dhcp-hostname", "router")?;
+
+    expect(
+        config.apply_changes(),
+        &["'interfaces eth0 dhcp-hostname' requires that 'interfaces eth0 address' is 'dhcp'"],
+    )?;
+
+    set(&config, &["interfaces", "eth0"], "address", "dhcp")?;
+    node(&config, &["interfaces", "eth0"])?
+        .get_property("address")
+        .ok_or_else(|| anyhow!("no address property"))?
+        .remove(Some("10.0.0.1/24"))?;
+    config.apply_changes()?;
+
+    Ok(())
+}
+
+#[test]
+fn must_through_parents() -> anyhow::Result<()> {
+    let (config, _schema) = common::get_conditional_config()?;
+    set(
+        &config,
+        &["interfaces", "eth0", "vlan", "10"],
+        "mtu",
+        "1500",
+    )?;
+
+    expect(
+        config.apply_changes(),
+        &["'interfaces eth0 vlan 10 mtu' requires that 'interfaces eth0 mtu' is set"],
+    )?;
+
+    set(&config, &["interfaces", "eth0"], "mtu", "9000")?;
+    config.apply_changes()?;
+
+    Ok(())
+}
+
+#[test]
+fn multi_node_when() -> anyhow::Result<()> {
+    let (config, _schema) = common::get_conditional_config()?;
+    set(&config, &["interfaces", "eth0"], "address", "10.0.0.1/24")?;
+    node(&config, &["interfaces", "eth0", "vlan", "10"])?;
+
+    expect(
+        config.apply_changes(),
+        &["'interfaces eth0 vlan' is only valid when 'interfaces eth0 address' isn't set"],
+    )
+}
+
+#[test]
+fn when_ignores_defaults() -> anyhow::Result<()> {
+    let (config, _schema) = common::get_conditional_config()?;
+    node(&config, &["interfaces", "eth0"])?;
+    config.apply_changes()?;
+
+    set(&config, &["interfaces", "eth0"], "speed", "1000")?;
+    expect(
+        config.apply_changes(),
+        &["'interfaces eth0 speed' is only valid when 'interfaces eth0 autoneg' is 'off'"],
+    )?;
+
+    set(&config, &["interfaces", "eth0"], "autoneg", "off")?;
+    config.apply_changes()?;
+
+    if config.is_clean() {
+        Ok(())
+    } else {
+        Err(anyhow!("changes not applied"))
+    }
+}
+
+#[test]
+fn applied_dependents_checked_again() -> anyhow::Result<()> {
+    let (config, _schema) = common::get_conditional_config()?;
+    set(&config, &["interfaces", "eth0"], "mtu", "9000")?;
+    set(
+        &config,
+        &["interfaces", "eth0", "vlan", "10"],
+        "mtu",
+        "1500",
+    )?;
+    config.apply_changes()?;
+
+    // only the parent is edited, but the vlan's condition is on it
+    node(&config, &["interfaces", "eth0"])?
+        .get_property("mtu")
+        .ok_or_else(|| anyhow!("no mtu property"))?
+        .remove(None)?;
+    expect(
+        config.apply_changes(),
+        &["'interfaces eth0 vlan 10 mtu' requires that 'interfaces eth0 mtu' is set"],
+    )?;
+
+    set(&config, &["interfaces", "eth1"], "address", "10.0.0.1/24")?;
+    node(&config, &["interfaces", "eth1", "vlan", "20"])?;
+    expect(
+        config.apply_changes(),
+        &[
+            "'interfaces eth0 vlan 10 mtu' requires that 'interfaces eth0 mtu' is set",
+            "'interfaces eth1 vlan' is only valid when 'interfaces eth1 address' isn't set",
+        ],
+    )?;
+
+    config.discard_changes();
+    if config.is_clean() {
+        config.apply_changes()?;
+        Ok(())
+    } else {
+        Err(anyhow!("changes not discarded"))
+    }
+}
+
+#[test]
+fn applied_multi_node_when_checked_again() -> anyhow::Result<()> {
+    let (config, _schema) = common::get_conditional_config()?;
+    node(&config, &["interfaces", "eth0", "vlan", "10"])?;
+    config.apply_changes()?;
+
+    set(&config, &["interfaces", "eth0"], "address", "10.0.0.1/24")?;
+    expect(
+        config.apply_changes(),
+        &["'interfaces eth0 vlan' is only valid when 'interfaces eth0 address' isn't set"],
+    )
+}
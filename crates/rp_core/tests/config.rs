@@ -2,7 +2,7 @@ mod common;
 
 use anyhow::anyhow;
 use rp_core::config::{Changeable, Node};
-use std::io::Cursor;
+use std::{io::Cursor, sync::atomic::Ordering};
 
 fn buffer() -> Cursor<Vec<u8>> {
     Cursor::new(Vec::new())
@@ -71,3 +71,270 @@ fn load() -> anyhow::Result<()> {
 
     Ok(())
 }
+
+#[test]
+fn sourced_multinode() -> anyhow::Result<()> {
+    let (config, _schema) = common::get_sourced_multinode_config()?;
+    let node = config
+        .get_node_with_name("multinode")
+        .ok_or_else(|| anyhow!("'multinode' node not in config"))?;
+
+    for link in &["eth0", "eth1"] {
+        let child = node
+            .get_node_with_name(link)?
+            .ok_or_else(|| anyhow!("sourced node '{}' not in multinode", link))?;
+        let values = child
+            .get_property("name")
+            .ok_or_else(|| anyhow!("sourced node '{}' doesn't have property 'name'", link))?
+            .values();
+
+        if values != vec![link.to_string()] {
+            return Err(anyhow!(
+                "sourced node '{}' has wrong name values: {:?}",
+                link,
+                values
+            ));
+        }
+    }
+
+    if config.is_clean() {
+        Ok(())
+    } else {
+        Err(anyhow!("config dirty after populating sourced nodes"))
+    }
+}
+
+#[test]
+fn refresh_unchanged_sources() -> anyhow::Result<()> {
+    let (config, _schema) = common::get_sourced_multinode_config()?;
+
+    if config.refresh_sources()? {
+        Err(anyhow!("refreshing unchanged sources reported changes"))
+    } else {
+        Ok(())
+    }
+}
+
+#[test]
+fn create_multinode_node_invalid_name() -> anyhow::Result<()> {
+    let (config, _schema) = common::get_template_multinode_config()?;
+    let node = config
+        .get_node_with_name("multinode")
+        .ok_or_else(|| anyhow!("'multinode' node not in config"))?;
+
+    match node.get_node_with_name("a") {
+        Ok(_) => Err(anyhow!("invalid node creation succeeded")),
+        Err(e) => {
+            if let Some(rp_core::error::NodeCreationError::NameMismatch { .. }) = e.downcast_ref() {
+                Ok(())
+            } else {
+                Err(e)
+            }
+        }
+    }
+}
+
+#[test]
+fn create_sourced_multinode_node() -> anyhow::Result<()> {
+    let (config, _schema) = common::get_sourced_multinode_config()?;
+    let node = config
+        .get_node_with_name("multinode")
+        .ok_or_else(|| anyhow!("'multinode' node not in config"))?;
+
+    match node.get_node_with_name("eth2") {
+        Ok(_) => Err(anyhow!("node creation in sourced multinode succeeded")),
+        Err(e) => {
+            if let Some(rp_core::error::NodeCreationError::NotAllowed { .. }) = e.downcast_ref() {
+                Ok(())
+            } else {
+                Err(e)
+            }
+        }
+    }
+}
+
+#[test]
+fn load_vanished_sourced_node() -> anyhow::Result<()> {
+    let (config, _schema) = common::get_sourced_multinode_config()?;
+    config.load_config(common::get_vanished_link_save_data())?;
+    let multinode = config
+        .get_node_with_name("multinode")
+        .ok_or_else(|| anyhow!("'multinode' node not in config"))?;
+
+    if multinode.find_node("eth2")?.is_some() {
+        return Err(anyhow!("vanished node loaded"));
+    }
+
+    let name = multinode
+        .find_node("eth0")?
+        .ok_or_else(|| anyhow!("'eth0' not in multinode"))?
+        .get_property_values(Some(String::from("name")));
+    if name.get("name") == Some(&vec![String::from("lan")]) {
+        Ok(())
+    } else {
+        Err(anyhow!("rest of the save not loaded: {:?}", name))
+    }
+}
+
+#[test]
+fn load_multinode_node_invalid_name() -> anyhow::Result<()> {
+    let (config, _schema) = common::get_template_multinode_config()?;
+    let load_source = common::get_invalid_multinode_save_data();
+
+    match config.load_config(load_source) {
+        Ok(_) => Err(anyhow!("loading invalid node name succeeded")),
+        Err(e) => {
+            if let Some(rp_core::error::NodeCreationError::NameMismatch { .. }) = e.downcast_ref() {
+                if config.is_clean() {
+                    Ok(())
+                } else {
+                    Err(anyhow!("config dirty after failed load"))
+                }
+            } else {
+                Err(e)
+            }
+        }
+    }
+}
+
+#[test]
+fn set_normalised_value() -> anyhow::Result<()> {
+    let (config, _schema) = common::get_normalising_config()?;
+    let node = config
+        .get_node_with_name("interface")
+        .ok_or_else(|| anyhow!("'interface' node not in config"))?;
+    let property = node
+        .get_property("hardware")
+        .ok_or_else(|| anyhow!("'interface' node doesn't have property 'hardware'"))?;
+
+    property.set("AA-BB-CC-DD-EE-FF")?;
+    config.apply_changes()?;
+
+    if property.values() != vec![String::from("aa:bb:cc:dd:ee:ff")] {
+        return Err(anyhow!(
+            "value not normalised on set: {:?}",
+            property.values()
+        ));
+    }
+
+    property.set("aa-bb-cc-dd-ee-ff")?;
+
+    if config.is_clean() {
+        Ok(())
+    } else {
+        Err(anyhow!("config dirty after setting an equivalent value"))
+    }
+}
+
+#[test]
+fn load_normalised_values() -> anyhow::Result<()> {
+    let (config, _schema) = common::get_normalising_config()?;
+    config.load_config(common::get_unnormalised_save_data())?;
+
+    let values = config
+        .get_node_with_name("interface")
+        .ok_or_else(|| anyhow!("'interface' node not in config"))?
+        .get_property_values(None);
+
+    let mut addresses = values
+        .get("address")
+        .cloned()
+        .ok_or_else(|| anyhow!("'address' not loaded"))?;
+    addresses.sort();
+
+    if values.get("hardware") != Some(&vec![String::from("aa:bb:cc:dd:ee:ff")]) {
+        Err(anyhow!("hardware not normalised on load: {:?}", values))
+    } else if addresses != vec![String::from("192.0.2.1"), String::from("2001:db8::1")] {
+        Err(anyhow!("addresses not normalised on load: {:?}", addresses))
+    } else {
+        Ok(())
+    }
+}
+
+#[test]
+fn edit_removed_multinode_node() -> anyhow::Result<()> {
+    let (config, _schema) = common::get_conditional_config()?;
+    let node = config
+        .get_node_with_name("interfaces")
+        .ok_or_else(|| anyhow!("'interfaces' node not in config"))?;
+    let mtu = |child: &str| -> anyhow::Result<Vec<String>> {
+        Ok(node
+            .get_node_with_name(child)?
+            .ok_or_else(|| anyhow!("node '{}' not in interfaces", child))?
+            .get_property("mtu")
+            .ok_or_else(|| anyhow!("node '{}' doesn't have property 'mtu'", child))?
+            .values())
+    };
+
+    node.get_node_with_name("eth0")?
+        .and_then(|child| child.get_property("mtu").map(|mtu| mtu.set("9000")))
+        .transpose()?;
+    config.apply_changes()?;
+
+    // editing a node that's being removed gets a new one in its place
+    node.remove_subnode("eth0")?;
+    if !mtu("eth0")?.is_empty() {
+        return Err(anyhow!("removed node edited instead of a new one"));
+    }
+
+    // the removed node comes back if the changes are discarded
+    config.discard_changes();
+    if mtu("eth0")? != vec!["9000"] {
+        return Err(anyhow!("removed node not back after discarding"));
+    }
+
+    node.remove_subnode("eth0")?;
+    mtu("eth0")?;
+    config.apply_changes()?;
+    if !mtu("eth0")?.is_empty() || !config.is_clean() {
+        return Err(anyhow!("new node not applied in place of the removed one"));
+    }
+
+    Ok(())
+}
+
+#[test]
+fn refresh_disappeared_sources() -> anyhow::Result<()> {
+    let (config, _schema) = common::get_unplugging_config()?;
+    let links = config
+        .get_node_with_name("links")
+        .ok_or_else(|| anyhow!("'links' node not in config"))?;
+    let names = || -> Vec<String> {
+        links
+            .get_available_node_names()
+            .into_iter()
+            .map(|name| name.to_string())
+            .collect::<Vec<String>>()
+    };
+
+    links
+        .get_node_with_name("eth1")?
+        .and_then(|link| link.get_property("mtu").map(|mtu| mtu.set("9000")))
+        .transpose()?;
+    common::UNPLUGGED.store(true, Ordering::SeqCst);
+
+    // the link with changes is removed along with them rather than dropped
+    // outright, so the changes are there to see until they're applied
+    if !config.refresh_sources()? || config.is_clean() || names() != vec!["eth0"] {
+        return Err(anyhow!("links not removed after unplugging: {:?}", names()));
+    }
+    config.apply_changes()?;
+    if !config.is_clean() || names() != vec!["eth0"] {
+        return Err(anyhow!("removed link still there after applying"));
+    }
+
+    common::UNPLUGGED.store(false, Ordering::SeqCst);
+    config.refresh_sources()?;
+    let mtu = links
+        .find_node("eth1")?
+        .ok_or_else(|| anyhow!("link not back after plugging it in"))?
+        .get_property("mtu")
+        .ok_or_else(|| anyhow!("link doesn't have property 'mtu'"))?
+        .values();
+
+    if mtu.is_empty() {
+        Ok(())
+    } else {
+        Err(anyhow!("link came back with the removed changes"))
+    }
+}
@@ -2,12 +2,13 @@ mod common;
 
 use anyhow::anyhow;
 use rp_core::config::Config;
-use std::rc::Rc;
+use std::sync::Arc;
 
 #[test]
 fn nonexistent_default_cat_query() -> anyhow::Result<()> {
-    let schema = Rc::new(common::get_nonexistent_default_cat_query_schema()?);
-    let result = Config::from_schema(Rc::downgrade(&schema));
+    common::register_sources()?;
+    let schema = Arc::new(common::get_nonexistent_default_cat_query_schema()?);
+    let result = Config::from_schema(Arc::downgrade(&schema));
 
     match result {
         Ok(_) => Err(anyhow!("config creation succeeded")),
@@ -24,8 +25,9 @@ fn nonexistent_default_cat_query() -> anyhow::Result<()> {
 #[test]
 fn invalid_default_cat_query() -> anyhow::Result<()> {
     println!("{:?}", std::env::current_dir()?);
-    let schema = Rc::new(common::get_invalid_default_cat_query_schema()?);
-    let result = Config::from_schema(Rc::downgrade(&schema));
+    common::register_sources()?;
+    let schema = Arc::new(common::get_invalid_default_cat_query_schema()?);
+    let result = Config::from_schema(Arc::downgrade(&schema));
 
     match result {
         Ok(_) => Err(anyhow!("config creation succeeded")),
@@ -42,8 +44,9 @@ fn invalid_default_cat_query() -> anyhow::Result<()> {
 #[test]
 fn nonexistent_default_ls_query() -> anyhow::Result<()> {
     println!("{:?}", std::env::current_dir()?);
-    let schema = Rc::new(common::get_nonexistent_default_ls_query_schema()?);
-    let result = Config::from_schema(Rc::downgrade(&schema));
+    common::register_sources()?;
+    let schema = Arc::new(common::get_nonexistent_default_ls_query_schema()?);
+    let result = Config::from_schema(Arc::downgrade(&schema));
 
     match result {
         Ok(_) => Err(anyhow!("config creation succeeded")),
@@ -60,8 +63,9 @@ fn nonexistent_default_ls_query() -> anyhow::Result<()> {
 #[test]
 fn invalid_default_ls_query() -> anyhow::Result<()> {
     println!("{:?}", std::env::current_dir()?);
-    let schema = Rc::new(common::get_invalid_default_ls_query_schema()?);
-    let result = Config::from_schema(Rc::downgrade(&schema));
+    common::register_sources()?;
+    let schema = Arc::new(common::get_invalid_default_ls_query_schema()?);
+    let result = Config::from_schema(Arc::downgrade(&schema));
 
     match result {
         Ok(_) => Err(anyhow!("config creation succeeded")),
@@ -74,3 +78,39 @@ fn invalid_default_ls_query() -> anyhow::Result<()> {
         }
     }
 }
+
+#[test]
+fn unknown_default_function() -> anyhow::Result<()> {
+    common::register_sources()?;
+    let schema = Arc::new(common::get_unknown_default_function_schema()?);
+    let result = Config::from_schema(Arc::downgrade(&schema));
+
+    match result {
+        Ok(_) => Err(anyhow!("config creation succeeded")),
+        Err(e) => {
+            if let Some(rp_core::error::SourceCommandError::UnknownFunction(_)) = e.downcast_ref() {
+                Ok(())
+            } else {
+                Err(e)
+            }
+        }
+    }
+}
+
+#[test]
+fn empty_default_function() -> anyhow::Result<()> {
+    common::register_sources()?;
+    let schema = Arc::new(common::get_empty_default_function_schema()?);
+    let result = Config::from_schema(Arc::downgrade(&schema));
+
+    match result {
+        Ok(_) => Err(anyhow!("config creation succeeded")),
+        Err(e) => {
+            if let Some(rp_core::error::SourceCommandError::NoValues(_)) = e.downcast_ref() {
+                Ok(())
+            } else {
+                Err(e)
+            }
+        }
+    }
+}
@@ -0,0 +1,151 @@
+mod common;
+
+use anyhow::anyhow;
+use rp_core::{
+    config::{Changeable, Config, Node, Property},
+    error::{ElementCountError, ValidationError, Violation},
+};
+
+fn nameserver<T>(
+    config: &Config,
+    f: impl FnOnce(&Property) -> anyhow::Result<T>,
+) -> anyhow::Result<T> {
+    f(config
+        .get_node_with_name("system")
+        .ok_or_else(|| anyhow!("'system' node not in config"))?
+        .get_property("nameserver")
+        .ok_or_else(|| anyhow!("'system' node doesn't have property 'nameserver'"))?)
+}
+
+fn add_user(config: &Config, name: &str) -> anyhow::Result<()> {
+    config
+        .get_node_with_name("users")
+        .ok_or_else(|| anyhow!("'users' node not in config"))?
+        .get_node_with_name(name)?;
+    Ok(())
+}
+
+fn expect_count_error(result: anyhow::Result<()>, too_many: bool) -> anyhow::Result<()> {
+    match result {
+        Ok(_) => Err(anyhow!("going past the element count limit succeeded")),
+        Err(e) => match e.downcast_ref() {
+            Some(ElementCountError::TooMany { .. }) if too_many => Ok(()),
+            Some(ElementCountError::TooFew { .. }) if !too_many => Ok(()),
+            _ => Err(e),
+        },
+    }
+}
+
+// the paths of every element count violation, sorted
+fn count_violations(result: anyhow::Result<()>) -> anyhow::Result<Vec<String>> {
+    match result {
+        Ok(_) => Err(anyhow!("validating an invalid config succeeded")),
+        Err(e) => {
+            if let Some(ValidationError { violations }) = e.downcast_ref() {
+                let mut paths = Vec::new();
+                for violation in violations {
+                    match violation {
+                        Violation::ElementCount(ElementCountError::TooMany { item, .. })
+                        | Violation::ElementCount(ElementCountError::TooFew { item, .. }) => {
+                            paths.push(item.to_owned())
+                        }
+                        _ => return Err(anyhow!("unexpected violation: {}", violation)),
+                    }
+                }
+                paths.sort();
+                Ok(paths)
+            } else {
+                Err(e)
+            }
+        }
+    }
+}
+
+#[test]
+fn set_beyond_max() -> anyhow::Result<()> {
+    let (config, _schema) = common::get_element_count_config()?;
+    nameserver(&config, |property| {
+        property.set("192.0.2.1")?;
+        property.set("192.0.2.2")?;
+
+        // setting an existing value again doesn't add to the count
+        property.set("192.0.2.2")?;
+        expect_count_error(property.set("192.0.2.3"), true)
+    })
+}
+
+#[test]
+fn remove_below_min() -> anyhow::Result<()> {
+    let (config, _schema) = common::get_element_count_config()?;
+    nameserver(&config, |property| {
+        property.set("192.0.2.1")?;
+        property.set("192.0.2.2")?;
+        property.remove(Some("192.0.2.2"))?;
+
+        expect_count_error(property.remove(Some("192.0.2.1")), false)?;
+        expect_count_error(property.remove(None), false)
+    })
+}
+
+#[test]
+fn add_node_beyond_max() -> anyhow::Result<()> {
+    let (config, _schema) = common::get_element_count_config()?;
+    add_user(&config, "admin")?;
+    add_user(&config, "operator")?;
+
+    expect_count_error(add_user(&config, "guest"), true)
+}
+
+#[test]
+fn remove_node_below_min() -> anyhow::Result<()> {
+    let (config, _schema) = common::get_element_count_config()?;
+    add_user(&config, "admin")?;
+    let users = config
+        .get_node_with_name("users")
+        .ok_or_else(|| anyhow!("'users' node not in config"))?;
+
+    expect_count_error(users.remove_subnode("admin"), false)
+}
+
+#[test]
+fn commit_below_min() -> anyhow::Result<()> {
+    let (config, _schema) = common::get_element_count_config()?;
+    let paths = count_violations(config.apply_changes().map(|_| ()))?;
+
+    if paths == vec![String::from("system nameserver"), String::from("users")] {
+        Ok(())
+    } else {
+        Err(anyhow!("wrong violations: {:?}", paths))
+    }
+}
+
+#[test]
+fn commit_within_limits() -> anyhow::Result<()> {
+    let (config, _schema) = common::get_element_count_config()?;
+    nameserver(&config, |property| property.set("192.0.2.1"))?;
+    add_user(&config, "admin")?;
+    config.apply_changes()?;
+
+    if config.is_clean() {
+        Ok(())
+    } else {
+        Err(anyhow!("changes not applied"))
+    }
+}
+
+#[test]
+fn load_beyond_max() -> anyhow::Result<()> {
+    let (config, _schema) = common::get_element_count_config()?;
+    let violations = config
+        .load_config(common::get_too_many_values_save_data())?
+        .ok_or_else(|| anyhow!("loading an invalid save reported no problems"))?;
+    let paths = count_violations(Err(violations.into()))?;
+
+    if paths != vec![String::from("system nameserver")] {
+        Err(anyhow!("wrong violations: {:?}", paths))
+    } else if nameserver(&config, |property| Ok(property.values().len()))? != 3 {
+        Err(anyhow!("invalid save not kept"))
+    } else {
+        Ok(())
+    }
+}
@@ -0,0 +1,194 @@
+mod common;
+
+use anyhow::anyhow;
+use rp_core::config::{Changeable, Node};
+use serde_json::{json, Value};
+
+fn pointer<'a>(value: &'a Value, path: &str) -> anyhow::Result<&'a Value> {
+    value
+        .pointer(path)
+        .ok_or_else(|| anyhow!("'{}' not in exported document", path))
+}
+
+// only the parts the exporter uses: objects with closed or uniform keys, and arrays
+// with an item limit
+fn check_keys(document: &Value, schema: &Value, path: &str) -> anyhow::Result<()> {
+    match document {
+        Value::Object(object) => {
+            for (key, value) in object {
+                let path = format!("{}/{}", path, key);
+                match (
+                    schema.pointer(&format!("/properties/{}", key)),
+                    schema.get("additionalProperties"),
+                ) {
+                    (Some(property), _) => check_keys(value, property, &path)?,
+                    (None, Some(Value::Bool(false))) | (None, None) => {
+                        return Err(anyhow!("'{}' isn't allowed by the JSON Schema", path))
+                    }
+                    (None, Some(additional)) => check_keys(value, additional, &path)?,
+                }
+            }
+        }
+        Value::Array(array) => {
+            if let Some(max) = schema.get("maxItems").and_then(Value::as_u64) {
+                if array.len() as u64 > max {
+                    return Err(anyhow!("'{}' has more values than allowed", path));
+                }
+            }
+        }
+        _ => (),
+    }
+
+    Ok(())
+}
+
+#[test]
+fn json_schema_describes_nodes_and_properties() -> anyhow::Result<()> {
+    let (_config, schema) = common::get_exported_config()?;
+    let exported = schema.to_json_schema();
+
+    let system = pointer(&exported, "/properties/nodes/properties/system")?;
+    assert_eq!(
+        pointer(system, "/description")?,
+        &json!("System-wide settings")
+    );
+
+    let properties = pointer(system, "/properties/properties")?;
+    assert_eq!(pointer(properties, "/required")?, &json!(["hostname"]));
+    assert_eq!(pointer(properties, "/additionalProperties")?, &json!(false));
+
+    let hostname = pointer(properties, "/properties/hostname")?;
+    assert_eq!(pointer(hostname, "/maxItems")?, &json!(1));
+    assert_eq!(pointer(hostname, "/default")?, &json!(["router"]));
+    assert_eq!(pointer(hostname, "/x-deletable")?, &json!(false));
+    assert_eq!(
+        pointer(hostname, "/items/$ref")?,
+        &json!("#/definitions/hostname")
+    );
+
+    let nameserver = pointer(properties, "/properties/nameserver")?;
+    assert_eq!(pointer(nameserver, "/maxItems")?, &json!(2));
+    assert_eq!(pointer(nameserver, "/x-ordered")?, &json!(true));
+
+    let password = pointer(properties, "/properties/password")?;
+    assert_eq!(pointer(password, "/writeOnly")?, &json!(true));
+    Ok(())
+}
+
+#[test]
+fn json_schema_describes_multi_nodes_and_templates() -> anyhow::Result<()> {
+    let (_config, schema) = common::get_exported_config()?;
+    let exported = schema.to_json_schema();
+
+    let interfaces = pointer(
+        &exported,
+        "/properties/nodes/properties/interfaces/properties/subnodes",
+    )?;
+    assert_eq!(
+        pointer(interfaces, "/propertyNames/$ref")?,
+        &json!("#/definitions/interface")
+    );
+    assert_eq!(pointer(interfaces, "/maxProperties")?, &json!(4));
+
+    let mtu = pointer(
+        interfaces,
+        "/additionalProperties/properties/properties/properties/mtu/items/anyOf",
+    )?;
+    assert!(mtu
+        .as_array()
+        .ok_or_else(|| anyhow!("mtu values aren't a list"))?
+        .iter()
+        .any(|value| value.get("const") == Some(&json!("auto"))
+            && value.get("description") == Some(&json!("Whatever the driver picks"))));
+    assert!(mtu
+        .as_array()
+        .ok_or_else(|| anyhow!("mtu values aren't a list"))?
+        .iter()
+        .any(
+            |value| value.pointer("/x-range/maximum") == Some(&json!(9000))
+                && value.get("pattern") == Some(&json!("^[+-]?[0-9]+$"))
+        ));
+
+    let speed = pointer(
+        interfaces,
+        "/additionalProperties/properties/properties/properties/speed/items",
+    )?;
+    assert_eq!(
+        pointer(speed, "/pattern")?,
+        &json!("^[+-]?[0-9]+(\\.[0-9]+)?(k|K|M|G|T)?$")
+    );
+
+    assert_eq!(
+        pointer(&exported, "/definitions/interface/pattern")?,
+        &json!("eth[0-9]+")
+    );
+    assert_eq!(
+        pointer(&exported, "/definitions/address/anyOf/0/format")?,
+        &json!("ipv4")
+    );
+    assert_eq!(
+        pointer(&exported, "/definitions/address/anyOf/1/$ref")?,
+        &json!("#/definitions/hostname")
+    );
+    Ok(())
+}
+
+#[test]
+fn saves_fit_json_schema() -> anyhow::Result<()> {
+    let (config, schema) = common::get_exported_config()?;
+
+    let interface = config
+        .get_node_with_name("interfaces")
+        .ok_or_else(|| anyhow!("'interfaces' node not in config"))?
+        .get_node_with_name("eth0")?
+        .ok_or_else(|| anyhow!("'eth0' node wasn't created"))?;
+    interface
+        .get_property("mtu")
+        .ok_or_else(|| anyhow!("interface doesn't have property 'mtu'"))?
+        .set("1500")?;
+    config.apply_changes()?;
+
+    let mut save = Vec::new();
+    config.save_config(&mut save)?;
+    let save: Value = serde_json::from_slice(&save)?;
+
+    check_keys(&save, &schema.to_json_schema(), "")
+}
+
+#[test]
+fn api_model_lists_paths() -> anyhow::Result<()> {
+    let (_config, schema) = common::get_exported_config()?;
+    let model = schema.to_api_model()?;
+
+    assert_eq!(pointer(&model, "/version")?, &json!(2));
+
+    let paths: Vec<&Value> = model["nodes"]
+        .as_array()
+        .ok_or_else(|| anyhow!("nodes aren't a list"))?
+        .iter()
+        .map(|node| &node["path"])
+        .collect();
+    assert_eq!(
+        paths,
+        vec![
+            &json!(["interfaces"]),
+            &json!(["interfaces", "*"]),
+            &json!(["system"]),
+        ]
+    );
+
+    let mtu = model["properties"]
+        .as_array()
+        .ok_or_else(|| anyhow!("properties aren't a list"))?
+        .iter()
+        .find(|property| property["path"] == json!(["interfaces", "*", "mtu"]))
+        .ok_or_else(|| anyhow!("mtu not in API model"))?;
+    assert_eq!(mtu["multiple"], json!(false));
+    assert_eq!(mtu["values"].as_array().map(Vec::len), Some(2));
+
+    assert_eq!(
+        pointer(&model, "/templates/interface/definition/regex")?,
+        &json!("eth[0-9]+")
+    );
+    Ok(())
+}
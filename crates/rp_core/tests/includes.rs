@@ -0,0 +1,108 @@
+use anyhow::anyhow;
+use rp_core::{
+    error::{IncludeError, MergeError, SchemaErrors, SchemaValidationError},
+    schema::Schema,
+};
+use std::{fs::File, path::PathBuf};
+
+fn schema_path(path: &str) -> PathBuf {
+    [env!("CARGO_MANIFEST_DIR"), "tests", "schemas", path]
+        .iter()
+        .collect()
+}
+
+#[test]
+fn include_relative_to_file() -> anyhow::Result<()> {
+    let mut schema = Schema::from_yaml_path(schema_path("include/main.yml"))?;
+    schema.validate()?;
+
+    let mut nodes: Vec<&String> = schema.nodes.keys().collect();
+    nodes.sort();
+
+    if nodes != vec!["interfaces", "main", "system"] {
+        Err(anyhow!("wrong nodes: {:?}", nodes))
+    } else if schema.templates.len() != 2 {
+        Err(anyhow!("wrong templates: {:?}", schema.templates.keys()))
+    } else if schema.version() != 1 {
+        Err(anyhow!("version lost: {}", schema.version()))
+    } else {
+        Ok(())
+    }
+}
+
+#[test]
+fn include_cycle() -> anyhow::Result<()> {
+    match Schema::from_yaml_path(schema_path("cycle/a.yml")) {
+        Ok(_) => Err(anyhow!("including a cycle succeeded")),
+        Err(e) => {
+            if let Some(IncludeError::Cycle(cycle)) = e.downcast_ref() {
+                if cycle.len() == 3 && cycle.first() == cycle.last() {
+                    Ok(())
+                } else {
+                    Err(anyhow!("wrong cycle: {:?}", cycle))
+                }
+            } else {
+                Err(e)
+            }
+        }
+    }
+}
+
+#[test]
+fn include_conflict() -> anyhow::Result<()> {
+    match Schema::from_yaml_path(schema_path("conflict/main.yml")) {
+        Ok(_) => Err(anyhow!("including a conflicting schema succeeded")),
+        Err(e) => {
+            if let Some(MergeError::Conflict { .. }) = e.downcast_ref() {
+                Ok(())
+            } else {
+                Err(e)
+            }
+        }
+    }
+}
+
+#[test]
+fn validation_error_names_file() -> anyhow::Result<()> {
+    let mut schema = Schema::from_yaml_path(schema_path("invalid/main.yml"))?;
+
+    match schema.validate() {
+        Ok(_) => Err(anyhow!("validating an invalid schema succeeded")),
+        Err(e) => {
+            let message = format!("{:#}", e);
+            if let Some(SchemaValidationError::MissingTemplate(_)) = e
+                .downcast_ref::<SchemaErrors>()
+                .and_then(|errors| errors.find())
+            {
+                if message.contains("broken.yml") {
+                    Ok(())
+                } else {
+                    Err(anyhow!("file not in error message: {}", message))
+                }
+            } else {
+                Err(e)
+            }
+        }
+    }
+}
+
+#[test]
+fn unresolved_include() -> anyhow::Result<()> {
+    let mut schema = Schema::from_yaml_file(File::open(schema_path("include/main.yml"))?)?;
+
+    match schema.validate() {
+        Ok(_) => Err(anyhow!(
+            "validating a schema with unresolved includes succeeded"
+        )),
+        Err(e) => {
+            if let Some(SchemaValidationError::UnresolvedInclude(_)) = e
+                .downcast_ref::<SchemaErrors>()
+                .and_then(|errors| errors.find())
+            {
+                Ok(())
+            } else {
+                Err(e)
+            }
+        }
+    }
+}
@@ -0,0 +1,100 @@
+mod common;
+
+use anyhow::anyhow;
+use rp_core::error::{SchemaError, SchemaWarning};
+
+// the warnings of each kind, by the path of the item they're about
+fn warnings<F>(warnings: &[SchemaError], kind: F) -> Vec<String>
+where
+    F: Fn(&SchemaWarning) -> bool,
+{
+    let mut paths: Vec<String> = warnings
+        .iter()
+        .filter(|warning| warning.error.downcast_ref().map_or(false, &kind))
+        .map(|warning| warning.location.path.join(" "))
+        .collect();
+    paths.sort();
+    paths
+}
+
+fn expect(found: Vec<String>, expected: &[&str], kind: &str) -> anyhow::Result<()> {
+    if found == expected {
+        Ok(())
+    } else {
+        Err(anyhow!(
+            "expected {} at {:?}, found {:?}",
+            kind,
+            expected,
+            found
+        ))
+    }
+}
+
+#[test]
+fn unused_templates() -> anyhow::Result<()> {
+    let lints = common::get_linted_schema()?.lint()?;
+
+    expect(
+        warnings(&lints, |w| matches!(w, SchemaWarning::UnusedTemplate(_))),
+        &["templates unused"],
+        "unused templates",
+    )
+}
+
+#[test]
+fn redundant_literals() -> anyhow::Result<()> {
+    let lints = common::get_linted_schema()?.lint()?;
+
+    // the described literal is kept for its description
+    expect(
+        warnings(&lints, |w| match w {
+            SchemaWarning::RedundantLiteral { literal, .. } => literal == "auto",
+            _ => false,
+        }),
+        &["system mode"],
+        "redundant literals",
+    )
+}
+
+#[test]
+fn empty_matches_and_ranges() -> anyhow::Result<()> {
+    let lints = common::get_linted_schema()?.lint()?;
+
+    expect(
+        warnings(&lints, |w| matches!(w, SchemaWarning::EmptyMatch(_))),
+        &["templates anything"],
+        "empty matches",
+    )?;
+    expect(
+        warnings(&lints, |w| matches!(w, SchemaWarning::EmptyRange(_))),
+        &["templates fraction"],
+        "empty ranges",
+    )
+}
+
+#[test]
+fn unsatisfiable_defaults() -> anyhow::Result<()> {
+    let lints = common::get_linted_schema()?.lint()?;
+
+    expect(
+        warnings(&lints, |w| {
+            matches!(w, SchemaWarning::UnsatisfiableDefault { .. })
+        }),
+        &["system gateway", "system static"],
+        "unsatisfiable defaults",
+    )
+}
+
+#[test]
+fn clean_properties() -> anyhow::Result<()> {
+    let lints = common::get_linted_schema()?.lint()?;
+
+    expect(
+        warnings(&lints, |_| true)
+            .into_iter()
+            .filter(|path| path == "system dhcp" || path == "system address")
+            .collect(),
+        &[],
+        "no warnings",
+    )
+}
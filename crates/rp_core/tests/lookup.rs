@@ -0,0 +1,104 @@
+mod common;
+
+use anyhow::anyhow;
+use rp_core::{
+    error::PathError,
+    schema::{PathElement, PathTarget},
+};
+
+#[test]
+fn lookup_property() -> anyhow::Result<()> {
+    let schema = common::get_introspected_schema()?;
+    let path = schema.lookup("interfaces ethernet eth0 vlan 10 address")?;
+
+    let property = path
+        .property()
+        .ok_or_else(|| anyhow!("path didn't lead to a property"))?;
+    if path.schema_path != ["interfaces", "ethernet", "*", "vlan", "*", "address"] {
+        Err(anyhow!("unexpected schema path {:?}", path.schema_path))
+    } else if !property.multiple || property.values.len() != 1 {
+        Err(anyhow!("wrong property found"))
+    } else if !path.next.is_empty() {
+        Err(anyhow!("properties have nothing after them"))
+    } else {
+        Ok(())
+    }
+}
+
+#[test]
+fn lookup_next_elements() -> anyhow::Result<()> {
+    let schema = common::get_introspected_schema()?;
+
+    let root = schema.lookup("")?;
+    if !matches!(root.target, PathTarget::Root)
+        || root.next
+            != [
+                PathElement::Node(String::from("interfaces")),
+                PathElement::Node(String::from("system")),
+            ]
+    {
+        return Err(anyhow!("unexpected top-level elements {:?}", root.next));
+    }
+
+    let ethernet = schema.lookup("interfaces ethernet")?;
+    if ethernet.next
+        != [PathElement::Key {
+            template: Some(String::from("ethernet")),
+            sourced: false,
+        }]
+    {
+        return Err(anyhow!("unexpected keys {:?}", ethernet.next));
+    }
+
+    let interface = schema.lookup("interfaces ethernet eth1")?;
+    if interface.next
+        != [
+            PathElement::Node(String::from("vlan")),
+            PathElement::Property(String::from("mtu")),
+        ]
+    {
+        Err(anyhow!("unexpected elements {:?}", interface.next))
+    } else {
+        Ok(())
+    }
+}
+
+#[test]
+fn lookup_key_mismatch() -> anyhow::Result<()> {
+    let schema = common::get_introspected_schema()?;
+
+    match schema.lookup("interfaces ethernet wlan0 mtu") {
+        Ok(_) => Err(anyhow!("key not matching the template was accepted")),
+        Err(e) => match e.downcast_ref() {
+            Some(PathError::KeyMismatch { key, .. }) if key == "wlan0" => Ok(()),
+            _ => Err(e),
+        },
+    }
+}
+
+#[test]
+fn lookup_sourced_key() -> anyhow::Result<()> {
+    let schema = common::get_introspected_schema()?;
+    let path = schema.lookup("interfaces link anything up")?;
+
+    if path.property().is_some() && path.schema_path == ["interfaces", "link", "*", "up"] {
+        Ok(())
+    } else {
+        Err(anyhow!("sourced key wasn't accepted"))
+    }
+}
+
+#[test]
+fn lookup_invalid_paths() -> anyhow::Result<()> {
+    let schema = common::get_introspected_schema()?;
+
+    match schema.lookup("system domain") {
+        Err(e) if matches!(e.downcast_ref(), Some(PathError::Unknown { .. })) => (),
+        _ => return Err(anyhow!("unknown property was found")),
+    }
+
+    match schema.lookup("system hostname router") {
+        Err(e) if matches!(e.downcast_ref(), Some(PathError::PastProperty { .. })) => Ok(()),
+        _ => Err(anyhow!("value was taken as a path element")),
+    }
+}
@@ -0,0 +1,90 @@
+mod common;
+
+use anyhow::anyhow;
+use rp_core::config::{Node, NodeName};
+use std::io::{Cursor, Seek, SeekFrom};
+
+#[test]
+fn migrate_old_save() -> anyhow::Result<()> {
+    let (config, _schema) = common::get_migrating_config()?;
+    config.load_config(common::get_old_version_save_data())?;
+
+    let simple = config
+        .get_node_with_name("singlenode")
+        .ok_or_else(|| anyhow!("'singlenode' node not in config"))?
+        .get_property_values(Some(String::from("simple")));
+
+    if simple.get("simple") != Some(&vec![String::from("b")]) {
+        return Err(anyhow!("renamed property not migrated: {:?}", simple));
+    }
+
+    let moved = config
+        .get_node_with_name("multinode")
+        .ok_or_else(|| anyhow!("'multinode' node not in config"))?
+        .get_node_with_name("1")?
+        .ok_or_else(|| anyhow!("node '1' not loaded into multinode"))?
+        .get_node_with_name("subnode")?
+        .ok_or_else(|| anyhow!("'subnode' not in node '1'"))?
+        .get_property_values(Some(String::from("simple")));
+
+    if moved.get("simple") != Some(&vec![String::from("a")]) {
+        Err(anyhow!("moved property not migrated: {:?}", moved))
+    } else {
+        Ok(())
+    }
+}
+
+#[test]
+fn rename_keeps_order() -> anyhow::Result<()> {
+    let (config, _schema) = common::get_ordered_migrating_config()?;
+    config.load_config(common::get_old_version_ordered_save_data())?;
+
+    let rules: Vec<String> = config
+        .get_node_with_name("rules")
+        .ok_or_else(|| anyhow!("'rules' node not in config"))?
+        .get_available_node_names()
+        .into_iter()
+        .filter_map(|name| match name {
+            NodeName::Literal(name) => Some(name),
+            NodeName::Multiple(_) => None,
+        })
+        .collect();
+
+    if rules == vec!["30", "25", "10"] {
+        Ok(())
+    } else {
+        Err(anyhow!("renamed rule moved: {:?}", rules))
+    }
+}
+
+#[test]
+fn load_newer_save() -> anyhow::Result<()> {
+    let (config, _schema) = common::get_migrating_config()?;
+
+    match config.load_config(common::get_newer_version_save_data()) {
+        Ok(_) => Err(anyhow!("loading a save newer than the schema succeeded")),
+        Err(e) => {
+            if let Some(rp_core::error::MigrationError::NewerSave { .. }) = e.downcast_ref() {
+                Ok(())
+            } else {
+                Err(e)
+            }
+        }
+    }
+}
+
+#[test]
+fn save_current_version() -> anyhow::Result<()> {
+    let (config, _schema) = common::get_migrating_config()?;
+    config.load_config(common::get_old_version_save_data())?;
+
+    let mut buf = Cursor::new(Vec::new());
+    config.save_config(&mut buf)?;
+    buf.seek(SeekFrom::Start(0))?;
+
+    // loading the save again must not run the migrations a second time, which would
+    // fail on the already moved property
+    let (reloaded, _schema) = common::get_migrating_config()?;
+    reloaded.load_config(buf)?;
+    Ok(())
+}
@@ -0,0 +1,200 @@
+mod common;
+
+use anyhow::anyhow;
+use rp_core::{
+    config::{Changeable, Config, ConfigNode, Node, NodeName, Position, Property},
+    error::OrderError,
+};
+use std::{
+    io::{Cursor, Seek, SeekFrom},
+    sync::Arc,
+};
+
+fn with_property<T>(
+    config: &Config,
+    property: &str,
+    f: impl FnOnce(&Property) -> anyhow::Result<T>,
+) -> anyhow::Result<T> {
+    f(config
+        .get_node_with_name("system")
+        .ok_or_else(|| anyhow!("'system' node not in config"))?
+        .get_property(property)
+        .ok_or_else(|| anyhow!("'system' node doesn't have property '{}'", property))?)
+}
+
+fn rules(config: &Config) -> anyhow::Result<Arc<ConfigNode>> {
+    config
+        .get_node_with_name("rules")
+        .ok_or_else(|| anyhow!("'rules' node not in config"))
+}
+
+fn nameservers(config: &Config) -> anyhow::Result<Vec<String>> {
+    with_property(config, "nameserver", |property| Ok(property.values()))
+}
+
+fn rule_names(config: &Config) -> anyhow::Result<Vec<String>> {
+    Ok(rules(config)?
+        .get_available_node_names()
+        .into_iter()
+        .filter_map(|name| match name {
+            NodeName::Literal(name) => Some(name),
+            NodeName::Multiple(_) => None,
+        })
+        .collect())
+}
+
+fn expect(actual: Vec<String>, expected: &[&str]) -> anyhow::Result<()> {
+    if actual == expected {
+        Ok(())
+    } else {
+        Err(anyhow!("wrong order: {:?}", actual))
+    }
+}
+
+#[test]
+fn values_keep_insertion_order() -> anyhow::Result<()> {
+    let (config, _schema) = common::get_ordered_config()?;
+    with_property(&config, "nameserver", |property| {
+        for value in &["c", "a", "b"] {
+            property.set(value)?;
+        }
+        Ok(())
+    })?;
+
+    expect(nameservers(&config)?, &["c", "a", "b"])
+}
+
+#[test]
+fn insert_and_move_values() -> anyhow::Result<()> {
+    let (config, _schema) = common::get_ordered_config()?;
+    with_property(&config, "nameserver", |property| {
+        property.set("a")?;
+        property.set("c")?;
+        property.insert("b", &Position::Before(String::from("c")))?;
+        property.insert("d", &Position::After(String::from("c")))?;
+        property.move_value("a", &Position::After(String::from("d")))
+    })?;
+
+    expect(nameservers(&config)?, &["b", "c", "d", "a"])
+}
+
+#[test]
+fn discard_restores_order() -> anyhow::Result<()> {
+    let (config, _schema) = common::get_ordered_config()?;
+    with_property(&config, "nameserver", |property| {
+        property.set("a")?;
+        property.set("b")?;
+        property.set("c")?;
+        Ok(())
+    })?;
+    config.apply_changes()?;
+
+    with_property(&config, "nameserver", |property| {
+        property.move_value("c", &Position::Before(String::from("a")))
+    })?;
+    if config.is_clean() {
+        return Err(anyhow!("moving a value isn't a change"));
+    }
+
+    config.discard_changes();
+    expect(nameservers(&config)?, &["a", "b", "c"])
+}
+
+#[test]
+fn order_survives_save_and_load() -> anyhow::Result<()> {
+    let (config, _schema) = common::get_ordered_config()?;
+    with_property(&config, "nameserver", |property| {
+        property.set("b")?;
+        property.set("a")?;
+        Ok(())
+    })?;
+    let rules_node = rules(&config)?;
+    for rule in &["20", "10", "30"] {
+        rules_node.get_node_with_name(rule)?;
+    }
+    rules_node.move_subnode("30", &Position::Before(String::from("20")))?;
+    config.apply_changes()?;
+
+    let mut buf = Cursor::new(Vec::new());
+    config.save_config(&mut buf)?;
+    buf.seek(SeekFrom::Start(0))?;
+
+    let (reloaded, _schema) = common::get_ordered_config()?;
+    reloaded.load_config(buf)?;
+
+    expect(nameservers(&reloaded)?, &["b", "a"])?;
+    expect(rule_names(&reloaded)?, &["30", "20", "10"])
+}
+
+#[test]
+fn reordered_defaults_survive_save_and_load() -> anyhow::Result<()> {
+    let (config, _schema) = common::get_ordered_config()?;
+    with_property(&config, "resolver", |property| {
+        property.move_value("1.0.0.1", &Position::Before(String::from("1.1.1.1")))
+    })?;
+    config.apply_changes()?;
+
+    let mut buf = Cursor::new(Vec::new());
+    config.save_config(&mut buf)?;
+    buf.seek(SeekFrom::Start(0))?;
+
+    let (reloaded, _schema) = common::get_ordered_config()?;
+    reloaded.load_config(buf)?;
+
+    expect(
+        with_property(&reloaded, "resolver", |property| Ok(property.values()))?,
+        &["1.0.0.1", "1.1.1.1"],
+    )
+}
+
+#[test]
+fn insert_node() -> anyhow::Result<()> {
+    let (config, _schema) = common::get_ordered_config()?;
+    let rules_node = rules(&config)?;
+    rules_node.get_node_with_name("10")?;
+    rules_node.get_node_with_name("30")?;
+    rules_node.insert_subnode("20", &Position::After(String::from("10")))?;
+
+    expect(rule_names(&config)?, &["10", "20", "30"])
+}
+
+#[test]
+fn move_unordered() -> anyhow::Result<()> {
+    let (config, _schema) = common::get_ordered_config()?;
+    let result = with_property(&config, "ntp", |property| {
+        property.set("a")?;
+        property.set("b")?;
+        property.move_value("b", &Position::Before(String::from("a")))
+    });
+
+    match result {
+        Ok(_) => Err(anyhow!("moving an unordered value succeeded")),
+        Err(e) => {
+            if let Some(OrderError::NotOrdered(_)) = e.downcast_ref() {
+                Ok(())
+            } else {
+                Err(e)
+            }
+        }
+    }
+}
+
+#[test]
+fn insert_next_to_nonexistent() -> anyhow::Result<()> {
+    let (config, _schema) = common::get_ordered_config()?;
+    let result = with_property(&config, "nameserver", |property| {
+        property.set("a")?;
+        property.insert("b", &Position::After(String::from("c")))
+    });
+
+    match result {
+        Ok(_) => Err(anyhow!("inserting next to a nonexistent value succeeded")),
+        Err(e) => {
+            if let Some(OrderError::NoSuchElement { .. }) = e.downcast_ref() {
+                expect(nameservers(&config)?, &["a"])
+            } else {
+                Err(e)
+            }
+        }
+    }
+}
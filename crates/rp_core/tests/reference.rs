@@ -0,0 +1,70 @@
+mod common;
+
+use anyhow::anyhow;
+use rp_core::schema::ReferenceFormat;
+
+fn expect(reference: &str, expected: &[&str]) -> anyhow::Result<()> {
+    match expected.iter().find(|line| !reference.contains(*line)) {
+        Some(line) => Err(anyhow!("'{}' missing from reference:\n{}", line, reference)),
+        None => Ok(()),
+    }
+}
+
+#[test]
+fn markdown_reference() -> anyhow::Result<()> {
+    let reference = common::get_introspected_schema()?.reference(ReferenceFormat::Markdown);
+
+    expect(
+        &reference,
+        &[
+            "- `vlan`: `integer [1, 4094]`",
+            "### `system`\n\n- `hostname`\n  - Values: 'router'\n  - Default: 'router'\n  - Flags: not deletable\n",
+            "### `interfaces ethernet`\n\nKeyed by names matching template 'ethernet'.\n",
+            "### `interfaces ethernet <interface> vlan <vlan>`",
+            "- `address`\n  - Values: template 'address'\n  - Flags: multiple\n",
+        ],
+    )
+}
+
+#[test]
+fn man_reference() -> anyhow::Result<()> {
+    let reference = common::get_introspected_schema()?.reference(ReferenceFormat::Man);
+
+    if !reference.starts_with(".TH ") {
+        return Err(anyhow!("man page doesn't start with a title"));
+    }
+
+    expect(
+        &reference,
+        &[
+            ".SS interfaces ethernet <interface>\n.TP\n.B mtu\nValues: 'auto'\n",
+            ".SS interfaces link\n.PP\nKeyed by names from the system only",
+            ".B hostname\nValues: 'router'\n.br\nDefault: 'router'\n",
+        ],
+    )
+}
+
+#[test]
+fn reference_sections_ordered() -> anyhow::Result<()> {
+    let reference = common::get_introspected_schema()?.reference(ReferenceFormat::Markdown);
+    let sections: Vec<&str> = reference
+        .lines()
+        .filter(|line| line.starts_with("### "))
+        .collect();
+
+    let expected = [
+        "### `interfaces`",
+        "### `interfaces ethernet`",
+        "### `interfaces ethernet <interface>`",
+        "### `interfaces ethernet <interface> vlan`",
+        "### `interfaces ethernet <interface> vlan <vlan>`",
+        "### `interfaces link`",
+        "### `interfaces link <link>`",
+        "### `system`",
+    ];
+    if sections == expected {
+        Ok(())
+    } else {
+        Err(anyhow!("unexpected sections {:?}", sections))
+    }
+}
@@ -0,0 +1,105 @@
+mod common;
+
+use anyhow::anyhow;
+use rp_core::config::{Changeable, Config, Node};
+
+fn set_route_interface(config: &Config, interface: &str) -> anyhow::Result<()> {
+    config
+        .get_node_with_name("route")
+        .ok_or_else(|| anyhow!("'route' node not in config"))?
+        .get_property("interface")
+        .ok_or_else(|| anyhow!("'route' node doesn't have property 'interface'"))?
+        .set(interface)
+}
+
+fn expect_reference_error(result: anyhow::Result<bool>) -> anyhow::Result<()> {
+    match result {
+        Ok(_) => Err(anyhow!("committing a dangling reference succeeded")),
+        Err(e) => {
+            if let Some(rp_core::error::ValidationError { violations }) = e.downcast_ref() {
+                match violations.as_slice() {
+                    [rp_core::error::Violation::Reference(_)] => Ok(()),
+                    _ => Err(anyhow!("wrong violations: {:?}", violations)),
+                }
+            } else {
+                Err(e)
+            }
+        }
+    }
+}
+
+#[test]
+fn reference_existing_node() -> anyhow::Result<()> {
+    let (config, _schema) = common::get_referencing_config()?;
+    let interfaces = config
+        .get_node_with_name("interfaces")
+        .ok_or_else(|| anyhow!("'interfaces' node not in config"))?;
+
+    // the referenced node only has to exist once the changes are applied
+    set_route_interface(&config, "eth0")?;
+    interfaces.get_node_with_name("eth0")?;
+
+    config.apply_changes()?;
+    Ok(())
+}
+
+#[test]
+fn reference_literal() -> anyhow::Result<()> {
+    let (config, _schema) = common::get_referencing_config()?;
+
+    set_route_interface(&config, "blackhole")?;
+    config.apply_changes()?;
+    Ok(())
+}
+
+#[test]
+fn dangling_reference() -> anyhow::Result<()> {
+    let (config, _schema) = common::get_referencing_config()?;
+
+    set_route_interface(&config, "eth9")?;
+    expect_reference_error(config.apply_changes())?;
+
+    if config.is_clean() {
+        Err(anyhow!("changes applied despite a dangling reference"))
+    } else {
+        Ok(())
+    }
+}
+
+#[test]
+fn remove_referenced_node() -> anyhow::Result<()> {
+    let (config, _schema) = common::get_referencing_config()?;
+    let interfaces = config
+        .get_node_with_name("interfaces")
+        .ok_or_else(|| anyhow!("'interfaces' node not in config"))?;
+
+    interfaces.get_node_with_name("eth0")?;
+    set_route_interface(&config, "eth0")?;
+    config.apply_changes()?;
+
+    interfaces.remove_subnode("eth0")?;
+    expect_reference_error(config.apply_changes())
+}
+
+#[test]
+fn reference_candidates() -> anyhow::Result<()> {
+    let (config, _schema) = common::get_referencing_config()?;
+    let interfaces = config
+        .get_node_with_name("interfaces")
+        .ok_or_else(|| anyhow!("'interfaces' node not in config"))?;
+    interfaces.get_node_with_name("eth0")?;
+
+    let route = config
+        .get_node_with_name("route")
+        .ok_or_else(|| anyhow!("'route' node not in config"))?;
+    let candidates = route
+        .get_property("interface")
+        .ok_or_else(|| anyhow!("'route' node doesn't have property 'interface'"))?
+        .reference_candidates(&config)?;
+
+    if candidates == vec![String::from("eth0")] {
+        Ok(())
+    } else {
+        Err(anyhow!("wrong reference candidates: {:?}", candidates))
+    }
+}
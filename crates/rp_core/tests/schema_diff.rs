@@ -0,0 +1,93 @@
+mod common;
+
+use anyhow::anyhow;
+use rp_core::schema::{Change, Compatibility, SchemaDiff};
+
+fn change(diff: &SchemaDiff, path: &str, change: Change) -> anyhow::Result<Compatibility> {
+    let path: Vec<&str> = path.split(' ').collect();
+    diff.changes
+        .iter()
+        .find(|c| c.path == path && c.change == change)
+        .map(|c| c.compatibility)
+        .ok_or_else(|| {
+            anyhow!(
+                "no '{:?}' at '{}' in diff:\n{}",
+                change,
+                path.join(" "),
+                diff
+            )
+        })
+}
+
+#[test]
+fn identical_schemas() -> anyhow::Result<()> {
+    let (older, _newer) = common::get_diffed_schemas()?;
+    let diff = older.diff(&older)?;
+
+    if diff.changes.is_empty() && diff.is_compatible() {
+        Ok(())
+    } else {
+        Err(anyhow!("schema differs from itself:\n{}", diff))
+    }
+}
+
+#[test]
+fn compatible_changes() -> anyhow::Result<()> {
+    let (older, newer) = common::get_diffed_schemas()?;
+    let diff = older.diff(&newer)?;
+
+    for (path, kind) in &[
+        ("services", Change::NodeAdded),
+        ("system hostname", Change::ValuesWidened),
+        ("system timezone", Change::DefaultChanged),
+        ("mtu", Change::TemplateChanged),
+        // migrations take care of these
+        ("system banner", Change::PropertyRemoved),
+        ("interfaces * duplex", Change::ValuesNarrowed),
+    ] {
+        if change(&diff, path, kind.clone())? != Compatibility::Compatible {
+            return Err(anyhow!("change at '{}' isn't compatible", path));
+        }
+    }
+
+    Ok(())
+}
+
+#[test]
+fn breaking_changes() -> anyhow::Result<()> {
+    let (older, newer) = common::get_diffed_schemas()?;
+    let diff = older.diff(&newer)?;
+
+    for (path, kind) in &[
+        ("system motd", Change::PropertyRemoved),
+        ("system domain", Change::MultipleToggled(false)),
+        ("system contact", Change::PropertyAdded),
+        ("interfaces * speed", Change::ValuesNarrowed),
+    ] {
+        if change(&diff, path, kind.clone())? != Compatibility::Breaking {
+            return Err(anyhow!("change at '{}' isn't breaking", path));
+        }
+    }
+
+    if diff.is_compatible() || diff.breaking().count() != 4 {
+        Err(anyhow!("unexpected breaking changes:\n{}", diff))
+    } else {
+        Ok(())
+    }
+}
+
+#[test]
+fn reversed_diff() -> anyhow::Result<()> {
+    let (older, newer) = common::get_diffed_schemas()?;
+    let diff = newer.diff(&older)?;
+
+    if change(&diff, "system domain", Change::MultipleToggled(true))? != Compatibility::Compatible {
+        Err(anyhow!("allowing multiple values isn't compatible"))
+    } else if change(&diff, "mtu", Change::TemplateChanged)? != Compatibility::Breaking {
+        Err(anyhow!("narrowing a range template isn't breaking"))
+    } else if change(&diff, "services", Change::NodeRemoved)? != Compatibility::Breaking {
+        Err(anyhow!("removing a node isn't breaking"))
+    } else {
+        Ok(())
+    }
+}
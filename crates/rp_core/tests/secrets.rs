@@ -0,0 +1,121 @@
+mod common;
+
+use anyhow::anyhow;
+use rp_core::{
+    config::{Changeable, Config, Node, Property},
+    error::{ConstraintError, PropertyError},
+};
+use std::io::{Cursor, Seek, SeekFrom};
+
+fn user_property<T>(
+    config: &Config,
+    name: &str,
+    f: impl FnOnce(&Property) -> anyhow::Result<T>,
+) -> anyhow::Result<T> {
+    f(config
+        .get_node_with_name("user")
+        .ok_or_else(|| anyhow!("'user' node not in config"))?
+        .get_property(name)
+        .ok_or_else(|| anyhow!("'user' node doesn't have property '{}'", name))?)
+}
+
+#[test]
+fn password_hashed_on_set() -> anyhow::Result<()> {
+    let (config, _schema) = common::get_secret_config()?;
+    user_property(&config, "password", |property| property.set("hunter2"))?;
+    config.apply_changes()?;
+
+    let mut buf = Cursor::new(Vec::new());
+    config.save_config(&mut buf)?;
+    let save = String::from_utf8(buf.get_ref().clone())?;
+
+    let hash = user_property(&config, "password", |property| Ok(property.values()))?;
+    match hash.first() {
+        Some(hash) if hash.starts_with("$6$") && save.contains(hash.as_str()) => (),
+        _ => return Err(anyhow!("password not saved as a hash: {:?}", hash)),
+    }
+
+    if save.contains("hunter2") {
+        Err(anyhow!("plaintext password in save: {}", save))
+    } else {
+        Ok(())
+    }
+}
+
+#[test]
+fn hash_kept_on_load() -> anyhow::Result<()> {
+    let (config, _schema) = common::get_secret_config()?;
+    user_property(&config, "password", |property| property.set("hunter2"))?;
+    config.apply_changes()?;
+
+    let mut buf = Cursor::new(Vec::new());
+    config.save_config(&mut buf)?;
+    buf.seek(SeekFrom::Start(0))?;
+
+    // the hash isn't hashed again, so the loaded config is the same as the saved one
+    let (reloaded, _schema) = common::get_secret_config()?;
+    reloaded.load_config(buf)?;
+
+    let saved = user_property(&config, "password", |property| Ok(property.values()))?;
+    let loaded = user_property(&reloaded, "password", |property| {
+        Ok(property.pending_values())
+    })?;
+
+    if saved == loaded {
+        Ok(())
+    } else {
+        Err(anyhow!("hash changed on load: {:?} -> {:?}", saved, loaded))
+    }
+}
+
+#[test]
+fn secret_masked_in_errors() -> anyhow::Result<()> {
+    let (config, _schema) = common::get_secret_config()?;
+
+    user_property(&config, "token", |property| {
+        match property.set("hunter2") {
+            Ok(_) => return Err(anyhow!("setting a disallowed value succeeded")),
+            Err(e) => match e.downcast_ref() {
+                Some(ConstraintError { given, .. }) if given != "hunter2" => (),
+                _ => return Err(e),
+            },
+        }
+
+        property.set("abc")?;
+        match property.remove(Some("hunter2")) {
+            Ok(_) => Err(anyhow!("removing a nonexistent value succeeded")),
+            Err(e) => match e.downcast_ref() {
+                Some(PropertyError::NoSuchValue(value)) if value != "hunter2" => Ok(()),
+                _ => Err(e),
+            },
+        }
+    })
+}
+
+#[test]
+fn password_removed_by_plaintext() -> anyhow::Result<()> {
+    let (config, _schema) = common::get_secret_config()?;
+    user_property(&config, "password", |property| property.set("hunter2"))?;
+    config.apply_changes()?;
+
+    // the stored hash is never shown, so the password is all there is to go by
+    user_property(&config, "password", |property| {
+        match property.remove(Some("hunter3")) {
+            Ok(_) => return Err(anyhow!("removing with the wrong password succeeded")),
+            Err(e) => match e.downcast_ref() {
+                Some(PropertyError::NoSuchValue(_)) => (),
+                _ => return Err(e),
+            },
+        }
+
+        property.remove(Some("hunter2"))
+    })?;
+    config.apply_changes()?;
+
+    let values = user_property(&config, "password", |property| Ok(property.values()))?;
+    if values.is_empty() {
+        Ok(())
+    } else {
+        Err(anyhow!("password not removed: {:?}", values))
+    }
+}
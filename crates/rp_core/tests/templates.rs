@@ -0,0 +1,72 @@
+mod common;
+
+use anyhow::anyhow;
+use rp_core::{
+    config::{Config, Node, Property},
+    error::ConstraintError,
+};
+
+fn system_property<T>(
+    config: &Config,
+    name: &str,
+    f: impl FnOnce(&Property) -> anyhow::Result<T>,
+) -> anyhow::Result<T> {
+    f(config
+        .get_node_with_name("system")
+        .ok_or_else(|| anyhow!("'system' node not in config"))?
+        .get_property(name)
+        .ok_or_else(|| anyhow!("'system' node doesn't have property '{}'", name))?)
+}
+
+fn expect_constraint_error(result: anyhow::Result<()>) -> anyhow::Result<()> {
+    match result {
+        Ok(_) => Err(anyhow!(
+            "setting a value the template doesn't match succeeded"
+        )),
+        Err(e) => {
+            if let Some(ConstraintError { .. }) = e.downcast_ref() {
+                Ok(())
+            } else {
+                Err(e)
+            }
+        }
+    }
+}
+
+#[test]
+fn any_of_references() -> anyhow::Result<()> {
+    let (config, _schema) = common::get_composite_template_config()?;
+
+    system_property(&config, "server", |property| {
+        property.set("Router")?;
+        if property.pending_values() != vec![String::from("router")] {
+            return Err(anyhow!(
+                "value not normalised by the referenced template: {:?}",
+                property.pending_values()
+            ));
+        }
+
+        property.set("10.0.0.1")?;
+        expect_constraint_error(property.set("-router"))
+    })
+}
+
+#[test]
+fn all_of_with_length() -> anyhow::Result<()> {
+    let (config, _schema) = common::get_composite_template_config()?;
+
+    system_property(&config, "name", |property| {
+        property.set("abcdefgh")?;
+        expect_constraint_error(property.set("abcdefghi"))
+    })
+}
+
+#[test]
+fn not_template() -> anyhow::Result<()> {
+    let (config, _schema) = common::get_composite_template_config()?;
+
+    system_property(&config, "hostname", |property| {
+        property.set("router")?;
+        expect_constraint_error(property.set("localhost"))
+    })
+}
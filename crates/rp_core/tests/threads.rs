@@ -0,0 +1,286 @@
+mod common;
+
+use anyhow::anyhow;
+use rp_core::{
+    common::Changes,
+    config::{Changeable, Config, ConfigNode, Node, NodeName, Property},
+    error::{ElementCountError, ValidationError},
+    schema::{Schema, Template},
+};
+use std::{
+    sync::{
+        atomic::{AtomicBool, Ordering},
+        mpsc, Arc,
+    },
+    thread,
+    time::Duration,
+};
+
+fn shared<T: Send + Sync>() {}
+
+#[test]
+fn shared_types() {
+    shared::<Schema>();
+    shared::<Template>();
+    shared::<Config>();
+    shared::<ConfigNode>();
+    shared::<Property>();
+}
+
+#[test]
+fn concurrent_edits() -> anyhow::Result<()> {
+    let (config, _schema) = common::get_exported_config()?;
+    let config = Arc::new(config);
+
+    let threads: Vec<_> = (0..4)
+        .map(|index| {
+            let config = Arc::clone(&config);
+            thread::spawn(move || -> anyhow::Result<()> {
+                let interfaces = config
+                    .get_node_with_name("interfaces")
+                    .ok_or_else(|| anyhow!("interfaces node not in config"))?;
+                let interface = interfaces
+                    .get_node_with_name(&format!("eth{}", index))?
+                    .ok_or_else(|| anyhow!("interface not created"))?;
+                interface
+                    .get_property("mtu")
+                    .ok_or_else(|| anyhow!("mtu property not in interface"))?
+                    .set(&(1500 + index).to_string())
+            })
+        })
+        .collect();
+
+    for thread in threads {
+        thread
+            .join()
+            .map_err(|_| anyhow!("editing thread panicked"))??;
+    }
+
+    config.validate()?;
+    let interfaces = config
+        .get_node_with_name("interfaces")
+        .ok_or_else(|| anyhow!("interfaces node not in config"))?;
+    let count = interfaces
+        .get_available_node_names()
+        .iter()
+        .filter(|name| matches!(name, NodeName::Literal(_)))
+        .count();
+
+    if count == 4 {
+        Ok(())
+    } else {
+        Err(anyhow!("{} interfaces created instead of 4", count))
+    }
+}
+
+#[test]
+fn concurrent_creation() -> anyhow::Result<()> {
+    let (config, _schema) = common::get_exported_config()?;
+    let interfaces = config
+        .get_node_with_name("interfaces")
+        .ok_or_else(|| anyhow!("interfaces node not in config"))?;
+
+    let threads: Vec<_> = (0..4)
+        .map(|_| {
+            let interfaces = Arc::clone(&interfaces);
+            thread::spawn(move || -> anyhow::Result<Arc<ConfigNode>> {
+                interfaces
+                    .get_node_with_name("eth0")?
+                    .ok_or_else(|| anyhow!("interface not created"))
+            })
+        })
+        .collect();
+
+    let mut created = Vec::new();
+    for thread in threads {
+        created.push(
+            thread
+                .join()
+                .map_err(|_| anyhow!("creating thread panicked"))??,
+        );
+    }
+
+    // everyone gets the one node that's in the config
+    let kept = interfaces
+        .get_node_with_name("eth0")?
+        .ok_or_else(|| anyhow!("interface not in config"))?;
+    if created.iter().all(|node| Arc::ptr_eq(node, &kept)) {
+        Ok(())
+    } else {
+        Err(anyhow!("concurrently created nodes replaced each other"))
+    }
+}
+
+#[test]
+fn concurrent_count_limits() -> anyhow::Result<()> {
+    let (config, _schema) = common::get_element_count_config()?;
+    let config = Arc::new(config);
+
+    // two users and two nameservers at most, however many are added at once
+    let threads: Vec<_> = (0..8)
+        .map(|index| {
+            let config = Arc::clone(&config);
+            thread::spawn(move || -> anyhow::Result<(bool, bool)> {
+                let users = config
+                    .get_node_with_name("users")
+                    .ok_or_else(|| anyhow!("users node not in config"))?;
+                let user = allowed(users.get_node_with_name(&format!("user{}", index)))?;
+                let nameserver = allowed(
+                    config
+                        .get_node_with_name("system")
+                        .ok_or_else(|| anyhow!("system node not in config"))?
+                        .get_property("nameserver")
+                        .ok_or_else(|| anyhow!("nameserver property not in system"))?
+                        .set(&format!("192.0.2.{}", index)),
+                )?;
+                Ok((user, nameserver))
+            })
+        })
+        .collect();
+
+    let (mut users, mut nameservers) = (0, 0);
+    for thread in threads {
+        let (user, nameserver) = thread
+            .join()
+            .map_err(|_| anyhow!("editing thread panicked"))??;
+        users += user as usize;
+        nameservers += nameserver as usize;
+    }
+
+    let system = config
+        .get_node_with_name("system")
+        .ok_or_else(|| anyhow!("system node not in config"))?;
+    let pending = system
+        .get_property("nameserver")
+        .ok_or_else(|| anyhow!("nameserver property not in system"))?
+        .pending_values()
+        .len();
+
+    if users == 2 && nameservers == 2 && pending == 2 {
+        Ok(())
+    } else {
+        Err(anyhow!(
+            "{} users and {} ({} pending) nameservers added, expected 2 each",
+            users,
+            nameservers,
+            pending
+        ))
+    }
+}
+
+#[test]
+fn edits_during_apply() -> anyhow::Result<()> {
+    let (config, _schema) = common::get_conditional_config()?;
+    let config = Arc::new(config);
+    let interfaces = config
+        .get_node_with_name("interfaces")
+        .ok_or_else(|| anyhow!("interfaces node not in config"))?;
+
+    // enough interfaces for applying to take a while
+    for index in 0..200 {
+        let interface = interfaces
+            .get_node_with_name(&format!("eth{}", index))?
+            .ok_or_else(|| anyhow!("interface not created"))?;
+        interface_property(&interface, "address")?.set("dhcp")?;
+        interface_property(&interface, "dhcp-hostname")?.set("host")?;
+    }
+    config.apply_changes()?;
+
+    let eth0 = interfaces
+        .get_node_with_name("eth0")?
+        .ok_or_else(|| anyhow!("interface not in config"))?;
+
+    for _ in 0..20 {
+        // the hostname needs the address, so the removal can't be applied
+        let address = Arc::clone(&eth0);
+        let edit = thread::spawn(move || -> anyhow::Result<()> {
+            interface_property(&address, "address")?.remove(Some("dhcp"))
+        });
+
+        match config.apply_changes() {
+            Err(e) if e.downcast_ref::<ValidationError>().is_none() => return Err(e),
+            _ => (),
+        }
+        edit.join()
+            .map_err(|_| anyhow!("editing thread panicked"))??;
+
+        // the removal was either rejected with the rest or left pending
+        config.discard_changes();
+        config.validate()?;
+    }
+
+    Ok(())
+}
+
+#[test]
+fn apply_within_edit() -> anyhow::Result<()> {
+    let changes = Arc::new(Changes::default());
+    let _edit = changes.edit();
+
+    // applying in the middle of an edit still waits for other threads' edits
+    let other_edited = Arc::new(AtomicBool::new(false));
+    let (started, edit_started) = mpsc::channel();
+    let other = {
+        let changes = Arc::clone(&changes);
+        let other_edited = Arc::clone(&other_edited);
+        thread::spawn(move || {
+            let _edit = changes.edit();
+            started.send(()).ok();
+            thread::sleep(Duration::from_millis(50));
+            other_edited.store(true, Ordering::SeqCst);
+        })
+    };
+    edit_started.recv()?;
+
+    let apply = changes.apply()?;
+    if !other_edited.load(Ordering::SeqCst) {
+        return Err(anyhow!(
+            "changes applied in the middle of another thread's edit"
+        ));
+    }
+    other
+        .join()
+        .map_err(|_| anyhow!("editing thread panicked"))?;
+
+    // and their edits wait for the changes to be applied
+    let edited = Arc::new(AtomicBool::new(false));
+    let other = {
+        let changes = Arc::clone(&changes);
+        let edited = Arc::clone(&edited);
+        thread::spawn(move || {
+            let _edit = changes.edit();
+            edited.store(true, Ordering::SeqCst);
+        })
+    };
+    thread::sleep(Duration::from_millis(50));
+    if edited.load(Ordering::SeqCst) {
+        return Err(anyhow!("edit made while changes were being applied"));
+    }
+
+    drop(apply);
+    other
+        .join()
+        .map_err(|_| anyhow!("editing thread panicked"))?;
+    if edited.load(Ordering::SeqCst) {
+        Ok(())
+    } else {
+        Err(anyhow!("edit not made after the changes were applied"))
+    }
+}
+
+fn interface_property<'a>(interface: &'a ConfigNode, name: &str) -> anyhow::Result<&'a Property> {
+    interface
+        .get_property(name)
+        .ok_or_else(|| anyhow!("'{}' property not in interface", name))
+}
+
+// false if the count limit stopped the addition
+fn allowed<T>(result: anyhow::Result<T>) -> anyhow::Result<bool> {
+    match result {
+        Ok(_) => Ok(true),
+        Err(e) => match e.downcast_ref() {
+            Some(ElementCountError::TooMany { .. }) => Ok(false),
+            _ => Err(e),
+        },
+    }
+}
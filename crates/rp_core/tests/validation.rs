@@ -0,0 +1,134 @@
+mod common;
+
+use anyhow::anyhow;
+use rp_core::{
+    config::{Changeable, Config, Node},
+    error::{ValidationError, Violation},
+};
+
+fn set(config: &Config, node: &str, property: &str, value: &str) -> anyhow::Result<()> {
+    config
+        .get_node_with_name(node)
+        .ok_or_else(|| anyhow!("'{}' node not in config", node))?
+        .get_property(property)
+        .ok_or_else(|| anyhow!("'{}' node doesn't have property '{}'", node, property))?
+        .set(value)
+}
+
+// the paths of every missing mandatory property, sorted
+fn missing_mandatory(result: anyhow::Result<bool>) -> anyhow::Result<Vec<String>> {
+    match result {
+        Ok(_) => Err(anyhow!("applying an invalid config succeeded")),
+        Err(e) => {
+            if let Some(ValidationError { violations }) = e.downcast_ref() {
+                let mut paths = Vec::new();
+                for violation in violations {
+                    match violation {
+                        Violation::MissingMandatory(path) => paths.push(path.to_owned()),
+                        _ => return Err(anyhow!("unexpected violation: {}", violation)),
+                    }
+                }
+                paths.sort();
+                Ok(paths)
+            } else {
+                Err(e)
+            }
+        }
+    }
+}
+
+#[test]
+fn collect_missing_mandatory() -> anyhow::Result<()> {
+    let (config, _schema) = common::get_mandatory_config()?;
+    set(&config, "system", "location", "basement")?;
+
+    let paths = missing_mandatory(config.apply_changes())?;
+    if paths
+        != vec![
+            String::from("system domain"),
+            String::from("system hostname"),
+        ]
+    {
+        Err(anyhow!("wrong violations: {:?}", paths))
+    } else if config.is_clean() {
+        Err(anyhow!("changes applied despite violations"))
+    } else {
+        Ok(())
+    }
+}
+
+#[test]
+fn mandatory_in_multinode() -> anyhow::Result<()> {
+    let (config, _schema) = common::get_mandatory_config()?;
+    set(&config, "system", "hostname", "router")?;
+    set(&config, "system", "domain", "example.com")?;
+    config
+        .get_node_with_name("users")
+        .ok_or_else(|| anyhow!("'users' node not in config"))?
+        .get_node_with_name("admin")?;
+
+    let paths = missing_mandatory(config.apply_changes())?;
+    if paths == vec![String::from("users admin password")] {
+        Ok(())
+    } else {
+        Err(anyhow!("wrong violations: {:?}", paths))
+    }
+}
+
+#[test]
+fn remove_mandatory() -> anyhow::Result<()> {
+    let (config, _schema) = common::get_mandatory_config()?;
+    set(&config, "system", "hostname", "router")?;
+    set(&config, "system", "domain", "example.com")?;
+    config.apply_changes()?;
+
+    config
+        .get_node_with_name("system")
+        .ok_or_else(|| anyhow!("'system' node not in config"))?
+        .get_property("domain")
+        .ok_or_else(|| anyhow!("'system' node doesn't have property 'domain'"))?
+        .remove(None)?;
+
+    let paths = missing_mandatory(config.apply_changes())?;
+    if paths == vec![String::from("system domain")] {
+        Ok(())
+    } else {
+        Err(anyhow!("wrong violations: {:?}", paths))
+    }
+}
+
+#[test]
+fn load_missing_mandatory() -> anyhow::Result<()> {
+    let (config, _schema) = common::get_mandatory_config()?;
+    let violations = config
+        .load_config(common::get_missing_mandatory_save_data())?
+        .ok_or_else(|| anyhow!("loading an invalid save reported no problems"))?;
+
+    let paths = missing_mandatory(Err(violations.into()))?;
+    if paths
+        != vec![
+            String::from("system domain"),
+            String::from("system hostname"),
+        ]
+    {
+        return Err(anyhow!("wrong violations: {:?}", paths));
+    }
+
+    let location = config
+        .get_node_with_name("system")
+        .ok_or_else(|| anyhow!("'system' node not in config"))?
+        .get_property("location")
+        .ok_or_else(|| anyhow!("'system' node doesn't have property 'location'"))?
+        .values();
+    if location != vec![String::from("basement")] || !config.is_clean() {
+        return Err(anyhow!("loaded values not kept: {:?}", location));
+    }
+
+    // the whole config is checked on applying until it's fixed, not just the changes
+    set(&config, "system", "location", "attic")?;
+    missing_mandatory(config.apply_changes())?;
+    set(&config, "system", "hostname", "router")?;
+    set(&config, "system", "domain", "example.com")?;
+    config.apply_changes()?;
+    Ok(())
+}
@@ -10,12 +10,11 @@ fn main() -> anyhow::Result<()> {
 
     let dest_path = Path::new(&out_dir).join("schema");
 
-    let schema_file = File::open(&schema_path)?;
-    let mut schema = Schema::from_yaml_file(&schema_file)?;
+    // the schema is split into files that include each other
+    let mut schema = Schema::from_yaml_path(&schema_path)?;
 
-    let debug_schema_file = File::open(&debug_schema_path)?;
     schema.merge(
-        Schema::from_yaml_file(&debug_schema_file)?,
+        Schema::from_yaml_path(&debug_schema_path)?,
         MergingStrategy::Ours,
     )?;
 
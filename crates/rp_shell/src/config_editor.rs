@@ -1,20 +1,21 @@
 use crate::error;
 use rp_core::{
-    config::{Changeable, Config, ConfigNode, Node, NodeName, Property},
+    config::{Changeable, Config, ConfigNode, Node, NodeName, Position, Property},
     schema::Schema,
 };
+use rp_log::*;
 use std::{
     collections::HashMap,
     fs::OpenOptions,
     path::{Path, PathBuf},
-    rc::Rc,
+    sync::Arc,
 };
 
 #[derive(Debug)]
 pub struct ConfigEditor<'a> {
     schema: &'a Schema,
     config: &'a Config,
-    node_stack: Vec<Rc<ConfigNode>>,
+    node_stack: Vec<Arc<ConfigNode>>,
     pub save_directory: PathBuf,
     pub save_filename: PathBuf,
 }
@@ -63,6 +64,42 @@ impl<'a> ConfigEditor<'a> {
         names
     }
 
+    pub fn get_current_description(&self) -> Option<String> {
+        self.node_stack.last().and_then(|n| n.description())
+    }
+
+    // descriptions of every node and property available in the current node
+    pub fn get_descriptions(&self) -> Vec<(String, Option<String>)> {
+        let mut descriptions = Vec::new();
+        for name in match self.node_stack.last() {
+            Some(n) => n.get_available_node_names(),
+            None => self.config.get_available_node_names(),
+        } {
+            let description = match &name {
+                NodeName::Literal(literal) => {
+                    self.get_node_description(literal).unwrap_or_default()
+                }
+                NodeName::Multiple(_) => None,
+            };
+            descriptions.push((format!("{}", name), description));
+        }
+
+        if let Some(n) = self.node_stack.last() {
+            for name in n.get_available_property_names() {
+                let description = n
+                    .get_property(&name)
+                    .and_then(|p| p.description().map(|d| d.to_owned()));
+                descriptions.push((name, description));
+            }
+        }
+
+        descriptions
+    }
+
+    pub fn get_node_description(&self, name: &str) -> anyhow::Result<Option<String>> {
+        Ok(self.get_subnode(name)?.description())
+    }
+
     pub fn edit_node(&mut self, name: &str) -> anyhow::Result<()> {
         let mut matching_name: Option<NodeName> = None;
         for node_name in match self.node_stack.last() {
@@ -112,7 +149,7 @@ impl<'a> ConfigEditor<'a> {
         }
     }
 
-    fn get_property(&self, property: &str) -> anyhow::Result<&Property> {
+    pub fn get_property(&self, property: &str) -> anyhow::Result<&Property> {
         Ok(self
             .node_stack
             .last()
@@ -130,6 +167,12 @@ impl<'a> ConfigEditor<'a> {
             .map(|n| n.get_property_values(of_property))
     }
 
+    // existing node names a property referring to other nodes may be set to
+    pub fn get_value_candidates(&self, property: &str) -> anyhow::Result<Vec<String>> {
+        self.get_property(property)?
+            .reference_candidates(self.config)
+    }
+
     pub fn set_property_value(&self, property: &str, value: &str) -> anyhow::Result<()> {
         let property = self.get_property(property)?;
         Ok(property.set(value)?)
@@ -153,6 +196,54 @@ impl<'a> ConfigEditor<'a> {
         }
     }
 
+    // puts a new value or node next to an existing one in an ordered property or
+    // multi node. properties are looked up first like when removing
+    pub fn insert(&self, item: &str, value: &str, position: &Position) -> anyhow::Result<()> {
+        if self.is_current_property(item) {
+            return self.get_property(item)?.insert(value, position);
+        }
+
+        self.get_subnode(item)?.insert_subnode(value, position)?;
+        Ok(())
+    }
+
+    pub fn move_item(&self, item: &str, value: &str, position: &Position) -> anyhow::Result<()> {
+        if self.is_current_property(item) {
+            return self.get_property(item)?.move_value(value, position);
+        }
+
+        self.get_subnode(item)?.move_subnode(value, position)
+    }
+
+    fn is_current_property(&self, item: &str) -> bool {
+        self.get_available_properties()
+            .map_or(false, |properties| properties.iter().any(|p| p == item))
+    }
+
+    // an existing subnode of the current node
+    fn get_subnode(&self, name: &str) -> anyhow::Result<Arc<ConfigNode>> {
+        let literal_exists = match self.node_stack.last() {
+            Some(n) => n.get_available_node_names(),
+            None => self.config.get_available_node_names(),
+        }
+        .iter()
+        .any(|node_name| match node_name {
+            NodeName::Literal(literal) => literal == name,
+            NodeName::Multiple(_) => false,
+        });
+
+        // looking up a name that isn't there would create a new node in a multi node
+        if !literal_exists {
+            return Err(error::ConfigEditorError::NodeNotFound(name.to_string()).into());
+        }
+
+        Ok(match self.node_stack.last() {
+            Some(n) => n.get_node_with_name(name)?,
+            None => self.config.get_node_with_name(name),
+        }
+        .ok_or_else(|| error::ConfigEditorError::NodeNotFound(name.to_string()))?)
+    }
+
     pub fn is_clean(&self) -> bool {
         self.config.is_clean()
     }
@@ -161,6 +252,12 @@ impl<'a> ConfigEditor<'a> {
         self.config.apply_changes()
     }
 
+    // picks up the nodes that appeared in or disappeared from the system since
+    // the config was built or last refreshed
+    pub fn refresh_sources(&self) -> anyhow::Result<bool> {
+        self.config.refresh_sources()
+    }
+
     pub fn discard_changes(&self) {
         self.config.discard_changes();
     }
@@ -190,7 +287,17 @@ impl<'a> ConfigEditor<'a> {
         let file = OpenOptions::new()
             .read(true)
             .open(self.save_directory.join(name))?;
-        self.config.load_config(file)
+
+        // the loaded config is kept even if it's invalid, it's just not applied
+        // any further until fixed
+        if let Some(violations) = self.config.load_config(file)? {
+            warn!(
+                "Loaded configuration is invalid, fix it before applying any changes. {}",
+                violations
+            );
+        }
+
+        Ok(())
     }
 }
 
@@ -199,6 +306,22 @@ impl<'a> ConfigEditor<'a> {
         self.config.pretty_print();
     }
 
+    // as a JSON Schema for saves, or as the API model with every node and property
+    pub fn print_schema(&self, model: bool) -> anyhow::Result<()> {
+        if model {
+            println!("{:#}", self.schema.to_api_model()?);
+        } else {
+            println!("{:#}", self.schema.to_json_schema());
+        }
+        Ok(())
+    }
+
+    // what the schema has at a path, and what can follow it
+    pub fn print_schema_path(&self, path: &[String]) -> anyhow::Result<()> {
+        print!("{}", self.schema.lookup_at(path)?);
+        Ok(())
+    }
+
     pub fn pretty_print_current_node(&self) {
         match self.node_stack.last() {
             Some(n) => n.pretty_print(0),
@@ -6,4 +6,8 @@ use thiserror::Error;
 pub enum GeneralError {
     #[error("Invalid shell mode {mode:?} for command '{command}'")]
     InvalidModeForCommand { command: String, mode: ShellMode },
+    #[error("Usage: {0}")]
+    Usage(&'static str),
+    #[error("{0} breaking schema change(s)")]
+    BreakingSchemaChanges(usize),
 }
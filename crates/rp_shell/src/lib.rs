@@ -3,31 +3,30 @@
 mod config_editor;
 pub mod error;
 mod shell;
+mod sources;
 
 pub use config_editor::ConfigEditor;
 use rp_core::{
     common::{CommandMetadata, ShellMode},
     config::Config,
     plugin::PluginManager,
-    schema::Schema,
+    schema::{ReferenceFormat, Schema},
 };
 use rp_log::*;
 use shell::{ExecutableCommand, Shell};
-use std::{rc::Rc, time::Instant};
+use std::{fs, path::Path, sync::Arc, time::Instant};
 
 pub async fn run() -> anyhow::Result<()> {
     setup_logging()?;
-
-    let binary = include_bytes!(concat!(env!("OUT_DIR"), "/schema"));
-    trace!("Schema binary: {} bytes", binary.len());
+    sources::register()?;
 
     let start = Instant::now();
-    let schema = Rc::new(Schema::from_binary(binary)?);
+    let schema = Arc::new(builtin_schema()?);
     debug!("Schema loaded in {}ms", start.elapsed().as_millis());
     schema.print_trace_info();
 
     let start = Instant::now();
-    let config = Config::from_schema(Rc::downgrade(&schema))?;
+    let config = Config::from_schema(Arc::downgrade(&schema))?;
     let mut editor = ConfigEditor::new(&config, &schema);
     debug!("Config created in {}ms", start.elapsed().as_millis());
 
@@ -59,6 +58,87 @@ pub async fn run() -> anyhow::Result<()> {
     Ok(())
 }
 
+fn builtin_schema() -> anyhow::Result<Schema> {
+    let binary = include_bytes!(concat!(env!("OUT_DIR"), "/schema"));
+    trace!("Schema binary: {} bytes", binary.len());
+    Schema::from_binary(binary)
+}
+
+// compares an older schema to a newer one, the built-in one by default, to tell
+// whether configs survive an upgrade. schemas are read from YAML files or built
+// schema binaries
+pub fn diff_schema(args: &[String]) -> anyhow::Result<()> {
+    const USAGE: &str = "rp_shell diff-schema <older schema> [<newer schema>]";
+
+    let (older, newer) = match args {
+        [older] => (read_schema(older)?, builtin_schema()?),
+        [older, newer] => (read_schema(older)?, read_schema(newer)?),
+        _ => return Err(error::GeneralError::Usage(USAGE).into()),
+    };
+
+    let diff = older.diff(&newer)?;
+    println!("{}", diff);
+
+    if diff.is_compatible() {
+        Ok(())
+    } else {
+        Err(error::GeneralError::BreakingSchemaChanges(diff.breaking().count()).into())
+    }
+}
+
+// warns about things in a schema, the built-in one by default, that are valid but
+// most likely mistakes
+pub fn lint_schema(args: &[String]) -> anyhow::Result<()> {
+    const USAGE: &str = "rp_shell lint-schema [<schema>]";
+
+    let schema = match args {
+        [] => builtin_schema()?,
+        [path] => read_schema(path)?,
+        _ => return Err(error::GeneralError::Usage(USAGE).into()),
+    };
+
+    let warnings = schema.lint()?;
+    for warning in &warnings {
+        println!("warning: {}", warning);
+    }
+    println!("{} warning(s)", warnings.len());
+
+    Ok(())
+}
+
+// the reference manual of a schema, the built-in one by default, as Markdown unless
+// a man page is asked for
+pub fn print_reference(args: &[String]) -> anyhow::Result<()> {
+    const USAGE: &str = "rp_shell reference [markdown|man] [<schema>]";
+
+    let (format, rest) = match args.split_first() {
+        Some((format, rest)) if format == "markdown" => (ReferenceFormat::Markdown, rest),
+        Some((format, rest)) if format == "man" => (ReferenceFormat::Man, rest),
+        _ => (ReferenceFormat::Markdown, args),
+    };
+
+    let schema = match rest {
+        [] => builtin_schema()?,
+        [path] => read_schema(path)?,
+        _ => return Err(error::GeneralError::Usage(USAGE).into()),
+    };
+
+    print!("{}", schema.reference(format));
+    Ok(())
+}
+
+fn read_schema(path: &str) -> anyhow::Result<Schema> {
+    let path = Path::new(path);
+    match path.extension().and_then(|extension| extension.to_str()) {
+        Some("yml") | Some("yaml") => {
+            let mut schema = Schema::from_yaml_path(path)?;
+            schema.validate()?;
+            Ok(schema)
+        }
+        _ => Schema::from_binary(&fs::read(path)?),
+    }
+}
+
 async fn process(shell: &mut Shell, editor: &mut ConfigEditor<'_>) -> anyhow::Result<()> {
     shell.prompt = get_prompt(shell, editor);
     let command = shell.process_input().await?;
@@ -1,5 +1,13 @@
 use futures::executor::block_on;
+use std::env;
 
 fn main() -> anyhow::Result<()> {
-    block_on(rp_shell::run())
+    let args: Vec<String> = env::args().skip(1).collect();
+
+    match args.first().map(String::as_str) {
+        Some("diff-schema") => rp_shell::diff_schema(&args[1..]),
+        Some("lint-schema") => rp_shell::lint_schema(&args[1..]),
+        Some("reference") => rp_shell::print_reference(&args[1..]),
+        _ => block_on(rp_shell::run()),
+    }
 }
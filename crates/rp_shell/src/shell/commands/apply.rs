@@ -23,6 +23,11 @@ pub struct Discard;
 
 impl ExecutableCommand for Apply {
     fn run(&self, _shell: &mut Shell, editor: &mut ConfigEditor) -> anyhow::Result<()> {
+        // changes to nodes that have disappeared from the system can't be applied
+        if editor.refresh_sources()? {
+            debug!("Sourced nodes refreshed");
+        }
+
         if editor.is_clean() {
             info!("No changes to apply (configuration clean)");
         } else if editor.apply_changes()? {
@@ -2,13 +2,19 @@ use super::{ExecutableCommand, Shell};
 use crate::ConfigEditor;
 use command_metadata::command;
 use rp_core::common::{CommandFromArgs, CommandMetadata, ShellMode};
+use rp_log::*;
 
 #[command(required_shell_mode = "Operational")]
 #[derive(Debug)]
 pub struct Configure;
 
 impl ExecutableCommand for Configure {
-    fn run(&self, shell: &mut Shell, _editor: &mut ConfigEditor) -> anyhow::Result<()> {
+    fn run(&self, shell: &mut Shell, editor: &mut ConfigEditor) -> anyhow::Result<()> {
+        // the system may have changed while in operational mode
+        if editor.refresh_sources()? {
+            debug!("Sourced nodes refreshed");
+        }
+
         shell.enter_mode()
     }
 }
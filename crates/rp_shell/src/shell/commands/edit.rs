@@ -3,8 +3,10 @@ use crate::ConfigEditor;
 use command_metadata::command;
 use rp_core::{
     common::{CommandFromArgs, CommandMetadata, ShellMode},
+    config::Position,
     error::ExpectedValue,
 };
+use strum::{EnumString, EnumVariantNames};
 
 #[command(required_shell_mode = "Configuration")]
 #[derive(Debug)]
@@ -34,6 +36,40 @@ pub struct Remove {
     value: Option<String>,
 }
 
+#[command(required_shell_mode = "Configuration")]
+#[derive(Debug)]
+pub struct Insert {
+    item: String,
+    value: String,
+    side: Side,
+    anchor: String,
+}
+
+#[command(required_shell_mode = "Configuration")]
+#[derive(Debug)]
+pub struct Move {
+    item: String,
+    value: String,
+    side: Side,
+    anchor: String,
+}
+
+#[derive(Debug, EnumString, EnumVariantNames)]
+#[strum(serialize_all = "lowercase")]
+enum Side {
+    Before,
+    After,
+}
+
+impl Side {
+    fn position(&self, anchor: &str) -> Position {
+        match self {
+            Side::Before => Position::Before(anchor.to_owned()),
+            Side::After => Position::After(anchor.to_owned()),
+        }
+    }
+}
+
 impl ExecutableCommand for Edit {
     fn run(&self, _shell: &mut Shell, editor: &mut ConfigEditor) -> anyhow::Result<()> {
         for arg in &self.nodes {
@@ -75,3 +111,15 @@ impl ExecutableCommand for Remove {
         editor.remove_node(&self.item)
     }
 }
+
+impl ExecutableCommand for Insert {
+    fn run(&self, _shell: &mut Shell, editor: &mut ConfigEditor) -> anyhow::Result<()> {
+        editor.insert(&self.item, &self.value, &self.side.position(&self.anchor))
+    }
+}
+
+impl ExecutableCommand for Move {
+    fn run(&self, _shell: &mut Shell, editor: &mut ConfigEditor) -> anyhow::Result<()> {
+        editor.move_item(&self.item, &self.value, &self.side.position(&self.anchor))
+    }
+}
@@ -0,0 +1,88 @@
+use super::{ExecutableCommand, Shell};
+use crate::ConfigEditor;
+use command_metadata::command;
+use rp_core::common::{CommandFromArgs, CommandMetadata, ShellMode};
+
+#[command(required_shell_mode = "Configuration")]
+#[derive(Debug)]
+pub struct Help {
+    item: Option<String>,
+}
+
+impl ExecutableCommand for Help {
+    fn run(&self, shell: &mut Shell, editor: &mut ConfigEditor) -> anyhow::Result<()> {
+        match &self.item {
+            Some(item) => describe_item(shell, editor, item),
+            None => {
+                if let Some(description) = editor.get_current_description() {
+                    shell.write(format_args!("{}\n\n", description))?;
+                }
+
+                print_descriptions(shell, &editor.get_descriptions())
+            }
+        }
+    }
+}
+
+fn describe_item(shell: &Shell, editor: &ConfigEditor, item: &str) -> anyhow::Result<()> {
+    // properties are looked up first like in remove
+    if let Some(properties) = editor.get_available_properties() {
+        if properties.contains(&item.to_owned()) {
+            let property = editor.get_property(item)?;
+            shell.write(format_args!(
+                "{}: {}\n\nAllowed values:\n",
+                item,
+                property.description().unwrap_or("no description")
+            ))?;
+
+            let values: Vec<(String, Option<String>)> = property
+                .allowed_values()
+                .iter()
+                .map(|allowed| (format!("{}", allowed.value), allowed.description.clone()))
+                .collect();
+            print_descriptions(shell, &values)?;
+
+            let candidates = editor.get_value_candidates(item)?;
+            if !candidates.is_empty() {
+                shell.write(format_args!("\nExisting nodes:\n"))?;
+                for candidate in candidates {
+                    shell.write(format_args!("  {}\n", candidate))?;
+                }
+            }
+
+            return Ok(());
+        }
+    }
+
+    let description = editor.get_node_description(item)?;
+    shell.write(format_args!(
+        "{}: {}\n",
+        item,
+        description.as_deref().unwrap_or("no description")
+    ))
+}
+
+fn print_descriptions(
+    shell: &Shell,
+    descriptions: &[(String, Option<String>)],
+) -> anyhow::Result<()> {
+    let width = descriptions
+        .iter()
+        .map(|(name, _)| name.len())
+        .max()
+        .unwrap_or(0);
+
+    for (name, description) in descriptions {
+        match description {
+            Some(description) => shell.write(format_args!(
+                "  {:width$}  {}\n",
+                name,
+                description,
+                width = width
+            ))?,
+            None => shell.write(format_args!("  {}\n", name))?,
+        }
+    }
+
+    Ok(())
+}
@@ -2,6 +2,7 @@ mod apply;
 mod configure;
 mod edit;
 mod exit;
+mod help;
 mod history;
 mod save_load;
 mod show;
@@ -10,9 +11,10 @@ use super::{super::ConfigEditor, Shell, ShellMode};
 use apply::{Apply, Discard};
 use command_metadata::CommandEnum;
 use configure::Configure;
-use edit::{Edit, Remove, Set, Top, Up};
+use edit::{Edit, Insert, Move, Remove, Set, Top, Up};
 use enum_dispatch::enum_dispatch;
 use exit::Exit;
+use help::Help;
 use history::History;
 use rp_core::common::{CommandFromArgs, CommandMetadata};
 use save_load::{Load, Save};
@@ -34,9 +36,12 @@ pub enum Command {
     Top,
     Set,
     Remove,
+    Insert,
+    Move,
     History,
     Apply,
     Discard,
     Save,
     Load,
+    Help,
 }
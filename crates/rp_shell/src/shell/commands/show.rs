@@ -30,6 +30,11 @@ impl ExecutableCommand for Show {
                         editor.pretty_print_config();
                         Ok(())
                     }
+                    ShowArgument::Schema => match &self.args[1..] {
+                        [] => editor.print_schema(false),
+                        [model] if model == "model" => editor.print_schema(true),
+                        path => editor.print_schema_path(path),
+                    },
                     _ => Err(error::NotImplemented {
                         description: format!("{:?}", a),
                     }
@@ -0,0 +1,47 @@
+use rp_core::common::{source_registry, Context};
+use std::fs;
+
+pub fn register() -> anyhow::Result<()> {
+    source_registry::register_function("system::net::link::list", link_list)?;
+    source_registry::register_property("{interface}.address", link_address)?;
+    source_registry::register_property("{interface}.mtu", link_mtu)?;
+    source_registry::register_property("{interface}.vlans", link_vlans)?;
+    Ok(())
+}
+
+fn link_list(context: &Context) -> anyhow::Result<Vec<String>> {
+    let path = context.format(String::from("{mock}/sys/class/net"))?;
+    let mut links = Vec::new();
+
+    for entry in fs::read_dir(path)? {
+        links.push(entry?.file_name().to_string_lossy().into_owned());
+    }
+
+    Ok(links)
+}
+
+// VLAN links are named after their parent link and VLAN ID, e.g. eth0.10
+fn link_vlans(context: &Context) -> anyhow::Result<Vec<String>> {
+    let prefix = context.format(String::from("{interface}."))?;
+    Ok(link_list(context)?
+        .into_iter()
+        .filter(|link| link.starts_with(&prefix))
+        .map(|link| link[prefix.len()..].to_owned())
+        .collect())
+}
+
+fn link_address(context: &Context) -> anyhow::Result<Vec<String>> {
+    read_link_attribute(context, "address")
+}
+
+fn link_mtu(context: &Context) -> anyhow::Result<Vec<String>> {
+    read_link_attribute(context, "mtu")
+}
+
+fn read_link_attribute(context: &Context, attribute: &str) -> anyhow::Result<Vec<String>> {
+    let path = context.format(format!(
+        "{{mock}}/sys/class/net/{{interface}}/{}",
+        attribute
+    ))?;
+    Ok(vec![fs::read_to_string(&path)?.trim().to_owned()])
+}
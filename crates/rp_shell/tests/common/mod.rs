@@ -1,14 +1,20 @@
-use rp_core::{config::Config, schema::Schema};
+use rp_core::{common::source_registry, config::Config, schema::Schema};
 use std::{
+    fs,
     io::{Cursor, Read, Seek, SeekFrom, Write},
-    rc::Rc,
+    sync::Arc,
 };
 
 fn buffer() -> Cursor<Vec<u8>> {
     Cursor::new(Vec::new())
 }
 
-pub fn get_schema_and_config() -> anyhow::Result<(Rc<Schema>, Config)> {
+pub fn get_schema_and_config() -> anyhow::Result<(Arc<Schema>, Config)> {
+    source_registry::register_function("test::cat", |context| {
+        let path = context.format(String::from("{mock}/test"))?;
+        Ok(vec![fs::read_to_string(&path)?.trim().to_owned()])
+    })?;
+
     let mut buf = buffer();
     let schema = r#"---
 templates:
@@ -16,18 +22,23 @@ templates:
     regex: "[0-9]"
 nodes:
   "multinode":
+    ordered: true
     source:
+      id: number
       template: digit
     node:
       properties:
         "simple":
           values:
           - literal: a
+            description: The letter a
   "singlenode":
+    description: A single node
     subnodes:
       "subnode": {}
     properties:
       "simple":
+        description: A simple property
         values:
         - template: digit
       "default":
@@ -42,12 +53,15 @@ nodes:
         - literal: "1"
         values:
         - template: digit
+      "ordered":
+        multiple: true
+        ordered: true
+        values:
+        - template: digit
       "query_default":
         default:
-          - query:
-              id: test
-              command:
-                cat: /test
+          - command:
+              function: "test::cat"
         values:
           - literal: a"#;
 
@@ -64,10 +78,10 @@ nodes:
 
     let mut bytes = Vec::new();
     buf.read_to_end(&mut bytes)?;
-    let schema = Rc::new(Schema::from_binary(&bytes)?);
+    let schema = Arc::new(Schema::from_binary(&bytes)?);
 
     Ok((
-        Rc::clone(&schema),
-        Config::from_schema(Rc::downgrade(&schema))?,
+        Arc::clone(&schema),
+        Config::from_schema(Arc::downgrade(&schema))?,
     ))
 }
@@ -1,6 +1,7 @@
 mod common;
 
 use anyhow::anyhow;
+use rp_core::config::Position;
 use rp_shell::ConfigEditor;
 
 fn assert_property(
@@ -201,3 +202,65 @@ fn remove_top_level_node() -> anyhow::Result<()> {
         }
     }
 }
+
+#[test]
+fn insert_and_move_property_values() -> anyhow::Result<()> {
+    let (schema, config) = common::get_schema_and_config()?;
+    let mut editor = ConfigEditor::new(&config, schema.as_ref());
+
+    editor.edit_node("singlenode")?;
+    editor.set_property_value("ordered", "1")?;
+    editor.set_property_value("ordered", "3")?;
+    editor.insert("ordered", "2", &Position::Before(String::from("3")))?;
+    editor.move_item("ordered", "1", &Position::After(String::from("3")))?;
+
+    let values = editor
+        .get_property_values(Some(String::from("ordered")))
+        .and_then(|values| values.get("ordered").cloned());
+    if values
+        == Some(vec![
+            String::from("2"),
+            String::from("3"),
+            String::from("1"),
+        ])
+    {
+        Ok(())
+    } else {
+        Err(anyhow!("wrong order: {:?}", values))
+    }
+}
+
+#[test]
+fn insert_node() -> anyhow::Result<()> {
+    let (schema, config) = common::get_schema_and_config()?;
+    let mut editor = ConfigEditor::new(&config, schema.as_ref());
+
+    editor.edit_node("multinode")?;
+    editor.edit_node("1")?;
+    editor.go_top()?;
+    editor.insert("multinode", "2", &Position::After(String::from("1")))?;
+    editor.edit_node("multinode")?;
+    editor.edit_node("2")?;
+
+    Ok(())
+}
+
+#[test]
+fn move_unordered_property_value() -> anyhow::Result<()> {
+    let (schema, config) = common::get_schema_and_config()?;
+    let mut editor = ConfigEditor::new(&config, schema.as_ref());
+
+    editor.edit_node("singlenode")?;
+    let result = editor.move_item("multiple", "0", &Position::After(String::from("1")));
+
+    match result {
+        Ok(_) => Err(anyhow!("moving an unordered value succeeded")),
+        Err(e) => {
+            if let Some(rp_core::error::OrderError::NotOrdered(_)) = e.downcast_ref() {
+                Ok(())
+            } else {
+                Err(e)
+            }
+        }
+    }
+}
@@ -0,0 +1,68 @@
+mod common;
+
+use anyhow::anyhow;
+use rp_shell::ConfigEditor;
+
+#[test]
+fn node_description() -> anyhow::Result<()> {
+    let (schema, config) = common::get_schema_and_config()?;
+    let editor = ConfigEditor::new(&config, schema.as_ref());
+
+    match editor.get_node_description("singlenode")? {
+        Some(description) if description == "A single node" => Ok(()),
+        description => Err(anyhow!("wrong node description: {:?}", description)),
+    }
+}
+
+#[test]
+fn nonexistent_node_description() -> anyhow::Result<()> {
+    let (schema, config) = common::get_schema_and_config()?;
+    let editor = ConfigEditor::new(&config, schema.as_ref());
+
+    match editor.get_node_description("nonexistent") {
+        Ok(_) => Err(anyhow!("nonexistent node had a description")),
+        Err(e) => {
+            if let Some(rp_shell::error::ConfigEditorError::NodeNotFound { .. }) = e.downcast_ref()
+            {
+                Ok(())
+            } else {
+                Err(e)
+            }
+        }
+    }
+}
+
+#[test]
+fn property_descriptions() -> anyhow::Result<()> {
+    let (schema, config) = common::get_schema_and_config()?;
+    let mut editor = ConfigEditor::new(&config, schema.as_ref());
+
+    editor.edit_node("singlenode")?;
+    let descriptions = editor.get_descriptions();
+
+    if !descriptions.contains(&(
+        String::from("simple"),
+        Some(String::from("A simple property")),
+    )) {
+        Err(anyhow!("property description missing: {:?}", descriptions))
+    } else if !descriptions.contains(&(String::from("default"), None)) {
+        Err(anyhow!("undescribed property missing: {:?}", descriptions))
+    } else {
+        Ok(())
+    }
+}
+
+#[test]
+fn value_description() -> anyhow::Result<()> {
+    let (schema, config) = common::get_schema_and_config()?;
+    let mut editor = ConfigEditor::new(&config, schema.as_ref());
+
+    editor.edit_node("multinode")?;
+    editor.edit_node("0")?;
+    let allowed = editor.get_property("simple")?.allowed_values();
+
+    match allowed.first().and_then(|a| a.description.as_deref()) {
+        Some("The letter a") => Ok(()),
+        description => Err(anyhow!("wrong value description: {:?}", description)),
+    }
+}